@@ -0,0 +1,203 @@
+use {
+    nmde::nmd::{
+        anatomy::NmdFileVersion,
+        NmdFileBoneOrder,
+    },
+    std::{
+        fmt::{
+            self,
+            Display,
+            Formatter,
+        },
+        path::PathBuf,
+    },
+};
+
+#[derive(Debug)]
+pub enum Command {
+    Bones(PathBuf),
+    Export(PathBuf, PathBuf, ExportOptions),
+    FromJson(PathBuf, PathBuf),
+    FromSkeleton(PathBuf, PathBuf),
+    Help,
+    Info(PathBuf),
+    Stats(PathBuf),
+    ToCsv(PathBuf, Option<PathBuf>),
+    ToGltf(PathBuf, Option<PathBuf>),
+    ToJson(PathBuf, Option<PathBuf>),
+    ToSkeleton(PathBuf, Option<PathBuf>),
+    Validate(Vec<PathBuf>),
+    Verify(Vec<PathBuf>),
+}
+
+#[derive(Debug, Default)]
+pub struct ExportOptions {
+    // As read if not given
+    pub version_opt: Option<NmdFileVersion>,
+    // Whether to copy the file being replaced aside first
+    pub backing_up: bool,
+    pub bone_order: NmdFileBoneOrder,
+}
+
+#[derive(Debug)]
+pub struct CommandError {
+    description: String,
+}
+
+impl Command {
+    pub const USAGE: &'static str = concat!(
+        "Usage: nmde-cli <command> [arguments…]\n",
+        "\n",
+        "Commands:\n",
+        "    info <file.nmd>                     Print header fields, bone count and block sizes\n",
+        "    bones <file.nmd>                    Print every bone as a table\n",
+        "    validate <file.nmd>…                Check files for problems, with the byte offset of each\n",
+        "                                        (exit code 1 if any is an error)\n",
+        "    to-csv <file.nmd> [out.csv]         Write the bone table as CSV, one row per bone (to stdout\n",
+        "                                        if no output)\n",
+        "    to-gltf <file.nmd> [out.gltf]       Write the bone hierarchy as a glTF 2.0 skeleton (to stdout\n",
+        "                                        if no output)\n",
+        "    to-json <file.nmd> [out.json]       Dump file data as JSON (to stdout if no output)\n",
+        "    from-json <file.json> <out.nmd>     Write an NMD file from JSON made by `to-json`\n",
+        "    to-skeleton <file.nmd> [out]        Write the skeleton as TOML, or JSON if `out` ends in\n",
+        "                                        .json, for editing by hand (to stdout as TOML if no\n",
+        "                                        output)\n",
+        "    from-skeleton <skeleton> <out.nmd>  Write an NMD file from a skeleton made by `to-skeleton`\n",
+        "    export <file.nmd> <out.nmd> [game]  Read a file and write it back out through the writer,\n",
+        "           [--backup] [--by-id]         laid out for SCIV, SCV or SCVI (default: as read).\n",
+        "                                        Files with physics or blob bytes that aren't decoded\n",
+        "                                        only go to games with the byte order they were read in;\n",
+        "                                        SCIV and SCV share theirs and a layout. `--backup` first\n",
+        "                                        copies any file it replaces to\n",
+        "                                        <out.nmd>.<seconds since 1970>.bak;\n",
+        "                                        `--by-id` writes bones in ID order rather than as read\n",
+        "    verify <file.nmd>…                  Check that files survive an unedited export byte for byte\n",
+        "    stats <dir>                         Tally every field's values by bone flag over the NMD files\n",
+        "                                        under a folder, with flag bytes of no known kind and\n",
+        "                                        correlations between unknown bytes and known fields\n",
+        "    help                                Print this message\n",
+        "\n",
+        "Byte ranges named in `annotations.toml` (in the working directory) are shown by name.",
+    );
+
+    fn expect_args(name: &str, args: &[String], min: usize, max: usize) -> Result<(), CommandError> {
+        if args.len() < min {
+            Err(CommandError::new(format!("`{}` is missing arguments", name)))
+        } else if args.len() > max {
+            Err(CommandError::new(format!("`{}` was given too many arguments", name)))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl TryFrom<Vec<String>> for Command {
+    type Error = CommandError;
+
+    /// Parse a command from program arguments, not including the program name.
+    fn try_from(mut args: Vec<String>) -> Result<Command, CommandError> {
+        if args.is_empty() {
+            return Ok(Command::Help);
+        }
+
+        let name = args.remove(0);
+        let path = |i: usize| PathBuf::from(&args[i]);
+
+        match name.as_str() {
+            "bones" => {
+                Self::expect_args(&name, &args, 1, 1)?;
+
+                Ok(Command::Bones(path(0)))
+            }
+            "export" => {
+                let mut options = ExportOptions::default();
+                let mut paths = Vec::new();
+
+                for arg in &args {
+                    match arg.as_str() {
+                        "--backup" => options.backing_up = true,
+                        "--by-id" => options.bone_order = NmdFileBoneOrder::ById,
+                        flag if flag.starts_with("--") => return Err(CommandError::new(format!("Unknown option `{}`", flag))),
+                        _ => paths.push(arg.to_owned()),
+                    }
+                }
+
+                Self::expect_args(&name, &paths, 2, 3)?;
+
+                if let Some(text) = paths.get(2) {
+                    options.version_opt = Some(text.parse().map_err(CommandError::new)?);
+                }
+
+                Ok(Command::Export(PathBuf::from(&paths[0]), PathBuf::from(&paths[1]), options))
+            }
+            "from-json" => {
+                Self::expect_args(&name, &args, 2, 2)?;
+
+                Ok(Command::FromJson(path(0), path(1)))
+            }
+            "from-skeleton" => {
+                Self::expect_args(&name, &args, 2, 2)?;
+
+                Ok(Command::FromSkeleton(path(0), path(1)))
+            }
+            "help" | "-h" | "--help" => {
+                Ok(Command::Help)
+            }
+            "info" => {
+                Self::expect_args(&name, &args, 1, 1)?;
+
+                Ok(Command::Info(path(0)))
+            }
+            "stats" => {
+                Self::expect_args(&name, &args, 1, 1)?;
+
+                Ok(Command::Stats(path(0)))
+            }
+            "to-csv" => {
+                Self::expect_args(&name, &args, 1, 2)?;
+
+                Ok(Command::ToCsv(path(0), (args.len() > 1).then(|| path(1))))
+            }
+            "to-gltf" => {
+                Self::expect_args(&name, &args, 1, 2)?;
+
+                Ok(Command::ToGltf(path(0), (args.len() > 1).then(|| path(1))))
+            }
+            "to-json" => {
+                Self::expect_args(&name, &args, 1, 2)?;
+
+                Ok(Command::ToJson(path(0), (args.len() > 1).then(|| path(1))))
+            }
+            "to-skeleton" => {
+                Self::expect_args(&name, &args, 1, 2)?;
+
+                Ok(Command::ToSkeleton(path(0), (args.len() > 1).then(|| path(1))))
+            }
+            "validate" => {
+                Self::expect_args(&name, &args, 1, usize::MAX)?;
+
+                Ok(Command::Validate(args.iter().map(PathBuf::from).collect()))
+            }
+            "verify" => {
+                Self::expect_args(&name, &args, 1, usize::MAX)?;
+
+                Ok(Command::Verify(args.iter().map(PathBuf::from).collect()))
+            }
+            _ => Err(CommandError::new(format!("Unknown command `{}`", name))),
+        }
+    }
+}
+
+impl CommandError {
+    fn new(description: String) -> Self {
+        Self {
+            description: description,
+        }
+    }
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.description)
+    }
+}
@@ -0,0 +1,233 @@
+// Struct fields are initialised as `field: field`, as in the library
+#![allow(clippy::redundant_field_names)]
+
+mod command;
+mod report;
+
+use {
+    command::{
+        Command,
+        ExportOptions,
+    },
+    nmde::nmd::{
+        data::NmdFileData,
+        NmdFile,
+        NmdFileAnnotations,
+        NmdFileBoneTable,
+        NmdFileCorpus,
+        NmdFileGltf,
+        NmdFileMap,
+        NmdFileSave,
+        NmdFileSeverity,
+        NmdFileSkeleton,
+        NmdFileValidator,
+        NmdFileVerifier,
+        NmdFileWriter,
+    },
+    std::{
+        env,
+        fs::{
+            self,
+            File,
+        },
+        io::{
+            self,
+            BufReader,
+            Error,
+            ErrorKind,
+            Result,
+            Write,
+        },
+        path::PathBuf,
+        process,
+    },
+};
+
+fn main() {
+    let command = match Command::try_from(env::args().skip(1).collect::<Vec<_>>()) {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("nmde-cli: {}\n\n{}", error, Command::USAGE);
+            process::exit(2);
+        }
+    };
+
+    match run(command) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(error) => {
+            eprintln!("nmde-cli: {}", error);
+            process::exit(1);
+        }
+    }
+}
+
+/// Run a command, returning whether it succeeded in the sense of its exit
+/// status (e.g. `validate` finding problems is not an I/O error).
+fn run(command: Command) -> Result<bool> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    match command {
+        Command::Bones(path) => {
+            report::bones(&mut out, &read(&path)?, &annotations()?)?;
+        }
+        Command::Export(path, out_path, options) => {
+            let data = read(&path)?;
+
+            if let Some(backup_path) = write(&out_path, &data, &options)? {
+                writeln!(out, "{}: backed up to {}", out_path.display(), backup_path.display())?;
+            }
+        }
+        Command::FromJson(path, out_path) => {
+            let data: NmdFileData = serde_json::from_reader(BufReader::new(File::open(&path)?))
+                .map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+            write(&out_path, &data, &ExportOptions::default())?;
+        }
+        Command::FromSkeleton(path, out_path) => {
+            let skeleton = NmdFileSkeleton::try_from(&path)
+                .map_err(|error| Error::new(error.kind(), format!("{}: {}", path.display(), error)))?;
+
+            write(&out_path, &NmdFileData::from(&skeleton), &ExportOptions::default())?;
+        }
+        Command::Help => {
+            writeln!(out, "{}", Command::USAGE)?;
+        }
+        Command::Info(path) => {
+            report::info(&mut out, &read(&path)?, fs::metadata(&path)?.len())?;
+        }
+        Command::Stats(path) => {
+            let mut corpus = NmdFileCorpus::new();
+
+            corpus.scan(&path)
+                .map_err(|error| Error::new(error.kind(), format!("{}: {}", path.display(), error)))?;
+            report::statistics(&mut out, &corpus)?;
+        }
+        Command::ToCsv(path, out_path_opt) => {
+            let table: NmdFileBoneTable = read(&path)?.bones.values().collect();
+
+            match out_path_opt {
+                Some(out_path) => table.save(&out_path)?,
+                None => write!(out, "{}", table.to_csv())?,
+            }
+        }
+        Command::ToGltf(path, out_path_opt) => {
+            let gltf = NmdFileGltf::from(&read(&path)?);
+
+            match out_path_opt {
+                Some(out_path) => gltf.save(&out_path)?,
+                None => writeln!(out, "{}", gltf.to_json()?)?,
+            }
+        }
+        Command::ToJson(path, out_path_opt) => {
+            let data = read(&path)?;
+
+            match out_path_opt {
+                Some(out_path) => serde_json::to_writer_pretty(File::create(&out_path)?, &data),
+                None => serde_json::to_writer_pretty(&mut out, &data),
+            }.map_err(Error::other)?;
+        }
+        Command::ToSkeleton(path, out_path_opt) => {
+            let skeleton = NmdFileSkeleton::from(&read(&path)?);
+
+            match out_path_opt {
+                Some(out_path) => skeleton.save(&out_path)?,
+                None => write!(out, "{}", skeleton.to_toml()?)?,
+            }
+        }
+        Command::Validate(paths) => {
+            let mut valid = true;
+
+            for path in paths {
+                let data = match read(&path) {
+                    Ok(data) => data,
+                    Err(error) => {
+                        writeln!(out, "{}", error)?;
+                        valid = false;
+                        continue;
+                    }
+                };
+                let findings = NmdFileValidator::from(&data).validate();
+
+                if findings.is_empty() {
+                    writeln!(out, "{}: ok", path.display())?;
+                }
+
+                for line in report::findings(&data, &findings) {
+                    writeln!(out, "{}: {}", path.display(), line)?;
+                }
+
+                valid &= findings.iter().all(|finding| finding.severity() != NmdFileSeverity::Error);
+            }
+
+            return Ok(valid);
+        }
+        Command::Verify(paths) => {
+            let annotations = annotations()?;
+            let mut identical = true;
+
+            for path in paths {
+                match NmdFileVerifier::try_from(&path).and_then(|verifier| verifier.verify()) {
+                    Ok(None) => {
+                        writeln!(out, "{}: ok", path.display())?;
+                    }
+                    Ok(Some(divergence)) => {
+                        let bytes = fs::read(&path)?;
+                        let annotation_opt = NmdFileMap::try_from(&bytes[..]).ok()
+                            .and_then(|map| map.annotation_at(&annotations, divergence.offset as usize).cloned());
+
+                        match annotation_opt {
+                            Some(annotation) => writeln!(out, "{}: {} ({})", path.display(), divergence, annotation.name)?,
+                            None => writeln!(out, "{}: {}", path.display(), divergence)?,
+                        }
+
+                        identical = false;
+                    }
+                    Err(error) => {
+                        writeln!(out, "{}: {}", path.display(), error)?;
+                        identical = false;
+                    }
+                }
+            }
+
+            return Ok(identical);
+        }
+    }
+
+    Ok(true)
+}
+
+// Names for byte ranges from the working directory's annotation file, if any
+fn annotations() -> Result<NmdFileAnnotations> {
+    let path = PathBuf::from(NmdFileAnnotations::DEFAULT_PATH);
+
+    match NmdFileAnnotations::try_from(&path) {
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(NmdFileAnnotations::default()),
+        Err(error) => Err(Error::new(error.kind(), format!("{}: {}", path.display(), error))),
+        annotations => annotations,
+    }
+}
+
+fn read(path: &PathBuf) -> Result<NmdFileData> {
+    NmdFile::try_from(path)
+        .map(|file| file.data)
+        .map_err(|error| {
+            let error = Error::from(error);
+
+            Error::new(error.kind(), format!("{}: {}", path.display(), error))
+        })
+}
+
+// Written beside `path` and moved over it once it reads back, so a failed
+// write leaves it as it was. Gives the backup's path, if one was made
+fn write(path: &PathBuf, data: &NmdFileData, options: &ExportOptions) -> Result<Option<PathBuf>> {
+    let save = NmdFileSave::try_from(path)?.with_backup(options.backing_up);
+    let mut writer = NmdFileWriter::new(save).with_bone_order(options.bone_order);
+
+    if let Some(version) = options.version_opt {
+        writer = writer.for_version(version);
+    }
+
+    Ok(writer.write_new(data, &data.bones, &data.conflicts, &data.physics, &data.conflict_physics)?.commit()?)
+}
@@ -0,0 +1,202 @@
+use {
+    nmde::nmd::{
+        anatomy::{
+            token::NmdFileToken,
+            NmdFileBone,
+            NmdFileHeader,
+        },
+        data::{
+            NmdFileData,
+            NmdFileParentSuggestion,
+        },
+        NmdFileAnnotations,
+        NmdFileCorpus,
+        NmdFileFinding,
+        NmdFileFindingKind,
+    },
+    std::{
+        io::{
+            Result,
+            Write,
+        },
+    },
+};
+
+// Most frequent values shown per token and flag
+const STATISTICS_VALUE_COUNT: usize = 5;
+const STATISTICS_CORRELATION_COUNT: usize = 20;
+const STATISTICS_MIN_R: f64 = 0.5;
+const STATISTICS_MIN_N: usize = 10;
+
+/// Print header fields, bone count and the size of each block. `file_length`
+/// is the on-disk size, used to size the (trailing) name block.
+pub fn info(out: &mut impl Write, data: &NmdFileData, file_length: u64) -> Result<()> {
+    let header = &data.header;
+    let name_data_length = file_length.saturating_sub(header.bone_name_data_address as u64);
+
+    writeln!(out, "Game:                       {} ({:?})", data.version, data.version.byte_order())?;
+
+    for version in &data.other_versions {
+        writeln!(out, "    or:                     {}, whose header is laid out the same", version)?;
+    }
+
+    writeln!(out, "Header")?;
+    writeln!(out, "    bone count:             {}", header.bone_count)?;
+    writeln!(out, "    bone data address:      {:#010X}", header.bone_data_address)?;
+    writeln!(out, "    physics data address:   {:#010X}", header.physics_data_address())?;
+    writeln!(out, "    blob data address:      {:#010X}", header.blob_data_address)?;
+    writeln!(out, "    bone name data address: {:#010X}", header.bone_name_data_address)?;
+    writeln!(out, "Blocks")?;
+    writeln!(out, "    header:                 {:#X} bytes", NmdFileHeader::CHUNK_SIZE)?;
+    writeln!(out, "    bone data:              {:#X} bytes", header.bone_data_length())?;
+    writeln!(out, "    physics data:           {:#X} bytes", header.physics_data_length())?;
+    writeln!(out, "    blob data:              {:#X} bytes", header.blob_data_length())?;
+    writeln!(out, "    bone name data:         {:#X} bytes", name_data_length)?;
+    writeln!(out, "    total:                  {:#X} bytes", file_length)?;
+    writeln!(out, "Bones")?;
+    writeln!(out, "    read:                   {}", data.bones.len())?;
+    writeln!(out, "    conflicting records:    {}", data.conflicts.len())?;
+    writeln!(out, "    orphans:                {}", data.orphans().count())?;
+    writeln!(out, "    physics:                {}", data.bones.values().filter(|bone_data| bone_data.is_phys()).count())?;
+    writeln!(out, "    physics records:        {}", data.physics.len())?;
+    writeln!(out, "Blob")?;
+
+    for annotation in data.blob.annotations() {
+        writeln!(out, "    {:#06X}..{:#06X}:         {}", annotation.range.start, annotation.range.end, annotation.label)?;
+    }
+
+    Ok(())
+}
+
+/// One row per bone, in ID order, then one per conflicting record. Flags of no
+/// known type show the names given to them in the annotations.
+pub fn bones(out: &mut impl Write, data: &NmdFileData, annotations: &NmdFileAnnotations) -> Result<()> {
+    writeln!(out, "{:<6} {:<6} {:<14} {:<32} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>6} {:>6} {:>4} {:>4} {:>4} {:>4} {:>10}",
+        "ID", "PARENT", "TYPE", "NAME",
+        "T.X", "T.Y", "T.Z", "R.X", "R.Y", "R.Z", "NEXT.X",
+        "G.X", "G.Y", "C+X", "C-X", "C+Y", "C-Y", "PHYS")?;
+
+    for bone_data in data.bones.values().chain(data.conflicts.values()) {
+        writeln!(out, "{}", bone_row(bone_data, annotations))?;
+    }
+
+    Ok(())
+}
+
+fn bone_row(bone_data: &NmdFileBone, annotations: &NmdFileAnnotations) -> String {
+    format!("{:<6} {:<6} {:<14} {:<32} {:>10.4} {:>10.4} {:>10.4} {:>10.4} {:>10.4} {:>10.4} {:>10.4} {:>6} {:>6} {:>4} {:>4} {:>4} {:>4} {:>10}",
+        format!("{:#04X}", bone_data.id),
+        parent_text(bone_data.parent_id),
+        annotations.flag_label(bone_data.flag),
+        bone_data.name,
+        bone_data.translation_x,
+        bone_data.translation_y,
+        bone_data.translation_z,
+        bone_data.rotation_x,
+        bone_data.rotation_y,
+        bone_data.rotation_z,
+        bone_data.translation_x_next,
+        bone_data.gravity_x,
+        bone_data.gravity_y,
+        bone_data.physics_constraint_x_max,
+        bone_data.physics_constraint_x_min,
+        bone_data.physics_constraint_y_max,
+        bone_data.physics_constraint_y_min,
+        if bone_data.is_phys() {
+            format!("{:#010X}", bone_data.physics_data_address)
+        } else {
+            String::from("-")
+        })
+}
+
+fn parent_text(parent_id: u16) -> String {
+    if parent_id == NmdFileBone::ROOT_BONE_ID {
+        String::from("root")
+    } else {
+        format!("{:#04X}", parent_id)
+    }
+}
+
+/// Describe each validator finding on a line, with a parent suggestion for
+/// orphans.
+pub fn findings(data: &NmdFileData, findings: &[NmdFileFinding]) -> Vec<String> {
+    findings.iter()
+        .map(|finding| match finding.kind {
+            NmdFileFindingKind::Orphan(id, _) => {
+                match NmdFileParentSuggestion::for_orphan(data.bones.values(), id).first() {
+                    Some(suggestion) => format!("{} (maybe {}: {})", finding, suggestion.parent_name, suggestion.reason),
+                    None => finding.to_string(),
+                }
+            }
+            _ => finding.to_string(),
+        })
+        .collect()
+}
+
+/// Print each token's value distribution by bone flag, the flag bytes of no
+/// known kind, and the strongest correlations between unknown bytes and known
+/// fields.
+pub fn statistics(out: &mut impl Write, corpus: &NmdFileCorpus) -> Result<()> {
+    writeln!(out, "Files: {} read, {} failed, {} bone records", corpus.files.len(), corpus.failures.len(), corpus.record_count)?;
+
+    for (path, error) in &corpus.failures {
+        writeln!(out, "    {}: {}", path.display(), error)?;
+    }
+
+    writeln!(out, "Tokens")?;
+
+    for statistics in corpus.tokens() {
+        if statistics.by_flag.is_empty() {
+            continue;
+        }
+
+        writeln!(out, "    {:?}", statistics.token)?;
+
+        for (flag_opt, distribution) in &statistics.by_flag {
+            let flag = match flag_opt {
+                Some(flag) => format!("flag {}", NmdFileCorpus::display_value(NmdFileToken::BoneFlag, &[*flag])),
+                None => "all".to_owned(),
+            };
+            let constant_bytes = distribution.constant_bytes();
+
+            writeln!(out, "        {}: {} read, {} distinct", flag, distribution.count, distribution.values.len())?;
+
+            for (value, count) in distribution.most_frequent(STATISTICS_VALUE_COUNT) {
+                writeln!(out, "            {:>6} × {}", count, NmdFileCorpus::display_value(statistics.token, value))?;
+            }
+
+            if distribution.values.len() > 1 && !constant_bytes.is_empty() {
+                writeln!(out, "            constant bytes: {:?}", constant_bytes)?;
+            }
+        }
+    }
+
+    writeln!(out, "Unknown flags")?;
+
+    if corpus.unknown_flags.is_empty() {
+        writeln!(out, "    none")?;
+    }
+
+    for (flag, unknown_flag) in &corpus.unknown_flags {
+        writeln!(out, "    {:#04X}: {} bones in {} files", flag, unknown_flag.count, unknown_flag.files.len())?;
+
+        for path in &unknown_flag.files {
+            writeln!(out, "        {}", path.display())?;
+        }
+    }
+
+    writeln!(out, "Correlations (|r| ≥ {}, at least {} bones)", STATISTICS_MIN_R, STATISTICS_MIN_N)?;
+
+    let correlations = corpus.correlations(STATISTICS_MIN_R, STATISTICS_MIN_N);
+
+    if correlations.is_empty() {
+        writeln!(out, "    none")?;
+    }
+
+    for correlation in correlations.iter().take(STATISTICS_CORRELATION_COUNT) {
+        writeln!(out, "    {:?} byte {} ~ {:?}: r = {:+.3} (n = {})",
+            correlation.token, correlation.byte, correlation.field, correlation.r, correlation.n)?;
+    }
+
+    Ok(())
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "gui")]
 pub mod fifo;
 pub mod nmd;
+#[cfg(feature = "gui")]
 pub mod utils;
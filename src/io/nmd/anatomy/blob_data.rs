@@ -0,0 +1,109 @@
+use {
+    std::{
+        fmt::{
+            self,
+            Display,
+            Formatter,
+        },
+        ops::Range,
+    },
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+/*
+ * TODO:
+ * ~ Only one sub-structure is identified so far. Add new ones as variants of
+ * `NmdFileBlobSegment`, detected in `NmdFileReader::read_blob`
+ */
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+// The blob data block, split into segments in file order
+pub struct NmdFileBlob {
+    pub segments: Vec<NmdFileBlobSegment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NmdFileBlobSegment {
+    // Bytes with no known structure, kept as-is
+    Raw(Vec<u8>),
+    // The ID of each bone record, in record order; rewritten from the bones
+    // on export so it follows additions, deletions and renumbering
+    BoneIdTable(Vec<u16>),
+}
+
+/// A labelled byte range of the blob, relative to its start.
+#[derive(Debug, Clone)]
+pub struct NmdFileBlobAnnotation {
+    pub range: Range<usize>,
+    pub label: String,
+}
+
+impl NmdFileBlob {
+    /// Label each segment, and each entry of a table.
+    pub fn annotations(&self) -> Vec<NmdFileBlobAnnotation> {
+        let mut annotations = vec!();
+        let mut offset = 0;
+
+        for segment in &self.segments {
+            let length = segment.len();
+
+            annotations.push(NmdFileBlobAnnotation::new(offset..offset + length, segment.to_string()));
+
+            if let NmdFileBlobSegment::BoneIdTable(ids) = segment {
+                for (i, id) in ids.iter().enumerate() {
+                    let entry_offset = offset + i * NmdFileBlobSegment::TABLE_ENTRY_SIZE;
+
+                    annotations.push(NmdFileBlobAnnotation::new(
+                        entry_offset..entry_offset + NmdFileBlobSegment::TABLE_ENTRY_SIZE,
+                        format!("record {}: bone {:#04X}", i, id)));
+                }
+            }
+
+            offset += length;
+        }
+
+        annotations
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.segments.iter().map(NmdFileBlobSegment::len).sum()
+    }
+}
+
+impl NmdFileBlobSegment {
+    pub const TABLE_ENTRY_SIZE: usize = 2;
+
+    // Never empty, as empty segments aren't made
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Raw(bytes)        => bytes.len(),
+            Self::BoneIdTable(ids)  => ids.len() * Self::TABLE_ENTRY_SIZE,
+        }
+    }
+}
+
+impl NmdFileBlobAnnotation {
+    fn new(range: Range<usize>, label: String) -> Self {
+        Self {
+            range: range,
+            label: label,
+        }
+    }
+}
+
+impl Display for NmdFileBlobSegment {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Raw(bytes)        => write!(formatter, "raw data ({:#X} bytes)", bytes.len()),
+            Self::BoneIdTable(ids)  => write!(formatter, "bone ID table ({} entries)", ids.len()),
+        }
+    }
+}
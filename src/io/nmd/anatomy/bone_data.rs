@@ -7,9 +7,10 @@ use {
         Deserialize,
         Serialize,
     },
+    std::ops::RangeInclusive,
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NmdFileBone {
     pub collision_data: [u8; 16],
     pub translation_x: f32,
@@ -42,6 +43,28 @@ impl NmdFileBone {
     pub const ASCII_BYTE_SHIFT: u8 = 0x40;
     pub const CHUNK_SIZE: u64 = 0x70;
     pub const ROOT_BONE_ID: u16 = 0xFFFF;
+    // Each character is stored as its code point plus the shift, in one byte;
+    // a byte at or below the shift ends the name
+    pub const NAME_CHARACTERS: RangeInclusive<char> = '\u{01}'..='\u{BF}';
+
+    /// Name bytes as stored, without the terminator. Fails with the first
+    /// character outside `NAME_CHARACTERS`.
+    pub fn encode_name(name: &str) -> Result<Vec<u8>, char> {
+        name.chars()
+            .map(|character| match Self::NAME_CHARACTERS.contains(&character) {
+                true => Ok(character as u8 + Self::ASCII_BYTE_SHIFT),
+                false => Err(character),
+            })
+            .collect()
+    }
+
+    /// Name stored at the start of `bytes`, up to its terminator.
+    pub fn decode_name(bytes: &[u8]) -> String {
+        bytes.iter()
+            .take_while(|byte| **byte > Self::ASCII_BYTE_SHIFT)
+            .map(|byte| char::from(byte - Self::ASCII_BYTE_SHIFT))
+            .collect()
+    }
 
     pub fn is_phys(&self) -> bool {
         self.flag.is_phys()
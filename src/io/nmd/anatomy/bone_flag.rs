@@ -5,6 +5,7 @@ use {
             Display,
             Formatter,
         },
+        str::FromStr,
     },
     serde::{
         Deserialize, Serialize
@@ -13,8 +14,9 @@ use {
 
 use NmdFileBoneFlag::*;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub enum NmdFileBoneFlag {
+    #[default]
     Standard,
     Weapon,
     Finger,
@@ -33,7 +35,8 @@ pub enum NmdFileBoneFlag {
     ConstRotOffset,
     SlerpOffset,
     Breast,
-    Unknown,
+    // Keeps the byte read so it can be written back as-is
+    Unknown(u8),
 }
 
 pub struct NmdFileBoneFlagIterator {
@@ -42,11 +45,7 @@ pub struct NmdFileBoneFlagIterator {
 
 impl NmdFileBoneFlag {
     pub fn is_phys(&self) -> bool {
-        match self {
-            Swing   => true,
-            Breast  => true,
-            _       => false
-        }
+        matches!(self, Swing | Breast)
     }
 
     pub fn iter() -> NmdFileBoneFlagIterator {
@@ -56,12 +55,6 @@ impl NmdFileBoneFlag {
     }
 }
 
-impl Default for NmdFileBoneFlag {
-    fn default() -> Self {
-        Self::Standard
-    }
-}
-
 impl From<u8> for NmdFileBoneFlag {
     fn from(i: u8) -> Self {
         match i {
@@ -83,7 +76,7 @@ impl From<u8> for NmdFileBoneFlag {
             0x1B            => ConstRotOffset,
             0x1D            => SlerpOffset,
             0x1E            => Breast,
-            unknown         => Unknown,
+            unknown         => Unknown(unknown),
         }
     }
 }
@@ -109,7 +102,7 @@ impl From<NmdFileBoneFlag> for u8 {
             ConstRotOffset  => 0x1B,
             SlerpOffset     => 0x1D,
             Breast          => 0x1E,
-            Unknown         => 0xFF,
+            Unknown(byte)   => byte,
         }
     }
 }
@@ -136,12 +129,31 @@ impl Display for NmdFileBoneFlag {
                 ConstRotOffset  => "ConstRotOffset",
                 SlerpOffset     => "SlerpOffset",
                 Breast          => "Breast",
-                Unknown         => "Unknown",
+                Unknown(byte)   => return write!(formatter, "Unknown({:#04X})", byte),
             }
         )
     }
 }
 
+// The inverse of `Display`, ignoring case
+impl FromStr for NmdFileBoneFlag {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let unknown_byte_opt = text.get(..8)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("Unknown("))
+            .and_then(|_| text[8..].strip_suffix(')'))
+            .and_then(|byte| u8::from_str_radix(byte.trim_start_matches("0x").trim_start_matches("0X"), 16).ok());
+
+        match unknown_byte_opt {
+            Some(byte) => Ok(Self::from(byte)),
+            None => Self::iter()
+                .find(|flag| flag.to_string().eq_ignore_ascii_case(text))
+                .ok_or_else(|| format!("Unknown bone flag `{}` (expected e.g. Standard, or Unknown(0x06) for a raw byte)", text)),
+        }
+    }
+}
+
 impl NmdFileBoneFlagIterator {
     const FLAGS: &'static [NmdFileBoneFlag] = &[
         Standard,
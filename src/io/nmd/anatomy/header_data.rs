@@ -1,6 +1,8 @@
+use crate::io::nmd::anatomy::token::NmdFileToken;
 use crate::io::nmd::anatomy::NmdFileAddress;
 use crate::io::nmd::anatomy::NmdFileBone;
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 
 /*
  * TODO:
@@ -10,7 +12,7 @@ use serde::{Deserialize, Serialize};
  * (can put elsewhere like mod.rs)
  */
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 // Formal header properties only i.e. in-file
 pub struct NmdFileHeader {
     pub bone_count: u16,
@@ -22,8 +24,11 @@ pub struct NmdFileHeader {
 impl NmdFileHeader {
     pub const CHUNK_SIZE: u64 = 0x20;
 
+    // Sizes and addresses worked out from the header come out as 0 (or the
+    // highest address) when its addresses are out of order; `ordinal` says
+    // whether they are
     pub fn blob_data_length(&self) -> usize {
-        self.blob_data_length_opt().unwrap()
+        self.blob_data_length_opt().unwrap_or(0)
     }
 
     fn blob_data_length_opt(&self) -> Option<usize> {
@@ -31,9 +36,8 @@ impl NmdFileHeader {
             .checked_sub(self.blob_data_address as usize)
     }
 
-    #[allow(unused)]
     pub fn bone_data_length(&self) -> usize {
-        self.bone_data_length_opt().unwrap()
+        self.bone_data_length_opt().unwrap_or(0)
     }
 
     fn bone_data_length_opt(&self) -> Option<usize> {
@@ -50,7 +54,7 @@ impl NmdFileHeader {
     }
 
     pub fn physics_data_address(&self) -> NmdFileAddress {
-        self.physics_data_address_opt().unwrap()
+        self.physics_data_address_opt().unwrap_or(NmdFileAddress::MAX)
     }
 
     pub fn physics_data_address_opt(&self) -> Option<NmdFileAddress> {
@@ -60,8 +64,30 @@ impl NmdFileHeader {
                     .try_into().ok()?)
     }
 
+    /// Ranges of the header that no token covers, in order.
+    pub fn unparsed_ranges() -> Vec<Range<usize>> {
+        let mut ranges = vec!();
+        let mut start = 0;
+
+        for token in NmdFileToken::HEADER_TOKENS {
+            let value = token.value();
+
+            if start < value.offset {
+                ranges.push(start..value.offset);
+            }
+
+            start = value.offset + value.format.size();
+        }
+
+        if start < Self::CHUNK_SIZE as usize {
+            ranges.push(start..Self::CHUNK_SIZE as usize);
+        }
+
+        ranges
+    }
+
     pub fn physics_data_length(&self) -> usize {
-        self.physics_data_length_opt().unwrap()
+        self.physics_data_length_opt().unwrap_or(0)
     }
 
     pub fn physics_data_length_opt(&self) -> Option<usize> {
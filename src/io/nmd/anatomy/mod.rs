@@ -1,8 +1,11 @@
 pub mod token;
 
+mod blob_data;
 mod bone_data;
 mod bone_flag;
 mod header_data;
+mod physics_data;
+mod version;
 
 /// Maximum `u32` in header, maybe `u64` in bones.
 /// Minimum `u16` but unlikely.
@@ -10,8 +13,14 @@ mod header_data;
 pub type NmdFileAddress = u32;
 
 pub use {
+    blob_data::NmdFileBlob,
+    blob_data::NmdFileBlobAnnotation,
+    blob_data::NmdFileBlobSegment,
     bone_data::NmdFileBone,
     bone_flag::NmdFileBoneFlag,
     bone_flag::NmdFileBoneFlagIterator,
     header_data::NmdFileHeader,
+    physics_data::NmdFilePhysics,
+    version::NmdFileByteOrder,
+    version::NmdFileVersion,
 };
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/*
+ * TODO:
+ * ~ Field meanings are unknown; they're read as floats because they look like
+ * floats in known files. Rename once understood (see `NmdFileToken`)
+ */
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+// A physics bone's record in the physics data block. It runs from the bone's
+// physics data address to the next record (or the end of the block); bytes
+// past the named fields are kept as-is
+pub struct NmdFilePhysics {
+    // Bone the record belongs to
+    pub id: u16,
+    pub parameter_a: f32,
+    pub parameter_b: f32,
+    pub parameter_c: f32,
+    pub unknown_data: Vec<u8>,
+}
+
+impl NmdFilePhysics {
+    // Records shorter than this aren't decoded
+    pub const HEAD_SIZE: u64 = 0x0C;
+
+    // Never empty, as there's always a head
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        Self::HEAD_SIZE as usize + self.unknown_data.len()
+    }
+
+    /// Whether both would be written as the same bytes, unlike `==` on their
+    /// floats (NaN, -0.0).
+    pub fn same_as(&self, other: &Self) -> bool {
+        self.parameter_a.to_bits() == other.parameter_a.to_bits()
+         && self.parameter_b.to_bits() == other.parameter_b.to_bits()
+         && self.parameter_c.to_bits() == other.parameter_c.to_bits()
+         && self.unknown_data == other.unknown_data
+    }
+}
@@ -1,9 +1,12 @@
+#[allow(clippy::module_inception)]
 mod token;
+mod token_codec;
 mod token_format;
 mod token_value;
 
 pub use {
     token::NmdFileToken,
+    token_codec::NmdFileTokenCodec,
     token_format::NmdFileTokenFormat,
     token_value::NmdFileTokenValue,
 };
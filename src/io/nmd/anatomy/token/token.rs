@@ -1,8 +1,11 @@
 use {
-    crate::io::nmd::anatomy::token::{
-        NmdFileTokenFormat,
-        NmdFileTokenValue,
-    }
+    crate::io::nmd::anatomy::{
+        token::{
+            NmdFileTokenFormat,
+            NmdFileTokenValue,
+        },
+        NmdFileVersion,
+    },
 };
 
 /*
@@ -34,7 +37,7 @@ macro_rules! token_value {
     };
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum NmdFileToken {
     HeaderBoneCount,
     HeaderBlobDataAddress,
@@ -66,9 +69,109 @@ pub enum NmdFileToken {
     BoneParentId,
     BoneId,
     BoneUnknownDataF, // Maybe constant: [0x00, 0x00, ...] (x 12)
+    PhysicsParameterA, // Provisional, as are the two below
+    PhysicsParameterB,
+    PhysicsParameterC,
 }
 
 impl NmdFileToken {
+    pub const BONE_TOKENS: &'static [NmdFileToken] = {
+        use NmdFileToken::*;
+
+        &[
+            BoneCollisionData,
+            BoneTranslationX,
+            BoneTranslationY,
+            BoneTranslationZ,
+            BoneUnknownDataA,
+            BoneRotationX,
+            BoneRotationY,
+            BoneRotationZ,
+            BoneUnknownDataB,
+            BoneNameAddress,
+            BoneUnknownDataC,
+            BonePhysicsDataAddress,
+            BoneUnknownDataD,
+            BoneTranslationXNext,
+            BoneGravityX,
+            BoneGravityY,
+            BonePhysicsConstraintXPos,
+            BonePhysicsConstraintXNeg,
+            BonePhysicsConstraintYPos,
+            BonePhysicsConstraintYNeg,
+            BoneUnknownDataE,
+            BoneFlag,
+            BoneParentId,
+            BoneId,
+            BoneUnknownDataF,
+        ]
+    };
+
+    // Bytes past these in a record have no token
+    pub const PHYSICS_TOKENS: &'static [NmdFileToken] = {
+        use NmdFileToken::*;
+
+        &[
+            PhysicsParameterA,
+            PhysicsParameterB,
+            PhysicsParameterC,
+        ]
+    };
+
+    pub const HEADER_TOKENS: &'static [NmdFileToken] = {
+        use NmdFileToken::*;
+
+        &[
+            HeaderBoneCount,
+            HeaderBlobDataAddress,
+            HeaderBoneNameDataAddress,
+            HeaderBoneDataAddress,
+            HeaderBoneCountEcho,
+        ]
+    };
+
+    /// Token covering `offset` within a bone chunk (i.e. relative to its start).
+    pub fn bone_token_at(offset: usize) -> Option<Self> {
+        Self::token_at(Self::BONE_TOKENS, offset)
+    }
+
+    /// Token covering `offset` within the header, if any; the header has bytes
+    /// that no token describes.
+    pub fn header_token_at(offset: usize) -> Option<Self> {
+        Self::token_at(Self::HEADER_TOKENS, offset)
+    }
+
+    /// Token covering `offset` within a physics record, if any.
+    pub fn physics_token_at(offset: usize) -> Option<Self> {
+        Self::token_at(Self::PHYSICS_TOKENS, offset)
+    }
+
+    fn token_at(tokens: &[Self], offset: usize) -> Option<Self> {
+        tokens.iter()
+            .find(|token| {
+                let NmdFileTokenValue { offset: token_offset, format, .. } = token.value();
+
+                (token_offset..token_offset + format.size()).contains(&offset)
+            })
+            .copied()
+    }
+
+    /// Offset and format of this token in the given game's layout.
+    //
+    // No offset differences between the games are known, only byte order (see
+    // `NmdFileVersion::byte_order`), so SCIV and SCV files come out the same. A
+    // differing token would get its own arm here, e.g.
+    // `(NmdFileVersion::Sc4, BoneFlag) => token_value!(…)`
+    pub const fn value_for(&self, version: NmdFileVersion) -> NmdFileTokenValue {
+        match version {
+            NmdFileVersion::Sc4 | NmdFileVersion::Sc5 | NmdFileVersion::Sc6
+                => self.value(),
+        }
+    }
+
+    // `token_value!` fills in whatever fields a token leaves out, which for
+    // some is none
+    #[allow(clippy::needless_update)]
     pub const fn value(&self) -> NmdFileTokenValue {
         use NmdFileToken::*;
         use NmdFileTokenFormat::*;
@@ -104,6 +207,9 @@ impl NmdFileToken {
             BoneParentId                => token_value!(0x0060, Short,      is_relative),
             BoneId                      => token_value!(0x0062, Short,      is_relative),
             BoneUnknownDataF            => token_value!(0x0064, Bytes(12),  is_relative),
+            PhysicsParameterA           => token_value!(0x0000, Float,      is_relative),
+            PhysicsParameterB           => token_value!(0x0004, Float,      is_relative),
+            PhysicsParameterC           => token_value!(0x0008, Float,      is_relative),
         }
     }
 }
@@ -0,0 +1,104 @@
+use {
+    crate::io::nmd::anatomy::{
+        token::NmdFileToken,
+        NmdFileByteOrder,
+        NmdFileVersion,
+    },
+    std::{
+        mem,
+        ops::Range,
+    },
+};
+
+/// A value held in a token's bytes, in either byte order.
+pub trait NmdFileTokenCodec: Sized {
+    const SIZE: usize;
+
+    /// `None` unless `bytes` is exactly `SIZE` long.
+    fn decode(bytes: &[u8], byte_order: NmdFileByteOrder) -> Option<Self>;
+
+    fn encode(&self, byte_order: NmdFileByteOrder) -> Vec<u8>;
+}
+
+macro_rules! token_codec {
+    ($($type:ty),* $(,)?) => {
+        $(
+            impl NmdFileTokenCodec for $type {
+                const SIZE: usize = mem::size_of::<$type>();
+
+                fn decode(bytes: &[u8], byte_order: NmdFileByteOrder) -> Option<Self> {
+                    let bytes = bytes.try_into().ok()?;
+
+                    Some(match byte_order {
+                        NmdFileByteOrder::BigEndian     => <$type>::from_be_bytes(bytes),
+                        NmdFileByteOrder::LittleEndian  => <$type>::from_le_bytes(bytes),
+                    })
+                }
+
+                fn encode(&self, byte_order: NmdFileByteOrder) -> Vec<u8> {
+                    match byte_order {
+                        NmdFileByteOrder::BigEndian     => self.to_be_bytes().to_vec(),
+                        NmdFileByteOrder::LittleEndian  => self.to_le_bytes().to_vec(),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+token_codec!(u8, i8, u16, i16, u32, f32);
+
+// Raw bytes are kept in file order whatever the byte order
+impl<const N: usize> NmdFileTokenCodec for [u8; N] {
+    const SIZE: usize = N;
+
+    fn decode(bytes: &[u8], _: NmdFileByteOrder) -> Option<Self> {
+        bytes.try_into().ok()
+    }
+
+    fn encode(&self, _: NmdFileByteOrder) -> Vec<u8> {
+        self.to_vec()
+    }
+}
+
+impl NmdFileToken {
+    /// Read this token's value from `bytes`, counting from `base` if the token
+    /// is relative. `None` if `bytes` ends first.
+    pub fn decode<T: NmdFileTokenCodec>(&self, bytes: &[u8], base: usize, version: NmdFileVersion) -> Option<T> {
+        let range = self.range_for(version, base);
+
+        debug_assert_eq!(range.len(), T::SIZE, "{:?} decoded with the wrong type", self);
+
+        T::decode(bytes.get(range)?, version.byte_order())
+    }
+
+    /// Write `value` into this token's bytes of `bytes`, counting from `base`
+    /// if the token is relative. `None` if `bytes` ends first, or the value
+    /// isn't the token's size.
+    pub fn encode<T: NmdFileTokenCodec>(&self, value: &T, bytes: &mut [u8], base: usize, version: NmdFileVersion) -> Option<()> {
+        let range = self.range_for(version, base);
+        let encoded = value.encode(version.byte_order());
+
+        debug_assert_eq!(range.len(), T::SIZE, "{:?} encoded with the wrong type", self);
+
+        if encoded.len() != range.len() {
+            return None;
+        }
+
+        bytes.get_mut(range)?.copy_from_slice(&encoded);
+
+        Some(())
+    }
+
+    /// Byte range of this token's value, counting from `base` if the token is
+    /// relative.
+    pub fn range_for(&self, version: NmdFileVersion, base: usize) -> Range<usize> {
+        let value = self.value_for(version);
+        let start = match value.is_relative {
+            true  => base.saturating_add(value.offset),
+            false => value.offset,
+        };
+
+        start..start.saturating_add(value.format.size())
+    }
+}
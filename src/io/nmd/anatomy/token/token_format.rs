@@ -1,4 +1,8 @@
-// Vestigial, may not be used
+use {
+    crate::io::nmd::anatomy::NmdFileAddress,
+    std::mem,
+};
+
 #[derive(Debug)]
 pub enum NmdFileTokenFormat {
     Address,
@@ -8,3 +12,19 @@ pub enum NmdFileTokenFormat {
     None,
     Short,
 }
+
+impl NmdFileTokenFormat {
+    /// Size in bytes of a value in this format.
+    pub const fn size(&self) -> usize {
+        use NmdFileTokenFormat::*;
+
+        match self {
+            Address     => mem::size_of::<NmdFileAddress>(),
+            Byte        => mem::size_of::<u8>(),
+            Bytes(n)    => *n,
+            Float       => mem::size_of::<f32>(),
+            None        => 0,
+            Short       => mem::size_of::<u16>(),
+        }
+    }
+}
@@ -12,7 +12,6 @@ pub struct NmdFileTokenValue {
 // Can't use `Default` in a const context, so workaround
 impl NmdFileTokenValue {
     pub const DEFAULT: Self = Self {
-        // Format field is vestigial, but keep for debugging
         format: NmdFileTokenFormat::None,
         is_relative: false,
         offset: 0x0000,
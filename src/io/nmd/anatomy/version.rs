@@ -0,0 +1,67 @@
+use {
+    std::{
+        fmt::{
+            self,
+            Display,
+            Formatter,
+        },
+        str::FromStr,
+    },
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+use NmdFileVersion::*;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum NmdFileByteOrder {
+    BigEndian,
+    LittleEndian,
+}
+
+/// Game an NMD file is laid out for.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum NmdFileVersion {
+    Sc4,
+    Sc5,
+    #[default]
+    Sc6,
+}
+
+impl NmdFileVersion {
+    pub const VERSIONS: &'static [NmdFileVersion] = &[Sc4, Sc5, Sc6];
+
+    // SCIV and SCV only shipped on PS3/360, so their files are big endian
+    pub fn byte_order(&self) -> NmdFileByteOrder {
+        match self {
+            Sc4 => NmdFileByteOrder::BigEndian,
+            Sc5 => NmdFileByteOrder::BigEndian,
+            Sc6 => NmdFileByteOrder::LittleEndian,
+        }
+    }
+}
+
+impl Display for NmdFileVersion {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}",
+            match self {
+                Sc4 => "SCIV",
+                Sc5 => "SCV",
+                Sc6 => "SCVI",
+            }
+        )
+    }
+}
+
+impl FromStr for NmdFileVersion {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        Self::VERSIONS.iter()
+            .find(|version| version.to_string().eq_ignore_ascii_case(text))
+            .copied()
+            .ok_or_else(|| format!("Unknown game version `{}` (expected SCIV, SCV or SCVI)", text))
+    }
+}
@@ -0,0 +1,302 @@
+use {
+    crate::io::nmd::anatomy::{
+        NmdFileBone,
+        NmdFileBoneFlag,
+        NmdFileByteOrder,
+        NmdFileHeader,
+    },
+    std::{
+        fmt::{
+            self,
+            Display,
+            Formatter,
+        },
+        fs,
+        io::{
+            self,
+            ErrorKind,
+        },
+        ops::Range,
+        path::PathBuf,
+    },
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+/// Names given to byte ranges, e.g. parts of unknown fields worked out while
+/// reverse-engineering. Kept in a TOML file so findings can be shared:
+///
+/// ```toml
+/// [[bone]]
+/// name = "chain weight"
+/// offset = 0x4C   # from the start of the bone chunk
+/// length = 2
+/// format = "int"  # how to show the value: "hex" (default), "int" or "float"
+/// ```
+///
+/// `[[header]]` ranges are from the start of the file, and `[[physics]]`
+/// ranges from the start of a physics record. Flag bytes with no known bone
+/// type can be named too:
+///
+/// ```toml
+/// [[flag]]
+/// name = "Twist"
+/// value = 0x06
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NmdFileAnnotations {
+    pub header: Vec<NmdFileAnnotation>,
+    pub bone: Vec<NmdFileAnnotation>,
+    pub physics: Vec<NmdFileAnnotation>,
+    pub flag: Vec<NmdFileFlagAnnotation>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NmdFileAnnotation {
+    pub name: String,
+    pub offset: usize,
+    pub length: usize,
+    #[serde(default)]
+    pub format: NmdFileAnnotationFormat,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NmdFileFlagAnnotation {
+    pub name: String,
+    pub value: u8,
+}
+
+/// What an annotation's offset is relative to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NmdFileAnnotationScope {
+    Header,
+    Bone,
+    Physics,
+}
+
+/// How to show (and read back) a range of raw bytes.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NmdFileAnnotationFormat {
+    #[default]
+    Hex,
+    // Signed, in the widest of 4, 2 or 1 bytes that the length divides into
+    Int,
+    // 4 byte floats, for lengths that divide into them
+    Float,
+}
+
+impl NmdFileAnnotations {
+    /// Where the annotation file is looked for, relative to the working
+    /// directory.
+    pub const DEFAULT_PATH: &'static str = "annotations.toml";
+
+    /// Annotation covering `offset` within the scope.
+    pub fn at(&self, scope: NmdFileAnnotationScope, offset: usize) -> Option<&NmdFileAnnotation> {
+        self.in_scope(scope)
+            .iter()
+            .find(|annotation| annotation.range().contains(&offset))
+    }
+
+    fn check(&self) -> Result<(), String> {
+        use NmdFileAnnotationScope::*;
+
+        for scope in [Header, Bone, Physics] {
+            for annotation in self.in_scope(scope) {
+                if annotation.length == 0 {
+                    return Err(format!("`{}` has no length", annotation.name));
+                }
+
+                if annotation.offset.checked_add(annotation.length).is_none() {
+                    return Err(format!("`{}` ends past any offset ({:#X} + {:#X} bytes)", annotation.name, annotation.offset, annotation.length));
+                }
+
+                if let Some(size) = scope.size() {
+                    if annotation.range().end > size {
+                        return Err(format!("`{}` runs past the end of the {} ({:#X} bytes)", annotation.name, scope, size));
+                    }
+                }
+            }
+        }
+
+        for (i, flag) in self.flag.iter().enumerate() {
+            if flag.name.is_empty() {
+                return Err(format!("flag {:#04X} has no name", flag.value));
+            }
+
+            // Only flags of no known type can be named
+            if !matches!(NmdFileBoneFlag::from(flag.value), NmdFileBoneFlag::Unknown(_)) {
+                return Err(format!("`{}` names flag {:#04X}, which is already {}", flag.name, flag.value, NmdFileBoneFlag::from(flag.value)));
+            }
+
+            if self.flag[..i].iter().any(|other| other.value == flag.value) {
+                return Err(format!("flag {:#04X} is named more than once", flag.value));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The flag's name, or the name given to it here with its byte if it is of
+    /// no known type, e.g. `Twist(0x06)`.
+    pub fn flag_label(&self, flag: NmdFileBoneFlag) -> String {
+        if let NmdFileBoneFlag::Unknown(byte) = flag {
+            if let Some(named) = self.flag.iter().find(|named| named.value == byte) {
+                return format!("{}({:#04X})", named.name, byte);
+            }
+        }
+
+        flag.to_string()
+    }
+
+    /// Known flags, then the ones named here.
+    pub fn flags(&self) -> impl Iterator<Item = NmdFileBoneFlag> + '_ {
+        NmdFileBoneFlag::iter()
+            .chain(self.flag.iter().map(|named| NmdFileBoneFlag::Unknown(named.value)))
+    }
+
+    fn in_scope(&self, scope: NmdFileAnnotationScope) -> &Vec<NmdFileAnnotation> {
+        match scope {
+            NmdFileAnnotationScope::Header  => &self.header,
+            NmdFileAnnotationScope::Bone    => &self.bone,
+            NmdFileAnnotationScope::Physics => &self.physics,
+        }
+    }
+
+    /// Annotations lying wholly within `range` of the scope.
+    pub fn within(&self, scope: NmdFileAnnotationScope, range: Range<usize>) -> impl Iterator<Item = &NmdFileAnnotation> {
+        self.in_scope(scope)
+            .iter()
+            .filter(move |annotation| range.start <= annotation.offset && annotation.range().end <= range.end)
+    }
+}
+
+impl NmdFileAnnotation {
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.length
+    }
+}
+
+impl NmdFileAnnotationScope {
+    // Physics records vary in length
+    pub fn size(&self) -> Option<usize> {
+        match self {
+            Self::Header    => Some(NmdFileHeader::CHUNK_SIZE as usize),
+            Self::Bone      => Some(NmdFileBone::CHUNK_SIZE as usize),
+            Self::Physics   => None,
+        }
+    }
+}
+
+impl NmdFileAnnotationFormat {
+    pub const FORMATS: &'static [Self] = &[Self::Hex, Self::Int, Self::Float];
+
+    /// Show `bytes` as space-separated values; as hex if the format doesn't fit
+    /// their length.
+    pub fn display(&self, bytes: &[u8], byte_order: NmdFileByteOrder) -> String {
+        let word_size = match self.word_size(bytes.len()) {
+            Some(word_size) => word_size,
+            None => return NmdFileAnnotationFormat::Hex.display(bytes, byte_order),
+        };
+
+        bytes.chunks_exact(word_size)
+            .map(|word| {
+                let mut word = word.to_vec();
+
+                if byte_order == NmdFileByteOrder::LittleEndian {
+                    word.reverse();
+                }
+
+                // `word` is now big endian
+                match (self, word_size) {
+                    (Self::Int, 1)      => (word[0] as i8).to_string(),
+                    (Self::Int, 2)      => i16::from_be_bytes([word[0], word[1]]).to_string(),
+                    (Self::Int, _)      => i32::from_be_bytes([word[0], word[1], word[2], word[3]]).to_string(),
+                    (Self::Float, _)    => f32::from_be_bytes([word[0], word[1], word[2], word[3]]).to_string(),
+                    (Self::Hex, _)      => format!("{:02X}", word[0]),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Read back text in the form `display` gives, for `length` bytes.
+    pub fn parse(&self, text: &str, length: usize, byte_order: NmdFileByteOrder) -> Option<Vec<u8>> {
+        let word_size = self.word_size(length)?;
+        let words: Vec<&str> = text.split_whitespace().collect();
+
+        if words.len() * word_size != length {
+            return None;
+        }
+
+        let mut bytes = vec!();
+
+        for word in words {
+            let mut word_bytes = match (self, word_size) {
+                (Self::Int, 1)      => word.parse::<i8>().ok()?.to_be_bytes().to_vec(),
+                (Self::Int, 2)      => word.parse::<i16>().ok()?.to_be_bytes().to_vec(),
+                (Self::Int, _)      => word.parse::<i32>().ok()?.to_be_bytes().to_vec(),
+                (Self::Float, _)    => word.parse::<f32>().ok()?.to_be_bytes().to_vec(),
+                (Self::Hex, _)      => vec![u8::from_str_radix(word, 16).ok()?],
+            };
+
+            if byte_order == NmdFileByteOrder::LittleEndian {
+                word_bytes.reverse();
+            }
+
+            bytes.extend(word_bytes);
+        }
+
+        Some(bytes)
+    }
+
+    /// Bytes per value for a range of `length` bytes, if the format fits it.
+    pub fn word_size(&self, length: usize) -> Option<usize> {
+        match self {
+            Self::Hex   => Some(1),
+            Self::Int   => [4, 2, 1].into_iter().find(|word_size| length.is_multiple_of(*word_size)),
+            Self::Float => length.is_multiple_of(4).then_some(4),
+        }
+    }
+}
+
+impl Display for NmdFileAnnotationScope {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}",
+            match self {
+                Self::Header    => "header",
+                Self::Bone      => "bone chunk",
+                Self::Physics   => "physics record",
+            }
+        )
+    }
+}
+
+impl Display for NmdFileAnnotationFormat {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}",
+            match self {
+                Self::Hex   => "Hex",
+                Self::Int   => "Int",
+                Self::Float => "Float",
+            }
+        )
+    }
+}
+
+impl TryFrom<&PathBuf> for NmdFileAnnotations {
+    type Error = io::Error;
+
+    fn try_from(path: &PathBuf) -> io::Result<Self> {
+        let annotations: Self = toml::from_str(&fs::read_to_string(path)?)
+            .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+
+        annotations.check()
+            .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+
+        Ok(annotations)
+    }
+}
@@ -0,0 +1,328 @@
+use {
+    crate::io::nmd::{
+        anatomy::NmdFileBone,
+        data::{
+            hex_bytes,
+            hex_text,
+        },
+    },
+    std::{
+        collections::{
+            BTreeMap,
+            HashSet,
+        },
+        fs,
+        io::{
+            self,
+            ErrorKind,
+        },
+        iter,
+        mem,
+        path::PathBuf,
+    },
+};
+
+// Every field of `NmdFileBone` as a column, by its `Display` text (read back
+// with `FromStr`), then the byte fields as hex
+macro_rules! bone_columns {
+    ({ $($field:ident),* $(,)? }, 'hex { $($hex_field:ident),* $(,)? }) => {
+        impl NmdFileBoneTable {
+            /// Column headers, in the order they're written.
+            pub const COLUMNS: &'static [&'static str] = &[
+                $(stringify!($field),)*
+                $(stringify!($hex_field),)*
+            ];
+        }
+
+        fn cell(bone_data: &NmdFileBone, column: &str) -> Option<String> {
+            match column {
+                $(stringify!($field) => Some(bone_data.$field.to_string()),)*
+                $(stringify!($hex_field) => Some(hex_text(&bone_data.$hex_field)),)*
+                _ => None,
+            }
+        }
+
+        fn set_cell(bone_data: &mut NmdFileBone, column: &str, text: &str) -> Result<(), String> {
+            let invalid = || format!("`{}` isn't a valid {}", text, column);
+
+            match column {
+                $(stringify!($field) => bone_data.$field = text.parse().map_err(|_| invalid())?,)*
+                $(stringify!($hex_field) => bone_data.$hex_field = hex_bytes(text)
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(invalid)?,)*
+                _ => return Err(format!("`{}` isn't a bone field", column)),
+            }
+
+            Ok(())
+        }
+    };
+}
+
+bone_columns!({
+    id, name, parent_id, flag,
+    translation_x, translation_y, translation_z,
+    rotation_x, rotation_y, rotation_z,
+    translation_x_next, gravity_x, gravity_y,
+    physics_constraint_x_max, physics_constraint_x_min,
+    physics_constraint_y_max, physics_constraint_y_min,
+    physics_data_address,
+}, 'hex {
+    collision_data, unknown_data_a, unknown_data_b, unknown_data_c,
+    unknown_data_d, unknown_data_e, unknown_data_f,
+});
+
+/// The bone table as CSV, one row per bone keyed by ID, for editing in a
+/// spreadsheet. Rows are matched to bones by ID rather than position, and
+/// columns dropped from an edited table leave their fields as they are.
+/// `READ_ONLY_COLUMNS` are written for reference but never applied.
+#[derive(Debug, Clone, Default)]
+pub struct NmdFileBoneTable {
+    columns: Vec<String>,
+    rows: Vec<NmdFileBoneTableRow>,
+}
+
+#[derive(Debug, Clone)]
+struct NmdFileBoneTableRow {
+    bone_id: u16,
+    // In the order of `columns`
+    cells: Vec<String>,
+}
+
+/// A cell of an imported table that differs from the bone it's applied to.
+#[derive(Debug, Clone)]
+pub struct NmdFileBoneTableChange {
+    pub bone_id: u16,
+    // As it is before the change
+    pub name: String,
+    pub column: String,
+    pub replaced_text: String,
+    pub text: String,
+}
+
+impl NmdFileBoneTable {
+    // Moving a bone or its physics is a matter for the tree, not a spreadsheet
+    pub const READ_ONLY_COLUMNS: &'static [&'static str] = &["id", "parent_id", "physics_data_address"];
+
+    fn is_applied(column: &str) -> bool {
+        !Self::READ_ONLY_COLUMNS.contains(&column)
+    }
+
+    /// The bone with this table's row for its ID (if any) applied.
+    pub fn apply(&self, bone_data: &NmdFileBone) -> NmdFileBone {
+        let mut applied = bone_data.to_owned();
+
+        if let Some(row) = self.rows.iter().find(|row| row.bone_id == bone_data.id) {
+            for (column, text) in self.columns.iter().zip(&row.cells) {
+                if Self::is_applied(column) {
+                    // Every cell was checked when read
+                    set_cell(&mut applied, column, text).ok();
+                }
+            }
+        }
+
+        applied
+    }
+
+    pub fn bone_ids(&self) -> impl Iterator<Item = u16> + '_ {
+        self.rows.iter().map(|row| row.bone_id)
+    }
+
+    /// Cells that would change the given bones, by row then column.
+    pub fn changes(&self, bones: &BTreeMap<u16, NmdFileBone>) -> Vec<NmdFileBoneTableChange> {
+        let mut changes = vec![];
+
+        for bone_data in self.bone_ids().filter_map(|bone_id| bones.get(&bone_id)) {
+            let applied = self.apply(bone_data);
+
+            for column in self.columns.iter().filter(|column| Self::is_applied(column)) {
+                let (replaced_text, text) = (cell(bone_data, column), cell(&applied, column));
+
+                if let (Some(replaced_text), Some(text)) = (replaced_text, text) {
+                    if replaced_text != text {
+                        changes.push(NmdFileBoneTableChange {
+                            bone_id: bone_data.id,
+                            name: bone_data.name.to_owned(),
+                            column: column.to_owned(),
+                            replaced_text: replaced_text,
+                            text: text,
+                        });
+                    }
+                }
+            }
+        }
+
+        changes
+    }
+
+    pub fn from_csv(text: &str) -> io::Result<Self> {
+        let invalid = |line: usize, error: String| io::Error::new(ErrorKind::InvalidData, format!("line {}: {}", line, error));
+        let mut records = parse_csv(text.trim_start_matches('\u{FEFF}'))
+            .map_err(|(line, error)| invalid(line, error))?
+            .into_iter();
+
+        let (line, columns) = records.next()
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "the table is empty"))?;
+        let mut seen_columns = HashSet::new();
+
+        if !columns.iter().any(|column| column == "id") {
+            return Err(invalid(line, String::from("there's no `id` column")));
+        }
+
+        for column in &columns {
+            if !Self::COLUMNS.contains(&column.as_str()) {
+                return Err(invalid(line, format!("`{}` isn't a bone field", column)));
+            }
+
+            if !seen_columns.insert(column) {
+                return Err(invalid(line, format!("`{}` appears twice", column)));
+            }
+        }
+
+        let mut rows = vec![];
+        let mut seen_ids = HashSet::new();
+
+        for (line, cells) in records {
+            if cells.len() != columns.len() {
+                return Err(invalid(line, format!("{} cells for {} columns", cells.len(), columns.len())));
+            }
+
+            // Checked up front, so applying can't fail partway
+            let mut scratch = NmdFileBone::default();
+
+            for (column, text) in columns.iter().zip(&cells) {
+                set_cell(&mut scratch, column, text).map_err(|error| invalid(line, error))?;
+
+                if column == "name" {
+                    NmdFileBone::encode_name(text).map_err(|character|
+                        invalid(line, format!("`{}` can't be used in a bone name", character)))?;
+                }
+            }
+
+            if !seen_ids.insert(scratch.id) {
+                return Err(invalid(line, format!("bone {:#06X} appears twice", scratch.id)));
+            }
+
+            rows.push(NmdFileBoneTableRow {
+                bone_id: scratch.id,
+                cells: cells,
+            });
+        }
+
+        Ok(Self {
+            columns: columns,
+            rows: rows,
+        })
+    }
+
+    pub fn save(&self, path: &PathBuf) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut text = String::new();
+
+        for cells in iter::once(&self.columns).chain(self.rows.iter().map(|row| &row.cells)) {
+            let line: Vec<String> = cells.iter().map(|cell| csv_cell(cell)).collect();
+
+            text.push_str(&line.join(","));
+            text.push_str("\r\n");
+        }
+
+        text
+    }
+
+    /// IDs in the table with no bone among the given ones.
+    pub fn unmatched_ids(&self, bones: &BTreeMap<u16, NmdFileBone>) -> Vec<u16> {
+        self.bone_ids().filter(|bone_id| !bones.contains_key(bone_id)).collect()
+    }
+}
+
+impl<'a> FromIterator<&'a NmdFileBone> for NmdFileBoneTable {
+    fn from_iter<I: IntoIterator<Item = &'a NmdFileBone>>(bones: I) -> Self {
+        let rows = bones.into_iter()
+            .map(|bone_data| NmdFileBoneTableRow {
+                bone_id: bone_data.id,
+                cells: Self::COLUMNS.iter()
+                    .filter_map(|column| cell(bone_data, column))
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            columns: Self::COLUMNS.iter().map(|column| column.to_string()).collect(),
+            rows: rows,
+        }
+    }
+}
+
+impl TryFrom<&PathBuf> for NmdFileBoneTable {
+    type Error = io::Error;
+
+    fn try_from(path: &PathBuf) -> io::Result<Self> {
+        Self::from_csv(&fs::read_to_string(path)?)
+    }
+}
+
+// Quoted if it would otherwise be read differently
+fn csv_cell(text: &str) -> String {
+    let quoting = text.contains([',', '"', '\r', '\n']) || text.trim() != text;
+
+    match quoting {
+        true => format!("\"{}\"", text.replace('"', "\"\"")),
+        false => text.to_owned(),
+    }
+}
+
+// Cells of each record, with the line it starts on
+type CsvRecords = Vec<(usize, Vec<String>)>;
+
+// Records as in RFC 4180; blank lines are skipped. Fails with the line and
+// what's wrong on it
+fn parse_csv(text: &str) -> Result<CsvRecords, (usize, String)> {
+    let mut records = vec![];
+    let mut cells = vec![];
+    let mut cell = String::new();
+    let mut characters = text.chars().peekable();
+    let (mut line, mut record_line) = (1, 1);
+    let mut quoted = false;
+
+    while let Some(character) = characters.next() {
+        match (quoted, character) {
+            (true, '"') if characters.peek() == Some(&'"') => {
+                characters.next();
+                cell.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, character) => {
+                line += (character == '\n') as usize;
+                cell.push(character);
+            }
+            (false, '"') if cell.is_empty() => quoted = true,
+            (false, ',') => cells.push(mem::take(&mut cell)),
+            (false, '\r') if characters.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                cells.push(mem::take(&mut cell));
+
+                if cells.len() > 1 || !cells[0].is_empty() {
+                    records.push((record_line, mem::take(&mut cells)));
+                }
+
+                cells.clear();
+                line += 1;
+                record_line = line;
+            }
+            (false, character) => cell.push(character),
+        }
+    }
+
+    if quoted {
+        return Err((record_line, String::from("a quoted cell isn't closed")));
+    }
+
+    if !cells.is_empty() || !cell.is_empty() {
+        cells.push(cell);
+        records.push((record_line, cells));
+    }
+
+    Ok(records)
+}
@@ -0,0 +1,390 @@
+use {
+    crate::io::nmd::{
+        anatomy::{
+            token::{
+                NmdFileToken,
+                NmdFileTokenFormat,
+            },
+            NmdFileBoneFlag,
+            NmdFileByteOrder,
+        },
+        data::{
+            hex_text,
+            ByteArr,
+        },
+        NmdError,
+        NmdFileBlock,
+        NmdFileMap,
+    },
+    std::{
+        cmp::Reverse,
+        collections::{
+            BTreeMap,
+            BTreeSet,
+            HashMap,
+        },
+        fs,
+        io,
+        path::PathBuf,
+    },
+};
+
+/// Value distributions of every token across a set of files, by the flag of
+/// the bone they belong to, for working out fields whose meaning is unknown.
+pub struct NmdFileCorpus {
+    pub files: Vec<PathBuf>,
+    // Files that couldn't be read, and why
+    pub failures: Vec<(PathBuf, NmdError)>,
+    pub record_count: usize,
+    // Flag bytes with no `NmdFileBoneFlag` variant
+    pub unknown_flags: BTreeMap<u8, NmdFileUnknownFlag>,
+    tokens: Vec<NmdFileTokenStatistics>,
+    // Indexed by unknown byte, then known field
+    correlation_sums: Vec<Vec<CorrelationSums>>,
+}
+
+/// How often each value of a token was seen.
+pub struct NmdFileTokenStatistics {
+    pub token: NmdFileToken,
+    // By raw flag byte of the bone; `None` for header tokens
+    pub by_flag: BTreeMap<Option<u8>, NmdFileValueDistribution>,
+}
+
+#[derive(Debug, Default)]
+pub struct NmdFileValueDistribution {
+    pub count: usize,
+    // By value, with multi-byte numbers in big endian whatever the file's
+    // byte order
+    pub values: BTreeMap<Vec<u8>, usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct NmdFileUnknownFlag {
+    pub count: usize,
+    pub files: BTreeSet<PathBuf>,
+}
+
+/// Pearson correlation between one byte of an unknown field and a known
+/// numeric field, over the bones where both were read.
+#[derive(Debug)]
+pub struct NmdFileCorrelation {
+    pub token: NmdFileToken,
+    // Into the token, in file order
+    pub byte: usize,
+    pub field: NmdFileToken,
+    pub r: f64,
+    pub n: usize,
+}
+
+#[derive(Clone, Default)]
+struct CorrelationSums {
+    n: usize,
+    x: f64,
+    y: f64,
+    xx: f64,
+    yy: f64,
+    xy: f64,
+}
+
+impl NmdFileCorpus {
+    pub const FILE_EXTENSION: &'static str = "nmd";
+
+    // Known fields that unknown bytes are checked against
+    const KNOWN_TOKENS: &'static [NmdFileToken] = {
+        use NmdFileToken::*;
+
+        &[
+            BoneTranslationX,
+            BoneTranslationY,
+            BoneTranslationZ,
+            BoneRotationX,
+            BoneRotationY,
+            BoneRotationZ,
+            BoneTranslationXNext,
+            BoneGravityX,
+            BoneGravityY,
+            BonePhysicsConstraintXPos,
+            BonePhysicsConstraintXNeg,
+            BonePhysicsConstraintYPos,
+            BonePhysicsConstraintYNeg,
+        ]
+    };
+
+    const UNKNOWN_TOKENS: &'static [NmdFileToken] = {
+        use NmdFileToken::*;
+
+        &[
+            BoneUnknownDataA,
+            BoneUnknownDataB,
+            BoneUnknownDataC,
+            BoneUnknownDataD,
+            BoneUnknownDataE,
+            BoneUnknownDataF,
+        ]
+    };
+
+    pub fn new() -> Self {
+        let unknown_byte_count = Self::unknown_bytes().count();
+        let tokens = NmdFileToken::HEADER_TOKENS.iter()
+            .chain(NmdFileToken::BONE_TOKENS)
+            .chain(NmdFileToken::PHYSICS_TOKENS)
+            .map(|token| NmdFileTokenStatistics {
+                token: *token,
+                by_flag: BTreeMap::new(),
+            })
+            .collect();
+
+        Self {
+            files: vec!(),
+            failures: vec!(),
+            record_count: 0,
+            unknown_flags: BTreeMap::new(),
+            tokens: tokens,
+            correlation_sums: vec![vec![CorrelationSums::default(); Self::KNOWN_TOKENS.len()]; unknown_byte_count],
+        }
+    }
+
+    /// Read a file into the statistics, or note why it couldn't be.
+    pub fn add(&mut self, path: &PathBuf) {
+        let result = fs::read(path)
+            .map_err(NmdError::from)
+            .and_then(|bytes| Ok((NmdFileMap::try_from(&bytes[..])?, bytes)));
+
+        match result {
+            Ok((map, bytes)) => self.add_mapped(path, &bytes, &map),
+            Err(error) => self.failures.push((path.to_owned(), error)),
+        }
+    }
+
+    fn add_correlations(&mut self, chunk: ByteArr, byte_order: NmdFileByteOrder) {
+        let known_values: Vec<Option<f64>> = Self::KNOWN_TOKENS.iter()
+            .map(|token| Self::number(*token, &Self::normalized(*token, Self::token_bytes(chunk, *token), byte_order)))
+            .collect();
+
+        for (i, (token, byte)) in Self::unknown_bytes().enumerate() {
+            let x = chunk[token.value().offset + byte] as f64;
+
+            for (j, y_opt) in known_values.iter().enumerate() {
+                if let Some(y) = y_opt.filter(|y| y.is_finite()) {
+                    self.correlation_sums[i][j].add(x, y);
+                }
+            }
+        }
+    }
+
+    fn add_mapped(&mut self, path: &PathBuf, bytes: ByteArr, map: &NmdFileMap) {
+        let byte_order = map.version().byte_order();
+        let flag_offset = NmdFileToken::BoneFlag.value().offset;
+        let flags: Vec<u8> = (0..map.records().len())
+            .map(|n| bytes[map.bone_chunk(n).start + flag_offset])
+            .collect();
+        let mut flags_by_id = HashMap::<u16, u8>::new();
+
+        for (record, flag) in map.records().iter().zip(&flags) {
+            flags_by_id.entry(record.id).or_insert(*flag);
+        }
+
+        for span in map.spans() {
+            let (token, flag_opt) = match &span.block {
+                NmdFileBlock::Header(token)                 => (*token, None),
+                NmdFileBlock::Bone(index, _, token)         => (*token, Some(flags[*index])),
+                NmdFileBlock::Physics(bone_id, Some(token)) => (*token, flags_by_id.get(bone_id).copied()),
+                _   => continue,
+            };
+
+            // Spans cut short by an overlap don't hold a whole value
+            if span.range.len() != token.value().format.size() {
+                continue;
+            }
+
+            let value = Self::normalized(token, &bytes[span.range.to_owned()], byte_order);
+
+            if let Some(statistics) = self.tokens.iter_mut().find(|statistics| statistics.token == token) {
+                statistics.by_flag.entry(flag_opt).or_default().add(value);
+            }
+        }
+
+        for (n, flag) in flags.iter().enumerate() {
+            if let NmdFileBoneFlag::Unknown(_) = NmdFileBoneFlag::from(*flag) {
+                let unknown_flag = self.unknown_flags.entry(*flag).or_default();
+
+                unknown_flag.count += 1;
+                unknown_flag.files.insert(path.to_owned());
+            }
+
+            self.add_correlations(&bytes[map.bone_chunk(n)], byte_order);
+        }
+
+        self.record_count += flags.len();
+        self.files.push(path.to_owned());
+    }
+
+    /// Correlations at least `min_r` strong (either way) over at least `min_n`
+    /// bones, strongest first.
+    pub fn correlations(&self, min_r: f64, min_n: usize) -> Vec<NmdFileCorrelation> {
+        let mut correlations: Vec<_> = Self::unknown_bytes()
+            .zip(&self.correlation_sums)
+            .flat_map(|((token, byte), sums)| {
+                Self::KNOWN_TOKENS.iter()
+                    .zip(sums)
+                    .filter_map(move |(field, sums)| Some(NmdFileCorrelation {
+                        token: token,
+                        byte: byte,
+                        field: *field,
+                        r: sums.r()?,
+                        n: sums.n,
+                    }))
+            })
+            .filter(|correlation| correlation.r.abs() >= min_r && correlation.n >= min_n)
+            .collect();
+
+        correlations.sort_by(|a, b| b.r.abs().total_cmp(&a.r.abs()));
+        correlations
+    }
+
+    /// Show a value the way its token reads it.
+    pub fn display_value(token: NmdFileToken, value: ByteArr) -> String {
+        use NmdFileToken::*;
+
+        match (token, token.value().format) {
+            // IDs, counts and flags read better in hex
+            (HeaderBoneCount | HeaderBoneCountEcho | BoneParentId | BoneId, NmdFileTokenFormat::Short)
+                => format!("{:#06X}", u16::from_be_bytes([value[0], value[1]])),
+            (BoneFlag, _) => match NmdFileBoneFlag::from(value[0]) {
+                flag @ NmdFileBoneFlag::Unknown(_) => flag.to_string(),
+                flag => format!("{:#04X} ({})", value[0], flag),
+            },
+            (_, NmdFileTokenFormat::Address)
+                => format!("{:#010X}", u32::from_be_bytes([value[0], value[1], value[2], value[3]])),
+            (_, NmdFileTokenFormat::Float)
+                => f32::from_be_bytes([value[0], value[1], value[2], value[3]]).to_string(),
+            (_, NmdFileTokenFormat::Short | NmdFileTokenFormat::Byte)
+                => Self::number(token, value).map_or_else(|| hex_text(value), |number| number.to_string()),
+            _   => hex_text(value),
+        }
+    }
+
+    // Numeric value of a float or (signed) integer token
+    fn number(token: NmdFileToken, value: ByteArr) -> Option<f64> {
+        match token.value().format {
+            NmdFileTokenFormat::Float   => Some(f32::from_be_bytes(value.try_into().ok()?) as f64),
+            NmdFileTokenFormat::Short   => Some(i16::from_be_bytes(value.try_into().ok()?) as f64),
+            NmdFileTokenFormat::Byte    => Some(*value.first()? as i8 as f64),
+            _   => None,
+        }
+    }
+
+    // Multi-byte numbers in big endian, so files of either order can be tallied
+    // together
+    fn normalized(token: NmdFileToken, value: ByteArr, byte_order: NmdFileByteOrder) -> Vec<u8> {
+        let mut value = value.to_vec();
+
+        match token.value().format {
+            NmdFileTokenFormat::Address | NmdFileTokenFormat::Float | NmdFileTokenFormat::Short
+                if byte_order == NmdFileByteOrder::LittleEndian => value.reverse(),
+            _   => {}
+        }
+
+        value
+    }
+
+    /// Add every file with the NMD extension under `path`, recursively.
+    pub fn scan(&mut self, path: &PathBuf) -> io::Result<()> {
+        if fs::metadata(path)?.is_dir() {
+            let mut entries = fs::read_dir(path)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<io::Result<Vec<_>>>()?;
+
+            entries.sort();
+
+            for entry in entries {
+                self.scan(&entry)?;
+            }
+        } else if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case(Self::FILE_EXTENSION)) {
+            self.add(path);
+        }
+
+        Ok(())
+    }
+
+    fn token_bytes(chunk: ByteArr, token: NmdFileToken) -> ByteArr {
+        let value = token.value();
+
+        &chunk[value.offset..value.offset + value.format.size()]
+    }
+
+    /// Statistics for each token, header tokens first, then bone and physics
+    /// tokens in file order.
+    pub fn tokens(&self) -> &Vec<NmdFileTokenStatistics> {
+        &self.tokens
+    }
+
+    // Each byte of each unknown field, in order
+    fn unknown_bytes() -> impl Iterator<Item = (NmdFileToken, usize)> {
+        Self::UNKNOWN_TOKENS.iter()
+            .flat_map(|token| (0..token.value().format.size()).map(move |byte| (*token, byte)))
+    }
+}
+
+impl Default for NmdFileCorpus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NmdFileValueDistribution {
+    fn add(&mut self, value: Vec<u8>) {
+        *self.values.entry(value).or_default() += 1;
+        self.count += 1;
+    }
+
+    /// Positions at which every value has the same byte.
+    pub fn constant_bytes(&self) -> Vec<usize> {
+        let mut values = self.values.keys();
+        let first = match values.next() {
+            Some(first) => first,
+            None => return vec!(),
+        };
+        let others: Vec<_> = values.collect();
+
+        (0..first.len())
+            .filter(|i| others.iter().all(|value| value.get(*i) == Some(&first[*i])))
+            .collect()
+    }
+
+    /// The `count` most frequent values, most frequent first.
+    pub fn most_frequent(&self, count: usize) -> Vec<(&Vec<u8>, usize)> {
+        let mut values: Vec<_> = self.values.iter()
+            .map(|(value, count)| (value, *count))
+            .collect();
+
+        values.sort_by_key(|(_, count)| Reverse(*count));
+        values.truncate(count);
+        values
+    }
+}
+
+impl CorrelationSums {
+    fn add(&mut self, x: f64, y: f64) {
+        self.n += 1;
+        self.x += x;
+        self.y += y;
+        self.xx += x * x;
+        self.yy += y * y;
+        self.xy += x * y;
+    }
+
+    // `None` if either side never varies
+    fn r(&self) -> Option<f64> {
+        let n = self.n as f64;
+        let covariance = n * self.xy - self.x * self.y;
+        let variance_x = n * self.xx - self.x * self.x;
+        let variance_y = n * self.yy - self.y * self.y;
+
+        if variance_x <= 0.0 || variance_y <= 0.0 {
+            return None;
+        }
+
+        Some(covariance / (variance_x * variance_y).sqrt())
+    }
+}
@@ -1,7 +1,7 @@
 use {
     crate::io::nmd::anatomy::NmdFileBone,
     std::{
-        collections::{BTreeSet as OrderedSet, VecDeque, btree_set::IntoIter as OrderedIter},
+        collections::{BTreeSet as OrderedSet, HashSet, VecDeque, btree_set::IntoIter as OrderedIter},
         iter,
         ops::RangeInclusive,
     },
@@ -60,7 +60,7 @@ pub trait NmdFileBoneTreeNode {
             }
         }
 
-        if orphans.len() > 0 {
+        if !orphans.is_empty() {
             for child in self.children_mut() {
                 child.assimilate(orphans);
             }
@@ -68,7 +68,7 @@ pub trait NmdFileBoneTreeNode {
     }
 
     fn at_path(&self, path: &VecDeque<u16>) -> Option<&NmdFileBoneTree<Self::Data>> {
-        if let Some(first_id) = path.get(0) {
+        if let Some(first_id) = path.front() {
             let mut next = self.child(*first_id);
 
             for i in 1.. {
@@ -84,7 +84,7 @@ pub trait NmdFileBoneTreeNode {
     }
 
     fn at_path_mut(&mut self, path: &VecDeque<u16>) -> Option<&mut NmdFileBoneTree<Self::Data>> {
-        if let Some(first_id) = path.get(0) {
+        if let Some(first_id) = path.front() {
             let mut next = self.child_mut(*first_id);
 
             for i in 1.. {
@@ -101,7 +101,7 @@ pub trait NmdFileBoneTreeNode {
 
     fn child(&self, id: u16) -> Option<&NmdFileBoneTree<Self::Data>> {
         self.children()
-            .into_iter()
+            .iter()
             .find(|child| child.id() == id)
     }
 
@@ -117,7 +117,7 @@ pub trait NmdFileBoneTreeNode {
 
     fn child_mut(&mut self, id: u16) -> Option<&mut NmdFileBoneTree<Self::Data>> {
         self.children_mut()
-            .into_iter()
+            .iter_mut()
             .find(|child| child.id() == id)
     }
 
@@ -136,7 +136,7 @@ pub trait NmdFileBoneTreeNode {
             self.mut_opt()
         } else {
             self.children_mut()
-                .into_iter()
+                .iter_mut()
                 .filter_map(|child| child.find_mut(id))
                 .next()
         }
@@ -152,7 +152,7 @@ pub trait NmdFileBoneTreeNode {
             Some(self)
         } else {
             self.children()
-                .into_iter()
+                .iter()
                 .filter_map(|child| child.find_parent(child_id))
                 .next()
         }
@@ -168,7 +168,7 @@ pub trait NmdFileBoneTreeNode {
             Some(self)
         } else {
             self.children_mut()
-                .into_iter()
+                .iter_mut()
                 .filter_map(|child| child.find_parent_mut(child_id))
                 .next()
         }
@@ -199,7 +199,7 @@ pub trait NmdFileBoneTreeNode {
     }
 
     fn id(&self) -> u16;
-    fn iter(&self) -> NmdFileBoneTreeIterator<Self::Data>;
+    fn iter(&self) -> NmdFileBoneTreeIterator<'_, Self::Data>;
 
     fn insert(&mut self, descendent: NmdFileBoneTree<Self::Data>) -> Option<NmdFileBoneTree<Self::Data>> {
         match self.find_mut(descendent.parent_id()) {
@@ -294,12 +294,8 @@ pub trait NmdFileBoneTreeNode {
     }
 
     fn take_child(&mut self, child_id: u16) -> Option<NmdFileBoneTree<Self::Data>> {
-        self.children()
-            .iter()
-            .enumerate()
-            .filter_map(|(i, child)| (child.id() == child_id).then(|| i))
-            .next()
-            .and_then(|i| Some(self.children_mut().remove(i)))
+        self.child_index(child_id)
+            .map(|i| self.children_mut().remove(i))
     }
 }
 
@@ -317,7 +313,7 @@ impl<A> NmdFileBoneTreeRoot<A> {
         let mut orphans = VecDeque::new();
 
         for bone_data in iterable {
-            let orphan = NmdFileBoneTree::new(&bone_data, assoc_fn(&bone_data));
+            let orphan = NmdFileBoneTree::new(bone_data, assoc_fn(bone_data));
 
             if bone_data.parent_id == NmdFileBone::ROOT_BONE_ID {
                 root.children.push(orphan);
@@ -327,8 +323,35 @@ impl<A> NmdFileBoneTreeRoot<A> {
         }
 
         root.assimilate(&mut orphans);
+        root.adopt(orphans);
         root
     }
+
+    // Bones whose parent never turned up stay at the top, keeping their
+    // dangling parent ID, with any orphans descended from them beneath
+    fn adopt(&mut self, orphans: VecDeque<NmdFileBoneTree<A>>) {
+        let orphan_ids: HashSet<u16> = orphans.iter().map(|orphan| orphan.id()).collect();
+        let (tops, mut orphans): (VecDeque<_>, VecDeque<_>) = orphans
+            .into_iter()
+            .partition(|orphan| !orphan_ids.contains(&orphan.parent_id()));
+        let first = self.children.len();
+
+        self.children.extend(tops);
+
+        for top in &mut self.children[first..] {
+            top.assimilate(&mut orphans);
+        }
+
+        // Whatever is left has a cycle of parent IDs
+        self.children.extend(orphans);
+    }
+
+    /// Top-level subtrees whose parent ID matches no bone.
+    pub fn orphans(&self) -> impl Iterator<Item = &NmdFileBoneTree<A>> {
+        self.children
+            .iter()
+            .filter(|child| child.is_orphan())
+    }
 }
 
 impl<A> NmdFileBoneTreeNode for NmdFileBoneTreeRoot<A> {
@@ -354,7 +377,7 @@ impl<A> NmdFileBoneTreeNode for NmdFileBoneTreeRoot<A> {
         NmdFileBone::ROOT_BONE_ID
     }
 
-    fn iter(&self) -> NmdFileBoneTreeIterator<A> {
+    fn iter(&self) -> NmdFileBoneTreeIterator<'_, A> {
         self.into_iter()
     }
 
@@ -386,7 +409,7 @@ impl<'a, A> IntoIterator for &'a NmdFileBoneTreeRoot<A> {
 }
 
 impl<A> NmdFileBoneTree<A> {
-    fn new(bone_data: &NmdFileBone, associated_data: A) -> Self {
+    pub fn new(bone_data: &NmdFileBone, associated_data: A) -> Self {
         Self {
             data: NmdFileBoneTreeData {
                 associated_data: associated_data,
@@ -438,6 +461,12 @@ impl<A> NmdFileBoneTree<A> {
         }
     }
 
+    /// Whether this subtree sits at the top without being a root bone. Only
+    /// meaningful for children of the tree root.
+    pub fn is_orphan(&self) -> bool {
+        self.parent_id() != NmdFileBone::ROOT_BONE_ID
+    }
+
     #[inline(always)]
     pub fn data(&self) -> &A {
         &self.data.associated_data
@@ -474,7 +503,7 @@ impl<A> NmdFileBoneTreeNode for NmdFileBoneTree<A> {
         self.data.id
     }
 
-    fn iter(&self) -> NmdFileBoneTreeIterator<A> {
+    fn iter(&self) -> NmdFileBoneTreeIterator<'_, A> {
         self.into_iter()
     }
 
@@ -1,17 +1,22 @@
 use {
     crate::io::nmd::{
         anatomy::{
+            NmdFileAddress,
+            NmdFileBlob,
+            NmdFileBlobSegment,
             NmdFileBone,
             NmdFileHeader,
+            NmdFilePhysics,
+            NmdFileVersion,
         },
         data::tree::*,
         data::{ByteArr, ByteVec},
+        NmdError,
         NmdFileReader,
+        NmdResult,
     },
     std::{
         collections::BTreeMap,
-        io::Error,
-        io::Result,
     },
     serde::{
         Deserialize,
@@ -22,30 +27,103 @@ use {
 #[derive(Serialize, Deserialize)]
 pub struct NmdFileData {
     pub header: NmdFileHeader,
+    // Blob data split into what's known of it; empty if not decoded, in which
+    // case the raw blob is used
+    #[serde(default)]
+    pub blob: NmdFileBlob,
     pub bones: BTreeMap<u16, NmdFileBone>,
-    bytes: NmdFileRawData,
+    // Records whose ID an earlier record already has, by record index
+    #[serde(default)]
+    pub conflicts: BTreeMap<usize, NmdFileBone>,
+    // Physics records of physics bones, by bone ID
+    #[serde(default)]
+    pub physics: BTreeMap<u16, NmdFilePhysics>,
+    // Physics records of conflicting records, by record index
+    #[serde(default)]
+    pub conflict_physics: BTreeMap<usize, NmdFilePhysics>,
+    // Game the file was read as, which export defaults to
+    #[serde(default)]
+    pub version: NmdFileVersion,
+    // Other games whose layout fits the header as well, e.g. SCIV for SCV
+    #[serde(default)]
+    pub other_versions: Vec<NmdFileVersion>,
+    pub(crate) bytes: NmdFileRawData,
 }
 
 #[derive(Serialize, Deserialize)]
-struct NmdFileRawData {
-    header: ByteVec,
-    physics: ByteVec,
-    blob: ByteVec,
+pub(crate) struct NmdFileRawData {
+    pub(crate) header: ByteVec,
+    pub(crate) physics: ByteVec,
+    pub(crate) blob: ByteVec,
+    // Everything from the name address to the end of the file
+    #[serde(default)]
+    pub(crate) bone_names: ByteVec,
+    #[serde(default)]
+    pub(crate) records: Vec<NmdFileRecord>,
+}
+
+/// Where a bone chunk sat in the file it was read from, so that an unedited
+/// file can be written back in the same layout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NmdFileRecord {
+    pub id: u16,
+    pub name_address: NmdFileAddress,
 }
 
 impl NmdFileData {
+    /// Games this data can be exported for. Physics records and the blob's
+    /// segments are written in the target's byte order, but bytes outside
+    /// any of them are copied as read, so a file with any can only go to
+    /// games with the byte order it was read in.
+    pub fn export_versions(&self) -> Vec<NmdFileVersion> {
+        NmdFileVersion::VERSIONS.iter()
+            .filter(|version| !self.has_undecoded_bytes() || version.byte_order() == self.version.byte_order())
+            .copied()
+            .collect()
+    }
+
     pub fn get(&self, bone_id: u16) -> Option<&NmdFileBone> {
         self.bones.get(&bone_id)
     }
 
+    /// Bones whose parent ID matches no bone.
+    pub fn orphans(&self) -> impl Iterator<Item = &NmdFileBone> {
+        self.bones.values()
+            .filter(|bone_data| bone_data.parent_id != NmdFileBone::ROOT_BONE_ID
+                                && !self.bones.contains_key(&bone_data.parent_id))
+    }
+
+    // Whether any physics or blob bytes are outside every decoded structure:
+    // physics bytes before the first record (each record runs on to the next
+    // one), or a blob no table was found in
+    fn has_undecoded_bytes(&self) -> bool {
+        let block_address = self.header.physics_data_address() as usize;
+        let first_record_offset = self.bones.values()
+            .filter(|bone_data| self.physics.contains_key(&bone_data.id))
+            .chain(self.conflicts.iter()
+                .filter(|(record_index, _)| self.conflict_physics.contains_key(record_index))
+                .map(|(_, bone_data)| bone_data))
+            .map(|bone_data| (bone_data.physics_data_address as usize).saturating_sub(block_address))
+            .min()
+            .unwrap_or(self.raw_physics_data().len());
+        let blob_undecoded = self.blob.segments.iter()
+            .all(|segment| matches!(segment, NmdFileBlobSegment::Raw(_)));
+
+        first_record_offset > 0 || (blob_undecoded && !self.raw_blob().is_empty())
+    }
+
     pub fn get_clone(&self, bone_id: u16) -> Option<NmdFileBone> {
-        self.bones.get(&bone_id).map(|bone_data| bone_data.clone())
+        self.bones.get(&bone_id).cloned()
     }
 
     pub fn raw_blob(&self) -> &ByteVec {
         &self.bytes.blob
     }
 
+    pub fn raw_bone_name_data(&self) -> &ByteVec {
+        &self.bytes.bone_names
+    }
+
     pub fn raw_header(&self) -> ByteArr<'_> {
         &self.bytes.header[..]
     }
@@ -54,6 +132,12 @@ impl NmdFileData {
         &self.bytes.physics
     }
 
+    /// Bone chunks in on-disk order, including any whose ID was shared with an
+    /// earlier chunk.
+    pub fn records(&self) -> &Vec<NmdFileRecord> {
+        &self.bytes.records
+    }
+
     pub fn set(&mut self, bone_id: u16, bone_data: NmdFileBone) {
         self.bones.insert(bone_id, bone_data);
     }
@@ -64,25 +148,38 @@ impl NmdFileData {
 }
 
 impl TryFrom<&mut NmdFileReader> for NmdFileData {
-    type Error = Error;
+    type Error = NmdError;
+
+    fn try_from(reader: &mut NmdFileReader) -> NmdResult<Self> {
+        let (bones, conflicts) = reader.read_bones()?;
+        let (physics, conflict_physics) = reader.read_physics(&bones, &conflicts)?;
+        let records = reader.read_records()?;
+        let blob = reader.read_blob(&records)?;
 
-    fn try_from(reader: &mut NmdFileReader) -> Result<Self> {
         Ok(Self {
-            bones: reader.read_bones()?,
+            blob: blob,
+            bones: bones,
+            conflicts: conflicts,
+            conflict_physics: conflict_physics,
             header: reader.header().to_owned(),
+            physics: physics,
+            version: reader.version(),
+            other_versions: reader.other_versions().to_vec(),
             bytes: NmdFileRawData::try_from(reader)?,
         })
     }
 }
 
 impl TryFrom<&mut NmdFileReader> for NmdFileRawData {
-    type Error = Error;
+    type Error = NmdError;
 
-    fn try_from(reader: &mut NmdFileReader) -> Result<Self> {
+    fn try_from(reader: &mut NmdFileReader) -> NmdResult<Self> {
         Ok(Self {
             header: reader.read_header_bytes()?,
             physics: reader.read_physics_bytes()?,
             blob: reader.read_blob_bytes()?,
+            bone_names: reader.read_bone_name_bytes()?,
+            records: reader.read_records()?,
         })
     }
 }
@@ -1,9 +1,30 @@
 mod bone_tree;
 mod file_data;
+mod parent_suggestion;
 
 pub(in crate) type ByteArr<'a> = &'a [u8];
 pub(in crate) type ByteVec = Vec<u8>;
 
+/// Bytes as space-separated hex pairs, e.g. "00 1A FF".
+pub(crate) fn hex_text(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Bytes from hex pairs, ignoring whitespace.
+pub(crate) fn hex_bytes(text: &str) -> Option<Vec<u8>> {
+    let digits: Vec<char> = text.chars().filter(|character| !character.is_whitespace()).collect();
+
+    digits.chunks(2)
+        .map(|pair| match pair {
+            [high, low] => u8::from_str_radix(&format!("{}{}", high, low), 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
 pub mod tree {
     pub use {
         super::bone_tree::{
@@ -15,6 +36,11 @@ pub mod tree {
     };
 }
 
+pub(crate) use file_data::NmdFileRawData;
+
 pub use {
     file_data::NmdFileData,
+    file_data::NmdFileRecord,
+    parent_suggestion::NmdFileParentReason,
+    parent_suggestion::NmdFileParentSuggestion,
 };
@@ -0,0 +1,198 @@
+use {
+    crate::io::nmd::anatomy::NmdFileBone,
+    std::{
+        collections::HashMap,
+        fmt::{
+            self,
+            Display,
+            Formatter,
+        },
+    },
+};
+
+/// A bone that could take the place of an orphan's missing parent.
+#[derive(Debug, Clone)]
+pub struct NmdFileParentSuggestion {
+    pub parent_id: u16,
+    pub parent_name: String,
+    pub reason: NmdFileParentReason,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum NmdFileParentReason {
+    // The orphan's mirror image (`KATA_R` for `KATA_L`) and its parent
+    Mirror(String, String),
+    // The bone before the orphan in a numbered chain
+    Sequence,
+    // Number of leading name parts shared with the orphan
+    NamePrefix(usize),
+}
+
+struct BoneIndex<'a> {
+    by_id: HashMap<u16, &'a NmdFileBone>,
+    by_name: HashMap<&'a str, &'a NmdFileBone>,
+}
+
+impl NmdFileParentSuggestion {
+    const SIDES: &'static [(&'static str, &'static str)] = &[
+        ("L", "R"),
+        ("l", "r"),
+        ("LEFT", "RIGHT"),
+        ("Left", "Right"),
+        ("left", "right"),
+    ];
+
+    /// Rank candidates for the parent of the bone with the given ID, best
+    /// first. Bones beneath the orphan are never suggested.
+    pub fn for_orphan<'a>(bones: impl IntoIterator<Item = &'a NmdFileBone>, orphan_id: u16) -> Vec<Self> {
+        let index = BoneIndex::new(bones);
+        let orphan = match index.by_id.get(&orphan_id) {
+            Some(orphan) => *orphan,
+            None => return vec!(),
+        };
+        let mut suggestions = vec!();
+
+        if let Some(suggestion) = Self::by_mirror(&index, orphan) {
+            suggestions.push(suggestion);
+        }
+
+        if let Some(suggestion) = Self::by_sequence(&index, orphan) {
+            suggestions.push(suggestion);
+        }
+
+        suggestions.extend(Self::by_name_prefix(&index, orphan));
+        suggestions.retain(|suggestion| !index.descends_from(suggestion.parent_id, orphan_id));
+
+        // Keep the best reason for each parent
+        let mut suggested = Vec::<u16>::new();
+
+        suggestions.retain(|suggestion| {
+            let first = !suggested.contains(&suggestion.parent_id);
+
+            suggested.push(suggestion.parent_id);
+            first
+        });
+
+        suggestions
+    }
+
+    // `KATA_L` has no parent, but `KATA_R` is under `SAKOTSU_R`, so try
+    // `SAKOTSU_L`; or the same parent if that has no side
+    fn by_mirror(index: &BoneIndex, orphan: &NmdFileBone) -> Option<Self> {
+        let mirror = index.by_name.get(Self::mirror_name(&orphan.name)?.as_str())?;
+        let mirror_parent = index.by_id.get(&mirror.parent_id)?;
+        let parent = match Self::mirror_name(&mirror_parent.name) {
+            Some(parent_name) => index.by_name.get(parent_name.as_str())?,
+            None => mirror_parent,
+        };
+
+        Some(Self::new(parent, NmdFileParentReason::Mirror(mirror.name.to_owned(), mirror_parent.name.to_owned())))
+    }
+
+    fn by_name_prefix<'a>(index: &'a BoneIndex, orphan: &'a NmdFileBone) -> impl Iterator<Item = Self> + 'a {
+        let mut candidates: Vec<_> = index.by_id
+            .values()
+            .filter(|bone_data| bone_data.id != orphan.id)
+            .map(|bone_data| (Self::shared_parts(&orphan.name, &bone_data.name), *bone_data))
+            .filter(|(shared_parts, _)| *shared_parts > 0)
+            .collect();
+
+        candidates.sort_by(|(a_parts, a), (b_parts, b)| b_parts.cmp(a_parts).then(a.id.cmp(&b.id)));
+        candidates
+            .into_iter()
+            .map(|(shared_parts, bone_data)| Self::new(bone_data, NmdFileParentReason::NamePrefix(shared_parts)))
+    }
+
+    // `hair_03` goes under `hair_02`
+    fn by_sequence(index: &BoneIndex, orphan: &NmdFileBone) -> Option<Self> {
+        let stem = orphan.name.trim_end_matches(|c: char| c.is_ascii_digit());
+        let digits = &orphan.name[stem.len()..];
+        let number = digits.parse::<u32>().ok()?.checked_sub(1)?;
+        let parent_name = format!("{}{:0width$}", stem, number, width = digits.len());
+
+        Some(Self::new(index.by_name.get(parent_name.as_str())?, NmdFileParentReason::Sequence))
+    }
+
+    /// Swap the side of every part of a name that has one, if any does.
+    fn mirror_name(name: &str) -> Option<String> {
+        let mut mirrored = false;
+        let parts: Vec<_> = name.split('_')
+            .map(|part| {
+                let side = Self::SIDES.iter().find_map(|(left, right)| {
+                    if part == *left {
+                        Some(*right)
+                    } else if part == *right {
+                        Some(*left)
+                    } else {
+                        None
+                    }
+                });
+
+                mirrored |= side.is_some();
+                side.unwrap_or(part)
+            })
+            .collect();
+
+        mirrored.then(|| parts.join("_"))
+    }
+
+    fn new(parent: &NmdFileBone, reason: NmdFileParentReason) -> Self {
+        Self {
+            parent_id: parent.id,
+            parent_name: parent.name.to_owned(),
+            reason: reason,
+        }
+    }
+
+    fn shared_parts(a: &str, b: &str) -> usize {
+        a.split('_')
+            .zip(b.split('_'))
+            .take_while(|(a_part, b_part)| !a_part.is_empty() && a_part.eq_ignore_ascii_case(b_part))
+            .count()
+    }
+}
+
+impl<'a> BoneIndex<'a> {
+    fn new(bones: impl IntoIterator<Item = &'a NmdFileBone>) -> Self {
+        let mut index = Self {
+            by_id: HashMap::new(),
+            by_name: HashMap::new(),
+        };
+
+        for bone_data in bones {
+            index.by_id.insert(bone_data.id, bone_data);
+            index.by_name.entry(bone_data.name.as_str()).or_insert(bone_data);
+        }
+
+        index
+    }
+
+    fn descends_from(&self, bone_id: u16, ancestor_id: u16) -> bool {
+        let mut next_id = bone_id;
+
+        // Bounded, in case parent IDs form a cycle
+        for _ in 0..=self.by_id.len() {
+            if next_id == ancestor_id {
+                return true;
+            }
+
+            match self.by_id.get(&next_id) {
+                Some(bone_data) => next_id = bone_data.parent_id,
+                None => return false,
+            }
+        }
+
+        false
+    }
+}
+
+impl Display for NmdFileParentReason {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Mirror(mirror, mirror_parent) => write!(formatter, "mirrors {} under {}", mirror, mirror_parent),
+            Self::Sequence                      => write!(formatter, "previous in sequence"),
+            Self::NamePrefix(1)                 => write!(formatter, "shares 1 name part"),
+            Self::NamePrefix(parts)             => write!(formatter, "shares {} name parts", parts),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use {
+    crate::io::nmd::anatomy::{
+        token::NmdFileToken,
+        NmdFileHeader,
+        NmdFileVersion,
+    },
+    std::{
+        error,
+        fmt::{
+            self,
+            Display,
+            Formatter,
+        },
+        io::{
+            self,
+            ErrorKind,
+        },
+    },
+};
+
+pub type NmdResult<T> = Result<T, NmdError>;
+
+/// Failure reading or writing an NMD file, with where it happened when known.
+#[derive(Debug)]
+pub enum NmdError {
+    // An I/O failure not tied to a field, e.g. opening the file
+    Io(io::Error),
+    // The file ended partway through a field
+    Truncated {
+        offset: u64,
+        token: Option<NmdFileToken>,
+        bone_id: Option<u16>,
+    },
+    // A header no game's layout fits
+    UnknownVersion,
+    // Header addresses that would give negative or overflowing block sizes
+    NonOrdinalHeader(NmdFileHeader),
+    // Bytes read in one byte order with no decoded structure can't be written
+    // in the other
+    ByteOrderConversion(NmdFileVersion, NmdFileVersion),
+    // An address too large for the field it's written to
+    AddressOutOfBounds {
+        offset: u64,
+        token: NmdFileToken,
+        bone_id: Option<u16>,
+        address: u64,
+    },
+    // A block came out at a different length than its contents call for
+    UnexpectedLength {
+        offset: u64,
+        expected: u64,
+        found: u64,
+    },
+    // Bones that shared a physics record when read, edited to differ in it
+    SharedPhysicsRecord {
+        address: u64,
+        bone_ids: (u16, u16),
+    },
+    // A name with a character the name block can't hold
+    UnencodableName {
+        bone_id: u16,
+        name: String,
+        character: char,
+    },
+}
+
+impl NmdError {
+    /// Fill in the bone a positioned error happened in, if not yet known.
+    pub fn for_bone(mut self, id: u16) -> Self {
+        match &mut self {
+            Self::Truncated { bone_id, .. }
+             | Self::AddressOutOfBounds { bone_id, .. } => { bone_id.get_or_insert(id); }
+            _ => {}
+        }
+
+        self
+    }
+
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Self::Truncated { offset, .. }
+             | Self::AddressOutOfBounds { offset, .. }
+             | Self::UnexpectedLength { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+}
+
+impl Display for NmdError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let bone_text = |bone_id: &Option<u16>| match bone_id {
+            Some(bone_id) => format!(" in bone {:#04X}", bone_id),
+            None => String::new(),
+        };
+
+        match self {
+            Self::Io(error)
+                => write!(formatter, "{}", error),
+            Self::Truncated { offset, token: Some(token), bone_id }
+                => write!(formatter, "{:#010X}: file ends while reading {:?}{}", offset, token, bone_text(bone_id)),
+            Self::Truncated { offset, token: None, bone_id }
+                => write!(formatter, "{:#010X}: file ends early{}", offset, bone_text(bone_id)),
+            Self::UnknownVersion
+                => write!(formatter, "header fits no game's layout: bone data doesn't follow it at {:#010X} in either byte order", NmdFileHeader::CHUNK_SIZE),
+            Self::NonOrdinalHeader(header)
+                => write!(formatter, "header addresses are out of order (bone data {:#010X}, {} bones, blob data {:#010X}, bone name data {:#010X})",
+                    header.bone_data_address, header.bone_count, header.blob_data_address, header.bone_name_data_address),
+            Self::ByteOrderConversion(from, to) => {
+                let versions: Vec<String> = NmdFileVersion::VERSIONS.iter()
+                    .filter(|version| version.byte_order() == from.byte_order())
+                    .map(ToString::to_string)
+                    .collect();
+
+                write!(formatter, "physics or blob bytes read as {} that aren't decoded can't be written for {} ({:?} to {:?}), as they're copied as read; export for {} instead",
+                    from, to, from.byte_order(), to.byte_order(), versions.join(" or "))
+            }
+            Self::AddressOutOfBounds { offset, token, bone_id, address }
+                => write!(formatter, "{:#010X}: address {:#X} doesn't fit {:?}{}", offset, address, token, bone_text(bone_id)),
+            Self::UnexpectedLength { offset, expected, found }
+                => write!(formatter, "{:#010X}: wrote {:#X} bytes where {:#X} were expected", offset, found, expected),
+            Self::SharedPhysicsRecord { address, bone_ids: (first_id, second_id) }
+                => write!(formatter, "bones {:#04X} and {:#04X} share the physics record at {:#010X} but were edited to differ in it; make them match to keep it shared",
+                    first_id, second_id, address),
+            Self::UnencodableName { bone_id, name, character }
+                => write!(formatter, "bone {:#04X} can't be named {:?}: {:?} can't be written (names only hold U+0001 to U+00BF)", bone_id, name, character),
+        }
+    }
+}
+
+impl error::Error for NmdError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NmdError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+// For callers that only deal in `io::Result`, e.g. the UI
+impl From<NmdError> for io::Error {
+    fn from(error: NmdError) -> Self {
+        match error {
+            NmdError::Io(error) => error,
+            NmdError::Truncated { .. } => io::Error::new(ErrorKind::UnexpectedEof, error),
+            _ => io::Error::new(ErrorKind::InvalidData, error),
+        }
+    }
+}
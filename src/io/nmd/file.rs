@@ -1,14 +1,12 @@
 use {
     crate::io::nmd::{
         data::NmdFileData,
+        NmdError,
         NmdFileReader,
         NmdFileWriter,
+        NmdResult,
     },
-    std::{
-        io::Error,
-        io::Result,
-        path::PathBuf,
-    },
+    std::path::PathBuf,
 };
 
 /*
@@ -39,9 +37,9 @@ impl NmdFile {
 }
 
 impl TryFrom<&PathBuf> for NmdFile {
-    type Error = Error;
+    type Error = NmdError;
 
-    fn try_from(path: &PathBuf) -> Result<Self> {
+    fn try_from(path: &PathBuf) -> NmdResult<Self> {
         let mut reader = NmdFileReader::try_from(path)?;
 
         Ok(Self {
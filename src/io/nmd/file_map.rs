@@ -0,0 +1,316 @@
+use {
+    crate::io::nmd::{
+        anatomy::{
+            token::NmdFileToken,
+            NmdFileBlobSegment,
+            NmdFileBone,
+            NmdFileHeader,
+            NmdFilePhysics,
+            NmdFileVersion,
+        },
+        data::{
+            ByteArr,
+            NmdFileRecord,
+        },
+        NmdError,
+        NmdFileAnnotation,
+        NmdFileAnnotationScope,
+        NmdFileAnnotations,
+        NmdFileReader,
+        NmdResult,
+    },
+    std::{
+        collections::BTreeMap,
+        fmt::{
+            self,
+            Display,
+            Formatter,
+        },
+        ops::Range,
+    },
+};
+
+/// What a span of a file's bytes holds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NmdFileBlock {
+    Header(NmdFileToken),
+    // Record index, ID read from the record, token
+    Bone(usize, u16, NmdFileToken),
+    // Bone ID (the first, if the record is shared), token if named
+    Physics(u16, Option<NmdFileToken>),
+    Blob(String),
+    // Indices of the records whose name address points here
+    BoneName(Vec<usize>),
+}
+
+/// A labelled, non-overlapping range of a file's bytes.
+#[derive(Debug, Clone)]
+pub struct NmdFileSpan {
+    pub range: Range<usize>,
+    pub block: NmdFileBlock,
+}
+
+/// The layout of a file's raw bytes: a span for each token of the header and
+/// of each bone chunk, for each physics record, for each identified part of
+/// the blob, and for each name in the name table.
+pub struct NmdFileMap {
+    header: NmdFileHeader,
+    records: Vec<NmdFileRecord>,
+    // Sorted by start
+    spans: Vec<NmdFileSpan>,
+    version: NmdFileVersion,
+}
+
+impl NmdFileMap {
+    /// Annotation naming the byte at `offset`, if any.
+    pub fn annotation_at<'a>(&self, annotations: &'a NmdFileAnnotations, offset: usize) -> Option<&'a NmdFileAnnotation> {
+        // Header bytes between tokens have no span
+        if offset < NmdFileHeader::CHUNK_SIZE as usize {
+            return annotations.at(NmdFileAnnotationScope::Header, offset);
+        }
+
+        let span = self.span_at(offset)?;
+
+        match &span.block {
+            NmdFileBlock::Bone(index, ..)
+                => annotations.at(NmdFileAnnotationScope::Bone, offset - self.bone_chunk(*index).start),
+            NmdFileBlock::Physics(_, Some(token))
+                => annotations.at(NmdFileAnnotationScope::Physics, offset - span.range.start + token.value().offset),
+            NmdFileBlock::Physics(_, None)
+                => annotations.at(NmdFileAnnotationScope::Physics, offset - span.range.start + NmdFilePhysics::HEAD_SIZE as usize),
+            _   => None,
+        }
+    }
+
+    /// Byte range of the `n`th bone chunk.
+    pub fn bone_chunk(&self, n: usize) -> Range<usize> {
+        let start = NmdFileHeader::CHUNK_SIZE as usize + n * NmdFileBone::CHUNK_SIZE as usize;
+
+        start..start + NmdFileBone::CHUNK_SIZE as usize
+    }
+
+    pub fn header(&self) -> &NmdFileHeader {
+        &self.header
+    }
+
+    /// Bone chunks in on-disk order.
+    pub fn records(&self) -> &Vec<NmdFileRecord> {
+        &self.records
+    }
+
+    /// Index of the first record with the given ID.
+    pub fn record_index(&self, bone_id: u16) -> Option<usize> {
+        self.records.iter().position(|record| record.id == bone_id)
+    }
+
+    /// Span covering `offset`, if any.
+    pub fn span_at(&self, offset: usize) -> Option<&NmdFileSpan> {
+        let i = self.spans.partition_point(|span| span.range.start <= offset);
+
+        self.spans.get(i.checked_sub(1)?)
+            .filter(|span| span.range.contains(&offset))
+    }
+
+    pub fn spans(&self) -> &Vec<NmdFileSpan> {
+        &self.spans
+    }
+
+    pub fn version(&self) -> NmdFileVersion {
+        self.version
+    }
+
+    fn blob_spans(reader: &mut NmdFileReader, records: &[NmdFileRecord]) -> NmdResult<Vec<NmdFileSpan>> {
+        let mut spans = vec!();
+        let mut offset = reader.header().blob_data_address as usize;
+
+        for segment in reader.read_blob(records)?.segments {
+            let length = segment.len();
+
+            match &segment {
+                NmdFileBlobSegment::BoneIdTable(ids) => {
+                    let entry_size = NmdFileBlobSegment::TABLE_ENTRY_SIZE;
+
+                    for (i, id) in ids.iter().enumerate() {
+                        let entry_offset = offset + i * entry_size;
+
+                        spans.push(NmdFileSpan::new(
+                            entry_offset..entry_offset + entry_size,
+                            NmdFileBlock::Blob(format!("{}, record {}: bone {:#04X}", segment, i, id))));
+                    }
+                }
+                NmdFileBlobSegment::Raw(_) => {
+                    spans.push(NmdFileSpan::new(offset..offset + length, NmdFileBlock::Blob(segment.to_string())));
+                }
+            }
+
+            offset += length;
+        }
+
+        Ok(spans)
+    }
+
+    fn bone_spans(&self, bones: &[NmdFileBone]) -> Vec<NmdFileSpan> {
+        let mut spans = vec!();
+
+        for (i, bone_data) in bones.iter().enumerate() {
+            let chunk_start = self.bone_chunk(i).start;
+
+            for token in NmdFileToken::BONE_TOKENS {
+                let value = token.value();
+                let start = chunk_start + value.offset;
+
+                spans.push(NmdFileSpan::new(
+                    start..start + value.format.size(),
+                    NmdFileBlock::Bone(i, bone_data.id, *token)));
+            }
+        }
+
+        spans
+    }
+
+    fn bone_name_spans(&self, bones: &[NmdFileBone], file_length: usize) -> Vec<NmdFileSpan> {
+        let name_block = self.header.bone_name_data_address as usize..file_length;
+        let mut name_records = BTreeMap::<usize, (usize, Vec<usize>)>::new();
+
+        for (i, (record, bone_data)) in self.records.iter().zip(bones).enumerate() {
+            let address = record.name_address as usize;
+            // As stored, one byte a character; names read from the file
+            // always encode
+            let length = NmdFileBone::encode_name(&bone_data.name).map_or(0, |name| name.len());
+
+            if name_block.contains(&address) {
+                name_records.entry(address)
+                    .or_insert_with(|| (length, vec!()))
+                    .1
+                    .push(i);
+            }
+        }
+
+        name_records.into_iter()
+            .map(|(address, (length, indices))| {
+                // Include the terminator, if the file doesn't end first
+                let end = (address + length + 1).min(file_length);
+
+                NmdFileSpan::new(address..end, NmdFileBlock::BoneName(indices))
+            })
+            .collect()
+    }
+
+    fn header_spans() -> Vec<NmdFileSpan> {
+        NmdFileToken::HEADER_TOKENS.iter()
+            .map(|token| {
+                let value = token.value();
+
+                NmdFileSpan::new(value.offset..value.offset + value.format.size(), NmdFileBlock::Header(*token))
+            })
+            .collect()
+    }
+
+    fn physics_spans(&self, reader: &mut NmdFileReader, bones: &[NmdFileBone]) -> NmdResult<Vec<NmdFileSpan>> {
+        // Every chunk by record index, so records of bones sharing an ID are
+        // found too
+        let records = bones.iter()
+            .cloned()
+            .enumerate()
+            .collect();
+        let mut record_starts = BTreeMap::<usize, (u16, usize)>::new();
+
+        for (record_index, physics) in reader.read_physics(&BTreeMap::new(), &records)?.1 {
+            let address = bones[record_index].physics_data_address as usize;
+
+            record_starts.entry(address).or_insert((physics.id, physics.len()));
+        }
+
+        let mut spans = vec!();
+
+        for (address, (bone_id, length)) in record_starts {
+            for token in NmdFileToken::PHYSICS_TOKENS {
+                let value = token.value();
+                let start = address + value.offset;
+
+                spans.push(NmdFileSpan::new(start..start + value.format.size(), NmdFileBlock::Physics(bone_id, Some(*token))));
+            }
+
+            let tail_start = address + NmdFilePhysics::HEAD_SIZE as usize;
+
+            if tail_start < address + length {
+                spans.push(NmdFileSpan::new(tail_start..address + length, NmdFileBlock::Physics(bone_id, None)));
+            }
+        }
+
+        Ok(spans)
+    }
+}
+
+impl NmdFileSpan {
+    fn new(range: Range<usize>, block: NmdFileBlock) -> Self {
+        Self {
+            range: range,
+            block: block,
+        }
+    }
+}
+
+impl TryFrom<ByteArr<'_>> for NmdFileMap {
+    type Error = NmdError;
+
+    fn try_from(bytes: ByteArr) -> NmdResult<Self> {
+        let mut reader = NmdFileReader::try_from(bytes.to_vec())?;
+        let records = reader.read_records()?;
+        let bones = (0..records.len())
+            .map(|n| reader.read_nth_bone(n))
+            .collect::<NmdResult<Vec<_>>>()?;
+        let mut map = Self {
+            header: reader.header().to_owned(),
+            records: records,
+            spans: Self::header_spans(),
+            version: reader.version(),
+        };
+
+        let bone_spans = map.bone_spans(&bones);
+        let physics_spans = map.physics_spans(&mut reader, &bones)?;
+        let blob_spans = Self::blob_spans(&mut reader, &map.records)?;
+        let bone_name_spans = map.bone_name_spans(&bones, bytes.len());
+
+        map.spans.extend(bone_spans);
+        map.spans.extend(physics_spans);
+        map.spans.extend(blob_spans);
+        map.spans.extend(bone_name_spans);
+        map.spans.sort_by_key(|span| span.range.start);
+
+        // Clip to the file, and to the next span where addresses overlap,
+        // e.g. a name that is the tail of another
+        for i in 0..map.spans.len() {
+            let limit = map.spans.get(i + 1)
+                .map_or(bytes.len(), |next| next.range.start.min(bytes.len()));
+            let span = &mut map.spans[i];
+
+            span.range.end = span.range.end.min(limit).max(span.range.start);
+        }
+
+        map.spans.retain(|span| !span.range.is_empty());
+
+        Ok(map)
+    }
+}
+
+impl Display for NmdFileBlock {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Header(token)
+                => write!(formatter, "header, {:?}", token),
+            Self::Bone(index, id, token)
+                => write!(formatter, "bone record {} (ID {:#04X}), {:?}", index, id, token),
+            Self::Physics(id, Some(token))
+                => write!(formatter, "physics record of bone {:#04X}, {:?}", id, token),
+            Self::Physics(id, None)
+                => write!(formatter, "physics record of bone {:#04X}, unknown data", id),
+            Self::Blob(label)
+                => write!(formatter, "blob data, {}", label),
+            Self::BoneName(indices) if indices.len() == 1
+                => write!(formatter, "bone name of record {}", indices[0]),
+            Self::BoneName(indices)
+                => write!(formatter, "bone name of records {:?}", indices),
+        }
+    }
+}
@@ -3,316 +3,431 @@ use {
         anatomy::{
             token::{
                 NmdFileToken,
-                NmdFileTokenValue,
+                NmdFileTokenCodec,
             },
             NmdFileAddress,
+            NmdFileBlob,
+            NmdFileBlobSegment,
             NmdFileBone,
             NmdFileBoneFlag,
             NmdFileHeader,
+            NmdFilePhysics,
+            NmdFileVersion,
         },
-        data::ByteVec,
+        data::{
+            ByteVec,
+            NmdFileRecord,
+        },
+        NmdError,
+        NmdResult,
     },
     std::{
-        collections::BTreeMap,
-        cmp,
-        io::{
-            Cursor,
-            Error,
-            ErrorKind,
-            Read,
-            Result,
-            Seek,
-            SeekFrom,
+        collections::{
+            btree_map::Entry,
+            BTreeMap,
         },
-        mem,
+        ops::Range,
         path::PathBuf,
-        iter::from_fn as iter_fn,
         fs,
     },
 };
 
-/*
- * TODO:
- * ~ Handle duplicate IDs & orphans (TODO FEAT:LOST_DATA)
- *
- *      Example:
- *
- *          hair_r006_f_H_Hair.nmd
- *
- *          (Duplicate)
- *          KOSHI                           ID: 0x0C    PARENT_ID: 0xFFFF
- *          ` Shares an ID with bone "shoulder_r__shit"
- *
- *          (Orphan)
- *          KATA_L                          ID: 0x09    PARENT_ID: 0x06
- *          ` Has a parent ID not corresponding to any bone
- *
- *          (Duplicate/Orphan)
- *          KATA_RT_L__prot_x0__offset      ID: 0x09    PARENT_ID: 0x07
- *
- *          (Orphan)
- *          shoulder_l_shit                 ID: 0x0A    PARENT_ID: 0x09
- *          ` Orphan with ambiguous parent (which would also be an orphan)
- *
- *      (Intuitively, KATA_L should be under SAKOTSU_L (ID: 0x08), and the next
- *      two bones should follow - same pattern as SAKOTSU_R.)
- */
-
-macro_rules! read {
-    ($reader:expr, $type:ty) => {
-        $reader.read::<$type, { mem::size_of::<$type>() }>()
-    };
-}
-
-macro_rules! read_at {
-    ($reader:expr, $type:ty, $token:path) => {
-        {
-            $reader.seek_token($token)?;
-
-            read!($reader, $type)
-        }
-    };
-}
-
-macro_rules! read_rewind {
-    ($reader:ident, $type:ty, $token:path) => {
-        {
-            let rewind_address = $reader.cursor.stream_position()?;
-            let read_result = read_at!($reader, $type, $token);
-
-            $reader.seek(rewind_address)?;
-
-            read_result
-        }
-    };
-}
-
-macro_rules! skip_then {
-    ($reader:ident, $type:ty, $then_expr:expr) => {
-        {
-            $reader.seek_rel(mem::size_of::<$type>() as i64)?;
-            $then_expr
-        }
-    };
-}
-
-type ByteCursor = Cursor<Vec<u8>>;
-
 #[derive(Debug)]
 pub struct NmdFileReader {
-    cached_header: Option<NmdFileHeader>,
-    cursor: ByteCursor,
+    bytes: ByteVec,
+    // Only ever ordinal, so sizes worked out from it are sound
+    header: NmdFileHeader,
+    version: NmdFileVersion,
+    // Other games whose layout fits the header as well as `version`'s
+    other_versions: Vec<NmdFileVersion>,
 }
 
 impl NmdFileReader {
-    fn anchor_cursor<F: FnOnce(&mut ByteCursor) -> T, T>(cursor: &mut ByteCursor, callback: F) -> Result<T> {
-        let resume_address = cursor.stream_position()?;
-        let value = callback(cursor);
+    fn blob_metadata(&self) -> (u64, usize) {
+        (self.header().blob_data_address as u64, self.header().blob_data_length())
+    }
 
-        cursor.seek(SeekFrom::Start(resume_address))?;
+    fn bone_name_metadata(&self) -> u64 {
+        self.header().bone_name_data_address as u64
+    }
 
-        Ok(value)
+    fn bone_metadata(&self) -> (u64, usize) {
+        (NmdFileHeader::CHUNK_SIZE, self.header().bone_count as usize)
     }
 
-    fn blob_metadata(&mut self) -> (u64, usize) {
-        (self.header().blob_data_address as u64, self.header().blob_data_length())
+    // The header has no version field, so try each game's layout on its own,
+    // keeping every one that reads a header agreeing with itself. Failing
+    // that, those that at least find bone data right after the header, leaving
+    // the bone count echo to the validator. SCIV and SCV share a layout and
+    // byte order, so a big endian file fits both.
+    fn detect_versions(&mut self) -> NmdResult<Vec<NmdFileVersion>> {
+        let mut fits = vec!();
+        let mut error_opt = None;
+
+        for version in NmdFileVersion::VERSIONS {
+            self.version = *version;
+
+            match self.header_fit() {
+                Ok(fit) => fits.push((*version, fit)),
+                Err(error) => { error_opt.get_or_insert(error); }
+            }
+        }
+
+        let fitting = |echo_matters: bool| -> Vec<NmdFileVersion> {
+            fits.iter()
+                .filter(|(_, (address_fits, echo_fits))| *address_fits && (*echo_fits || !echo_matters))
+                .map(|(version, _)| *version)
+                .collect()
+        };
+        let mut versions = fitting(true);
+
+        if versions.is_empty() {
+            versions = fitting(false);
+        }
+
+        match error_opt {
+            _ if !versions.is_empty() => Ok(versions),
+            // Too short for any layout's header
+            Some(error) if fits.is_empty() => Err(error),
+            _ => Err(NmdError::UnknownVersion),
+        }
     }
 
-    fn bone_metadata(&mut self) -> (u64, usize) {
-        (NmdFileHeader::CHUNK_SIZE, self.header().bone_count as usize)
+    fn find_bone_id_table(&self, bytes: &[u8], ids: &[u16]) -> Option<usize> {
+        let entry_size = NmdFileBlobSegment::TABLE_ENTRY_SIZE;
+        let table_length = ids.len() * entry_size;
+        let byte_order = self.version.byte_order();
+
+        (0..bytes.len().checked_sub(table_length)? + 1)
+            .step_by(entry_size)
+            .find(|offset| {
+                bytes[*offset..*offset + table_length]
+                    .chunks_exact(entry_size)
+                    .zip(ids)
+                    .all(|(entry, id)| u16::decode(entry, byte_order) == Some(*id))
+            })
     }
 
     pub fn header(&self) -> &NmdFileHeader {
-        self.cached_header.as_ref().unwrap()
+        &self.header
     }
 
-    fn iter_bones<'s>(&'s mut self) -> Result<impl IntoIterator<Item = NmdFileBone> + 's> {
-        let (mut address, mut count) = self.bone_metadata();
+    // Whether the header, read as the current version, has bone data right
+    // after it, and a bone count matching its echo
+    fn header_fit(&self) -> NmdResult<(bool, bool)> {
+        use NmdFileToken::*;
 
-        iter_fn(|| {
-            if count > 0 {
-                match self.read_bone_at(address) {
-                    Ok(bone_data) => {
-                        address += NmdFileBone::CHUNK_SIZE;
-                        count -= 1;
+        let bone_count: u16 = self.read_token(0, HeaderBoneCount)?;
+        let bone_count_echo: u16 = self.read_token(0, HeaderBoneCountEcho)?;
+        let bone_data_address: NmdFileAddress = self.read_token(0, HeaderBoneDataAddress)?;
 
-                        Some(Ok(bone_data))
-                    }
-                    Err(error) => Some(Err(error))
-                }
-            } else {
-                None
-            }
-        }).collect::<Result<Vec<_>>>()
+        Ok((bone_data_address as u64 == NmdFileHeader::CHUNK_SIZE, bone_count == bone_count_echo))
     }
 
     fn physics_metadata(&self) -> (u64, usize) {
         (self.header().physics_data_address() as u64, self.header().physics_data_length())
     }
 
-    fn read<T: Sized, const T_SIZE: usize>(&mut self) -> Result<T> {
-        unsafe {
-            let mut bytes = [0u8; T_SIZE];
+    /// Split the blob into the sub-structures that can be identified in it,
+    /// given the file's bone records.
+    pub fn read_blob(&mut self, records: &[NmdFileRecord]) -> NmdResult<NmdFileBlob> {
+        let bytes = self.read_blob_bytes()?;
+        let ids: Vec<u16> = records.iter().map(|record| record.id).collect();
+        let mut segments = vec!();
+
+        // A table of every record's ID in order; too short to tell apart from
+        // chance with fewer than two bones
+        match self.find_bone_id_table(&bytes, &ids) {
+            Some(offset) if ids.len() > 1 => {
+                let table_end = offset + ids.len() * NmdFileBlobSegment::TABLE_ENTRY_SIZE;
+
+                if offset > 0 {
+                    segments.push(NmdFileBlobSegment::Raw(bytes[..offset].to_vec()));
+                }
 
-            self.cursor.read_exact(&mut bytes)?;
+                segments.push(NmdFileBlobSegment::BoneIdTable(ids));
 
-            Ok(mem::transmute_copy(&bytes))
+                if table_end < bytes.len() {
+                    segments.push(NmdFileBlobSegment::Raw(bytes[table_end..].to_vec()));
+                }
+            }
+            _ if !bytes.is_empty() => segments.push(NmdFileBlobSegment::Raw(bytes)),
+            _ => {}
         }
+
+        Ok(NmdFileBlob {
+            segments: segments,
+        })
     }
 
-    pub fn read_blob_bytes(&mut self) -> Result<ByteVec> {
+    pub fn read_blob_bytes(&mut self) -> NmdResult<ByteVec> {
         let (address, length) = self.blob_metadata();
 
         self.read_bytes(address, length)
     }
 
-    fn read_bytes(&mut self, address: u64, count: usize) -> Result<ByteVec> {
-        let mut bytes = Vec::<u8>::new();
+    // The whole of `count` bytes from `address`, or where the file ends first
+    fn read_bytes(&self, address: u64, count: usize) -> NmdResult<ByteVec> {
+        let range = Self::range(address, count as u64);
+
+        match self.bytes.get(range) {
+            Some(bytes) => Ok(bytes.to_vec()),
+            None => Err(NmdError::Truncated {
+                offset: self.bytes.len() as u64,
+                token: None,
+                bone_id: None,
+            }),
+        }
+    }
 
-        self.seek(address)?;
-        self.cursor.by_ref().take(count as u64).read_to_end(&mut bytes)?;
+    pub fn read_bone_name_bytes(&mut self) -> NmdResult<ByteVec> {
+        let address = self.bone_name_metadata();
 
-        Ok(bytes)
+        // The name block runs to the end of the file
+        self.read_bytes(address, self.bytes.len().saturating_sub(address as usize))
     }
 
-    pub fn read_bones(&mut self) -> Result<BTreeMap<u16, NmdFileBone>> {
+    /// Read every bone record, keyed by ID. A record whose ID was already read
+    /// is returned separately, keyed by its record index.
+    //
+    // Example (hair_r006_f_H_Hair.nmd): KOSHI (ID: 0x0C) shares an ID with
+    // shoulder_r__shit, and KATA_RT_L__prot_x0__offset (ID: 0x09) with KATA_L
+    pub fn read_bones(&mut self) -> NmdResult<(BTreeMap<u16, NmdFileBone>, BTreeMap<usize, NmdFileBone>)> {
+        let (_, count) = self.bone_metadata();
         let mut bone_map = BTreeMap::<u16, NmdFileBone>::new();
+        let mut conflict_map = BTreeMap::<usize, NmdFileBone>::new();
 
-        for bone_data in self.iter_bones()? {
-            // TODO FEAT:LOST_DATA
-            bone_map.insert(bone_data.id, bone_data);
-        }
+        for i in 0..count {
+            let bone_data = self.read_nth_bone(i)?;
 
-        Ok(bone_map)
-    }
-
-    fn read_bone_at(&mut self, address: u64) -> Result<NmdFileBone> {
-        self.seek(address)?;
+            match bone_map.entry(bone_data.id) {
+                Entry::Vacant(entry) => { entry.insert(bone_data); }
+                Entry::Occupied(_) => { conflict_map.insert(i, bone_data); }
+            }
+        }
 
-        Ok(self.read_bone_at_cursor()?)
+        Ok((bone_map, conflict_map))
     }
 
-    fn read_bone_at_cursor(&mut self) -> Result<NmdFileBone> {
+    fn read_bone_at(&self, address: u64) -> NmdResult<NmdFileBone> {
         use NmdFileToken::*;
 
-        let name_address = read_rewind!(self, NmdFileAddress, BoneNameAddress)?;
-
-        Ok(NmdFileBone {
-            collision_data: read!(self, [u8; 16])?,
-            translation_x: read!(self, f32)?,
-            translation_y: read!(self, f32)?,
-            translation_z: read!(self, f32)?,
-            unknown_data_a: read!(self, [u8; 4])?,
-            rotation_x: read!(self, f32)?,
-            rotation_y: read!(self, f32)?,
-            rotation_z: read!(self, f32)?,
-            unknown_data_b: read!(self, [u8; 4])?,
-            name: self.read_bone_name(name_address as u64)?,
-            unknown_data_c: skip_then!(self, NmdFileAddress, read!(self, [u8; 4])?),
-            physics_data_address: read!(self, NmdFileAddress)?,
-            unknown_data_d: read!(self, [u8; 4])?,
-            translation_x_next: read!(self, f32)?,
-            gravity_x: read!(self, i16)?,
-            gravity_y: read!(self, i16)?,
-            physics_constraint_x_max: read!(self, i8)?,
-            physics_constraint_x_min: read!(self, i8)?,
-            physics_constraint_y_max: read!(self, i8)?,
-            physics_constraint_y_min: read!(self, i8)?,
-            unknown_data_e: read!(self, [u8; 19])?,
-            flag: NmdFileBoneFlag::from(read!(self, u8)?),
-            parent_id: read!(self, u16)?,
-            id: read!(self, u16)?,
-            unknown_data_f: read!(self, [u8; 12])?,
+        let name_address: NmdFileAddress = self.read_token(address, BoneNameAddress)?;
+        let bone_data = || -> NmdResult<NmdFileBone> {
+            Ok(NmdFileBone {
+                collision_data: self.read_token(address, BoneCollisionData)?,
+                translation_x: self.read_token(address, BoneTranslationX)?,
+                translation_y: self.read_token(address, BoneTranslationY)?,
+                translation_z: self.read_token(address, BoneTranslationZ)?,
+                unknown_data_a: self.read_token(address, BoneUnknownDataA)?,
+                rotation_x: self.read_token(address, BoneRotationX)?,
+                rotation_y: self.read_token(address, BoneRotationY)?,
+                rotation_z: self.read_token(address, BoneRotationZ)?,
+                unknown_data_b: self.read_token(address, BoneUnknownDataB)?,
+                name: self.read_bone_name(name_address as u64),
+                unknown_data_c: self.read_token(address, BoneUnknownDataC)?,
+                physics_data_address: self.read_token(address, BonePhysicsDataAddress)?,
+                unknown_data_d: self.read_token(address, BoneUnknownDataD)?,
+                translation_x_next: self.read_token(address, BoneTranslationXNext)?,
+                gravity_x: self.read_token(address, BoneGravityX)?,
+                gravity_y: self.read_token(address, BoneGravityY)?,
+                physics_constraint_x_max: self.read_token(address, BonePhysicsConstraintXPos)?,
+                physics_constraint_x_min: self.read_token(address, BonePhysicsConstraintXNeg)?,
+                physics_constraint_y_max: self.read_token(address, BonePhysicsConstraintYPos)?,
+                physics_constraint_y_min: self.read_token(address, BonePhysicsConstraintYNeg)?,
+                unknown_data_e: self.read_token(address, BoneUnknownDataE)?,
+                flag: NmdFileBoneFlag::from(self.read_token::<u8>(address, BoneFlag)?),
+                parent_id: self.read_token(address, BoneParentId)?,
+                id: self.read_token(address, BoneId)?,
+                unknown_data_f: self.read_token(address, BoneUnknownDataF)?,
+            })
+        };
+
+        bone_data().map_err(|error| {
+            // The ID comes last in the chunk, so may well be readable still
+            match self.read_token(address, BoneId) {
+                Ok(bone_id) => error.for_bone(bone_id),
+                Err(_) => error,
+            }
         })
     }
 
-    fn read_bone_name(&mut self, name_address: u64) -> Result<String> {
-        let mut name = String::from("");
+    /// Read the bone in the `n`th chunk, whether or not its ID is shared.
+    pub fn read_nth_bone(&mut self, n: usize) -> NmdResult<NmdFileBone> {
+        let (address, _) = self.bone_metadata();
 
-        Self::anchor_cursor(&mut self.cursor, |cursor| {
-            cursor.seek(SeekFrom::Start(name_address))?;
+        self.read_bone_at(address + n as u64 * NmdFileBone::CHUNK_SIZE)
+    }
 
-            for byte in cursor.bytes() {
-                let character = Self::to_ascii(byte?);
+    pub fn read_records(&mut self) -> NmdResult<Vec<NmdFileRecord>> {
+        use NmdFileToken::*;
 
-                if character != '\0' {
-                    name.push(character);
-                } else {
-                    break;
-                }
-            }
+        let (address, count) = self.bone_metadata();
 
-            Ok::<_, Error>(())
-        })??;
+        (0..count as u64)
+            .map(|n| {
+                let chunk_address = address + n * NmdFileBone::CHUNK_SIZE;
 
-        Ok(name)
+                Ok(NmdFileRecord {
+                    name_address: self.read_token(chunk_address, BoneNameAddress)?,
+                    id: self.read_token(chunk_address, BoneId)?,
+                })
+            })
+            .collect()
+    }
+
+    // Up to the terminator, or the end of the file
+    fn read_bone_name(&self, name_address: u64) -> String {
+        NmdFileBone::decode_name(self.bytes.get(name_address as usize..).unwrap_or_default())
     }
 
     // Use `header()` instead of this method for outside calls
-    fn read_header(&mut self) -> Result<NmdFileHeader> {
+    fn read_header(&self) -> NmdResult<NmdFileHeader> {
         use NmdFileToken::*;
 
         Ok(NmdFileHeader {
-            bone_count: read_at!(self, u16, HeaderBoneCount)?,
-            blob_data_address: read_at!(self, NmdFileAddress, HeaderBlobDataAddress)?,
-            bone_name_data_address: read_at!(self, NmdFileAddress, HeaderBoneNameDataAddress)?,
-            bone_data_address: read_at!(self, NmdFileAddress, HeaderBoneDataAddress)?,
+            bone_count: self.read_token(0, HeaderBoneCount)?,
+            blob_data_address: self.read_token(0, HeaderBlobDataAddress)?,
+            bone_name_data_address: self.read_token(0, HeaderBoneNameDataAddress)?,
+            bone_data_address: self.read_token(0, HeaderBoneDataAddress)?,
         })
     }
 
-    pub fn read_header_bytes(&mut self) -> Result<ByteVec> {
+    pub fn read_header_bytes(&mut self) -> NmdResult<ByteVec> {
         self.read_bytes(0, NmdFileHeader::CHUNK_SIZE as usize)
     }
 
-    pub fn read_physics_bytes(&mut self) -> Result<ByteVec> {
+    /// Decode the physics record of each physics bone whose address falls in
+    /// the physics block, keyed by bone ID, and those of conflicting records,
+    /// keyed by record index.
+    pub fn read_physics(&mut self, bones: &BTreeMap<u16, NmdFileBone>, conflicts: &BTreeMap<usize, NmdFileBone>) -> NmdResult<(BTreeMap<u16, NmdFilePhysics>, BTreeMap<usize, NmdFilePhysics>)> {
         let (address, length) = self.physics_metadata();
+        let block = address..address + length as u64;
+        let mut record_addresses: Vec<u64> = bones.values()
+            .chain(conflicts.values())
+            .filter(|bone_data| bone_data.is_phys())
+            .map(|bone_data| bone_data.physics_data_address as u64)
+            .filter(|record_address| block.contains(record_address))
+            .collect();
+        let mut record_ends = BTreeMap::<u64, u64>::new();
+
+        record_addresses.sort_unstable();
+        record_addresses.dedup();
+
+        for (i, record_address) in record_addresses.iter().enumerate() {
+            let record_end = record_addresses.get(i + 1).copied().unwrap_or(block.end);
+
+            if record_end - record_address >= NmdFilePhysics::HEAD_SIZE {
+                record_ends.insert(*record_address, record_end);
+            }
+        }
 
-        self.read_bytes(address, length)
+        // Bones sharing a record each get a copy
+        let mut read = |bone_data: &NmdFileBone| -> NmdResult<Option<NmdFilePhysics>> {
+            let record_address = bone_data.physics_data_address as u64;
+
+            match record_ends.get(&record_address) {
+                Some(record_end) if bone_data.is_phys() => self.read_physics_at(bone_data.id, record_address, *record_end)
+                    .map(Some)
+                    .map_err(|error| error.for_bone(bone_data.id)),
+                _ => Ok(None),
+            }
+        };
+        let mut physics_map = BTreeMap::<u16, NmdFilePhysics>::new();
+        let mut conflict_physics_map = BTreeMap::<usize, NmdFilePhysics>::new();
+
+        for bone_data in bones.values() {
+            if let Some(physics) = read(bone_data)? {
+                physics_map.insert(bone_data.id, physics);
+            }
+        }
+
+        for (record_index, bone_data) in conflicts {
+            if let Some(physics) = read(bone_data)? {
+                conflict_physics_map.insert(*record_index, physics);
+            }
+        }
+
+        Ok((physics_map, conflict_physics_map))
+    }
+
+    fn read_physics_at(&self, bone_id: u16, address: u64, end: u64) -> NmdResult<NmdFilePhysics> {
+        use NmdFileToken::*;
+
+        Ok(NmdFilePhysics {
+            id: bone_id,
+            parameter_a: self.read_token(address, PhysicsParameterA)?,
+            parameter_b: self.read_token(address, PhysicsParameterB)?,
+            parameter_c: self.read_token(address, PhysicsParameterC)?,
+            unknown_data: self.read_bytes(address + NmdFilePhysics::HEAD_SIZE, (end - address - NmdFilePhysics::HEAD_SIZE) as usize)?,
+        })
     }
 
-    pub fn seek(&mut self, address: u64) -> Result<u64> {
-        self.cursor.seek(SeekFrom::Start(address))
+    pub fn read_physics_bytes(&mut self) -> NmdResult<ByteVec> {
+        let (address, length) = self.physics_metadata();
+
+        self.read_bytes(address, length)
     }
 
-    pub fn seek_rel(&mut self, offset: i64) -> Result<u64> {
-        self.cursor.seek(SeekFrom::Current(offset))
+    /// Decode a token's value, counting from `address` if the token is
+    /// relative (e.g. to the start of a bone chunk).
+    fn read_token<T: NmdFileTokenCodec>(&self, address: u64, token: NmdFileToken) -> NmdResult<T> {
+        token.decode(&self.bytes, address as usize, self.version)
+            .ok_or_else(|| NmdError::Truncated {
+                offset: token.range_for(self.version, address as usize).start as u64,
+                token: Some(token),
+                bone_id: None,
+            })
     }
 
-    pub fn seek_token(&mut self, token: NmdFileToken) -> Result<u64> {
-        let NmdFileTokenValue {
-            is_relative,
-            offset,
-            ..
-        } = token.value();
+    /// Games other than the one read as whose layout fits the header just as
+    /// well, so the file could be for any of them.
+    pub fn other_versions(&self) -> &[NmdFileVersion] {
+        &self.other_versions
+    }
 
-        self.cursor.seek(match is_relative {
-            true  => SeekFrom::Current(offset as i64),
-            false => SeekFrom::Start(offset as u64),
-        })
+    pub fn version(&self) -> NmdFileVersion {
+        self.version
     }
 
-    fn to_ascii(byte: u8) -> char {
-        char::from(cmp::max(NmdFileBone::ASCII_BYTE_SHIFT, byte) - NmdFileBone::ASCII_BYTE_SHIFT)
+    // Saturates rather than overflows on addresses read from the file
+    fn range(address: u64, count: u64) -> Range<usize> {
+        address as usize..address.saturating_add(count) as usize
     }
 }
 
 impl TryFrom<&PathBuf> for NmdFileReader {
-    type Error = Error;
+    type Error = NmdError;
+
+    fn try_from(path: &PathBuf) -> NmdResult<Self> {
+        Self::try_from(fs::read(path)?)
+    }
+}
+
+impl TryFrom<ByteVec> for NmdFileReader {
+    type Error = NmdError;
 
-    fn try_from(path: &PathBuf) -> Result<Self> {
+    fn try_from(bytes: ByteVec) -> NmdResult<Self> {
         let mut reader = Self {
-            cached_header: None,
-            cursor: Cursor::new(fs::read(path)?),
+            bytes: bytes,
+            header: NmdFileHeader::default(),
+            version: NmdFileVersion::default(),
+            other_versions: vec!(),
         };
 
-        let header = reader.read_header()?;
+        // Where several fit, the latest; SCIV and SCV are written the same
+        if let Some((version, other_versions)) = reader.detect_versions()?.split_last() {
+            reader.version = *version;
+            reader.other_versions = other_versions.to_vec();
+        }
+
+        reader.header = reader.read_header()?;
 
-        // `Ok` only if computed sizes will not panic
-        if header.ordinal() {
-            reader.cached_header = Some(header);
+        // Block sizes would be meaningless otherwise
+        if reader.header.ordinal() {
             Ok(reader)
         } else {
-            Err(Error::new(ErrorKind::Other, "Non-ordinal header"))
+            Err(NmdError::NonOrdinalHeader(reader.header))
         }
     }
 }
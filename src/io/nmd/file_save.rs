@@ -0,0 +1,141 @@
+use {
+    crate::io::nmd::{
+        data::NmdFileData,
+        NmdError,
+        NmdFileReader,
+        NmdResult,
+    },
+    std::{
+        fs::{
+            self,
+            File,
+            OpenOptions,
+        },
+        io::{
+            self,
+            ErrorKind,
+            Seek,
+            SeekFrom,
+            Write,
+        },
+        path::{
+            Path,
+            PathBuf,
+        },
+        process,
+        time::{
+            SystemTime,
+            UNIX_EPOCH,
+        },
+    },
+};
+
+// Removed when dropped, i.e. unless moved into place
+struct NmdFileTempPath(PathBuf);
+
+/// Sink for `NmdFileWriter` that writes beside the target file and only moves
+/// over it once the written file reads back, so a failed save leaves the
+/// target as it was.
+pub struct NmdFileSave {
+    file: File,
+    path: PathBuf,
+    temp_path: NmdFileTempPath,
+    backing_up: bool,
+}
+
+impl NmdFileSave {
+    /// Copy the file being replaced to `<name>.<seconds since 1970>.bak` beside
+    /// it first, or `<name>.<seconds>.<n>.bak` if that's taken.
+    pub fn with_backup(mut self, backing_up: bool) -> Self {
+        self.backing_up = backing_up;
+        self
+    }
+
+    fn sibling_path(path: &Path, name: impl FnOnce(&str) -> String) -> io::Result<PathBuf> {
+        match path.file_name() {
+            Some(file_name) => Ok(path.with_file_name(name(&file_name.to_string_lossy()))),
+            None => Err(io::Error::new(ErrorKind::InvalidInput, format!("{} doesn't name a file", path.display()))),
+        }
+    }
+
+    // Never over an earlier backup, e.g. one from a save the same second
+    fn backup(path: &PathBuf) -> NmdResult<PathBuf> {
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let mut counter = 0;
+
+        loop {
+            let backup_path = Self::sibling_path(path, |name| match counter {
+                0 => format!("{}.{}.bak", name, seconds),
+                _ => format!("{}.{}.{}.bak", name, seconds, counter),
+            })?;
+
+            match OpenOptions::new().write(true).create_new(true).open(&backup_path) {
+                Ok(mut backup) => {
+                    io::copy(&mut File::open(path)?, &mut backup)?;
+                    return Ok(backup_path);
+                }
+                Err(error) if error.kind() == ErrorKind::AlreadyExists => counter += 1,
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+
+    /// Read the written file back, then back up the file it replaces (if asked
+    /// and there is one) and move it into place. Gives the backup's path.
+    pub fn commit(self) -> NmdResult<Option<PathBuf>> {
+        let Self { file, path, temp_path, backing_up } = self;
+
+        file.sync_all()?;
+        drop(file);
+
+        NmdFileReader::try_from(&temp_path.0)
+            .and_then(|mut reader| NmdFileData::try_from(&mut reader))?;
+
+        let backup_path_opt = match backing_up && path.exists() {
+            true => Some(Self::backup(&path)?),
+            false => None,
+        };
+
+        fs::rename(&temp_path.0, &path)?;
+
+        Ok(backup_path_opt)
+    }
+}
+
+impl Write for NmdFileSave {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.file.write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Seek for NmdFileSave {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        self.file.seek(position)
+    }
+}
+
+impl TryFrom<&PathBuf> for NmdFileSave {
+    type Error = NmdError;
+
+    fn try_from(path: &PathBuf) -> NmdResult<Self> {
+        let temp_path = Self::sibling_path(path, |name| format!(".{}.{}.tmp", name, process::id()))?;
+
+        Ok(Self {
+            file: File::create(&temp_path)?,
+            path: path.to_owned(),
+            temp_path: NmdFileTempPath(temp_path),
+            backing_up: false,
+        })
+    }
+}
+
+impl Drop for NmdFileTempPath {
+    fn drop(&mut self) {
+        fs::remove_file(&self.0).ok();
+    }
+}
@@ -0,0 +1,363 @@
+use {
+    crate::io::nmd::{
+        anatomy::{
+            token::NmdFileToken,
+            NmdFileAddress,
+            NmdFileBone,
+            NmdFileBoneFlag,
+            NmdFileByteOrder,
+            NmdFileVersion,
+        },
+        data::NmdFileData,
+    },
+    std::{
+        collections::{
+            HashMap,
+            HashSet,
+        },
+        fmt::{
+            self,
+            Display,
+            Formatter,
+        },
+    },
+};
+
+/// Something wrong (or suspect) in a file, and where.
+#[derive(Debug, Clone)]
+pub struct NmdFileFinding {
+    // Byte offset of the field at fault, if the data came from a file
+    pub offset: Option<u64>,
+    pub kind: NmdFileFindingKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum NmdFileFindingKind {
+    // Game read as, other games whose layout fits the header as well
+    AmbiguousVersion(NmdFileVersion, Vec<NmdFileVersion>),
+    // Header addresses out of order, so block sizes can't be worked out
+    HeaderOutOfOrder,
+    // Bone count, then the echo of it at the end of the header
+    BoneCountMismatch(u16, u16),
+    // Bone ID, name address, bounds of the name block
+    NameAddressOutOfBounds(u16, NmdFileAddress, (NmdFileAddress, NmdFileAddress)),
+    // Bone ID, physics address, bounds of the physics block
+    PhysicsAddressOutOfBounds(u16, NmdFileAddress, (NmdFileAddress, NmdFileAddress)),
+    // Bone ID, raw flag byte
+    UnknownFlag(u16, u8),
+    // Bone ID and name, ID of the first bone with the name
+    DuplicateName(u16, String, u16),
+    // Bone ID and name, first character the name block can't hold
+    UnencodableName(u16, String, char),
+    // Bone ID and name
+    DuplicateId(u16, String),
+    // Bone ID, parent ID
+    Orphan(u16, u16),
+    // IDs of the bones in the cycle, in parent order
+    ParentCycle(Vec<u16>),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum NmdFileSeverity {
+    Error,
+    Warning,
+}
+
+/// Checks file data for problems the reader lets through.
+pub struct NmdFileValidator<'a> {
+    data: &'a NmdFileData,
+    // Record index of each bone, going by the records read
+    record_indices: HashMap<u16, usize>,
+}
+
+impl<'a> NmdFileValidator<'a> {
+    fn bone_offset(&self, record_index_opt: Option<usize>, token: NmdFileToken) -> Option<u64> {
+        let record_offset = record_index_opt? as u64 * NmdFileBone::CHUNK_SIZE;
+
+        Some(self.data.header.bone_data_address as u64 + record_offset + token.value_for(self.data.version).offset as u64)
+    }
+
+    /// Every bone with its record index, conflicting records included.
+    fn bones(&self) -> Vec<(Option<usize>, &'a NmdFileBone)> {
+        let mut bones: Vec<_> = self.data.bones
+            .values()
+            .map(|bone_data| (self.record_indices.get(&bone_data.id).copied(), bone_data))
+            .chain(self.data.conflicts.iter().map(|(i, bone_data)| (Some(*i), bone_data)))
+            .collect();
+
+        bones.sort_by_key(|(record_index_opt, bone_data)| (record_index_opt.unwrap_or(usize::MAX), bone_data.id));
+        bones
+    }
+
+    fn check_bone_count(&self, findings: &mut Vec<NmdFileFinding>) {
+        let token_value = NmdFileToken::HeaderBoneCountEcho.value_for(self.data.version);
+        let bytes = match self.data.raw_header().get(token_value.offset..token_value.offset + 2) {
+            Some(bytes) => [bytes[0], bytes[1]],
+            None => return,
+        };
+        let echo = match self.data.version.byte_order() {
+            NmdFileByteOrder::BigEndian     => u16::from_be_bytes(bytes),
+            NmdFileByteOrder::LittleEndian  => u16::from_le_bytes(bytes),
+        };
+
+        if echo != self.data.header.bone_count {
+            findings.push(NmdFileFinding::new(
+                Some(token_value.offset as u64),
+                NmdFileFindingKind::BoneCountMismatch(self.data.header.bone_count, echo)));
+        }
+    }
+
+    fn check_conflicts(&self, findings: &mut Vec<NmdFileFinding>) {
+        for (record_index, bone_data) in &self.data.conflicts {
+            findings.push(NmdFileFinding::new(
+                self.bone_offset(Some(*record_index), NmdFileToken::BoneId),
+                NmdFileFindingKind::DuplicateId(bone_data.id, bone_data.name.to_owned())));
+        }
+    }
+
+    fn check_cycles(&self, findings: &mut Vec<NmdFileFinding>) {
+        let parents: HashMap<u16, u16> = self.data.bones
+            .values()
+            .map(|bone_data| (bone_data.id, bone_data.parent_id))
+            .collect();
+        let mut cleared = HashSet::<u16>::new();
+
+        for id in parents.keys() {
+            let mut path = Vec::<u16>::new();
+            let mut next_id = *id;
+
+            while !cleared.contains(&next_id) {
+                if let Some(start) = path.iter().position(|path_id| *path_id == next_id) {
+                    let mut cycle = path[start..].to_vec();
+                    let min_index = (0..cycle.len()).min_by_key(|i| cycle[*i]).unwrap_or(0);
+
+                    // Same report whichever member the walk began at
+                    cycle.rotate_left(min_index);
+
+                    findings.push(NmdFileFinding::new(
+                        self.bone_offset(self.record_indices.get(&cycle[0]).copied(), NmdFileToken::BoneParentId),
+                        NmdFileFindingKind::ParentCycle(cycle)));
+                    break;
+                }
+
+                match parents.get(&next_id) {
+                    Some(parent_id) => {
+                        path.push(next_id);
+                        next_id = *parent_id;
+                    }
+                    None => break,
+                }
+            }
+
+            cleared.extend(path);
+        }
+    }
+
+    fn check_flags(&self, findings: &mut Vec<NmdFileFinding>) {
+        for (record_index_opt, bone_data) in self.bones() {
+            if let NmdFileBoneFlag::Unknown(byte) = bone_data.flag {
+                findings.push(NmdFileFinding::new(
+                    self.bone_offset(record_index_opt, NmdFileToken::BoneFlag),
+                    NmdFileFindingKind::UnknownFlag(bone_data.id, byte)));
+            }
+        }
+    }
+
+    fn check_header(&self, findings: &mut Vec<NmdFileFinding>) {
+        if !self.data.other_versions.is_empty() {
+            findings.push(NmdFileFinding::new(
+                None,
+                NmdFileFindingKind::AmbiguousVersion(self.data.version, self.data.other_versions.to_owned())));
+        }
+
+        if !self.data.header.ordinal() {
+            findings.push(NmdFileFinding::new(
+                Some(NmdFileToken::HeaderBlobDataAddress.value_for(self.data.version).offset as u64),
+                NmdFileFindingKind::HeaderOutOfOrder));
+        }
+    }
+
+    fn check_names(&self, findings: &mut Vec<NmdFileFinding>) {
+        let mut first_ids = HashMap::<&str, u16>::new();
+
+        for (record_index_opt, bone_data) in self.bones() {
+            if let Err(character) = NmdFileBone::encode_name(&bone_data.name) {
+                findings.push(NmdFileFinding::new(
+                    self.bone_offset(record_index_opt, NmdFileToken::BoneNameAddress),
+                    NmdFileFindingKind::UnencodableName(bone_data.id, bone_data.name.to_owned(), character)));
+            }
+
+            match first_ids.get(bone_data.name.as_str()) {
+                Some(first_id) => findings.push(NmdFileFinding::new(
+                    self.bone_offset(record_index_opt, NmdFileToken::BoneNameAddress),
+                    NmdFileFindingKind::DuplicateName(bone_data.id, bone_data.name.to_owned(), *first_id))),
+                None => { first_ids.insert(&bone_data.name, bone_data.id); }
+            }
+        }
+    }
+
+    fn check_name_addresses(&self, findings: &mut Vec<NmdFileFinding>) {
+        let name_start = self.data.header.bone_name_data_address;
+        let name_end = name_start.saturating_add(self.data.raw_bone_name_data().len() as NmdFileAddress);
+
+        for (record_index, record) in self.data.records().iter().enumerate() {
+            if !(name_start..name_end).contains(&record.name_address) {
+                findings.push(NmdFileFinding::new(
+                    self.bone_offset(Some(record_index), NmdFileToken::BoneNameAddress),
+                    NmdFileFindingKind::NameAddressOutOfBounds(record.id, record.name_address, (name_start, name_end))));
+            }
+        }
+    }
+
+    fn check_orphans(&self, findings: &mut Vec<NmdFileFinding>) {
+        for bone_data in self.data.orphans() {
+            findings.push(NmdFileFinding::new(
+                self.bone_offset(self.record_indices.get(&bone_data.id).copied(), NmdFileToken::BoneParentId),
+                NmdFileFindingKind::Orphan(bone_data.id, bone_data.parent_id)));
+        }
+    }
+
+    fn check_physics_addresses(&self, findings: &mut Vec<NmdFileFinding>) {
+        let physics_start = match self.data.header.physics_data_address_opt() {
+            Some(physics_start) => physics_start,
+            None => return,
+        };
+        let physics_end = self.data.header.blob_data_address;
+
+        for (record_index_opt, bone_data) in self.bones() {
+            if bone_data.is_phys() && !(physics_start..physics_end).contains(&bone_data.physics_data_address) {
+                findings.push(NmdFileFinding::new(
+                    self.bone_offset(record_index_opt, NmdFileToken::BonePhysicsDataAddress),
+                    NmdFileFindingKind::PhysicsAddressOutOfBounds(bone_data.id, bone_data.physics_data_address, (physics_start, physics_end))));
+            }
+        }
+    }
+
+    /// Run every check, returning findings in offset order (findings with no
+    /// offset last).
+    pub fn validate(&self) -> Vec<NmdFileFinding> {
+        let mut findings = vec!();
+
+        self.check_header(&mut findings);
+        self.check_bone_count(&mut findings);
+        self.check_name_addresses(&mut findings);
+        self.check_physics_addresses(&mut findings);
+        self.check_flags(&mut findings);
+        self.check_conflicts(&mut findings);
+        self.check_names(&mut findings);
+        self.check_orphans(&mut findings);
+        self.check_cycles(&mut findings);
+
+        findings.sort_by_key(|finding| finding.offset.unwrap_or(u64::MAX));
+        findings
+    }
+}
+
+impl<'a> From<&'a NmdFileData> for NmdFileValidator<'a> {
+    fn from(data: &'a NmdFileData) -> Self {
+        let mut record_indices = HashMap::new();
+
+        for (i, record) in data.records().iter().enumerate() {
+            if !data.conflicts.contains_key(&i) {
+                record_indices.entry(record.id).or_insert(i);
+            }
+        }
+
+        Self {
+            data: data,
+            record_indices: record_indices,
+        }
+    }
+}
+
+impl NmdFileFinding {
+    fn new(offset: Option<u64>, kind: NmdFileFindingKind) -> Self {
+        Self {
+            offset: offset,
+            kind: kind,
+        }
+    }
+
+    /// ID of the bone the finding is about, if it's about one.
+    pub fn bone_id(&self) -> Option<u16> {
+        use NmdFileFindingKind::*;
+
+        match &self.kind {
+            NameAddressOutOfBounds(id, ..)
+             | PhysicsAddressOutOfBounds(id, ..)
+             | UnknownFlag(id, _)
+             | DuplicateName(id, ..)
+             | UnencodableName(id, ..)
+             | DuplicateId(id, _)
+             | Orphan(id, _)            => Some(*id),
+            ParentCycle(ids)            => ids.first().copied(),
+            _                           => None,
+        }
+    }
+
+    pub fn severity(&self) -> NmdFileSeverity {
+        use NmdFileFindingKind::*;
+
+        match self.kind {
+            AmbiguousVersion(..)
+             | UnknownFlag(..)
+             | DuplicateName(..)
+             | DuplicateId(..)
+             | Orphan(..)           => NmdFileSeverity::Warning,
+            _                       => NmdFileSeverity::Error,
+        }
+    }
+}
+
+impl Display for NmdFileFinding {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self.offset {
+            Some(offset) => write!(formatter, "{:#010X}: ", offset)?,
+            None => write!(formatter, "{:>10}  ", "-")?,
+        }
+
+        write!(formatter, "{}: {}", self.severity(), self.kind)
+    }
+}
+
+impl Display for NmdFileFindingKind {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        use NmdFileFindingKind::*;
+
+        match self {
+            AmbiguousVersion(version, other_versions)
+                => write!(formatter, "header fits {} as well as {}, which can't be told apart; read as {}",
+                    other_versions.iter().map(ToString::to_string).collect::<Vec<_>>().join(" and "), version, version),
+            HeaderOutOfOrder
+                => write!(formatter, "header addresses are out of order"),
+            BoneCountMismatch(count, echo)
+                => write!(formatter, "header bone count {} doesn't match its echo {}", count, echo),
+            NameAddressOutOfBounds(id, address, (start, end))
+                => write!(formatter, "bone {:#04X} has name address {:#010X}, outside the name block ({:#010X}..{:#010X})", id, address, start, end),
+            PhysicsAddressOutOfBounds(id, address, (start, end))
+                => write!(formatter, "physics bone {:#04X} has physics address {:#010X}, outside the physics block ({:#010X}..{:#010X})", id, address, start, end),
+            UnknownFlag(id, byte)
+                => write!(formatter, "bone {:#04X} has unknown flag {:#04X}", id, byte),
+            DuplicateName(id, name, first_id)
+                => write!(formatter, "bone {:#04X} is named {}, like bone {:#04X}", id, name, first_id),
+            UnencodableName(id, name, character)
+                => write!(formatter, "bone {:#04X} is named {:?}, but {:?} can't be written (names only hold U+0001 to U+00BF)", id, name, character),
+            DuplicateId(id, name)
+                => write!(formatter, "bone {} has ID {:#04X}, which an earlier record already has", name, id),
+            Orphan(id, parent_id)
+                => write!(formatter, "bone {:#04X} has parent ID {:#04X}, which matches no bone", id, parent_id),
+            ParentCycle(ids)
+                => write!(formatter, "parent links form a cycle: {}",
+                    ids.iter().map(|id| format!("{:#04X}", id)).collect::<Vec<_>>().join(" → ")),
+        }
+    }
+}
+
+impl Display for NmdFileSeverity {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}", match self {
+            Self::Error     => "error",
+            Self::Warning   => "warning",
+        })
+    }
+}
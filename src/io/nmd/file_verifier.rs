@@ -0,0 +1,161 @@
+use {
+    crate::io::nmd::{
+        anatomy::{
+            token::NmdFileToken,
+            NmdFileBone,
+            NmdFileHeader,
+        },
+        data::{
+            ByteArr,
+            ByteVec,
+            NmdFileData,
+        },
+        NmdError,
+        NmdFileReader,
+        NmdFileWriter,
+        NmdResult,
+    },
+    std::{
+        fmt::{
+            self,
+            Display,
+            Formatter,
+        },
+        fs,
+        io::Cursor,
+        path::PathBuf,
+    },
+};
+
+/// First byte at which a written file differs from the file it was read from.
+#[derive(Debug)]
+pub struct NmdFileDivergence {
+    pub offset: u64,
+    pub location: NmdFileLocation,
+    // `None` past the end of the file
+    pub expected: Option<u8>,
+    pub found: Option<u8>,
+}
+
+/// What a byte offset falls on, going by the layout of the original file.
+#[derive(Debug)]
+pub enum NmdFileLocation {
+    Header(Option<NmdFileToken>),
+    // Record index, ID read from the original record, token
+    Bone(usize, u16, NmdFileToken),
+    PhysicsData,
+    BlobData,
+    BoneNameData,
+    Unmapped,
+}
+
+/// Checks that reading a file and writing it back without edits reproduces
+/// it byte for byte.
+pub struct NmdFileVerifier {
+    bytes: ByteVec,
+    data: NmdFileData,
+}
+
+impl NmdFileVerifier {
+    fn divergence(data: &NmdFileData, original: ByteArr, written: ByteArr) -> Option<NmdFileDivergence> {
+        let length = original.len().max(written.len());
+        let offset = (0..length).find(|&i| original.get(i) != written.get(i))?;
+
+        Some(NmdFileDivergence {
+            offset: offset as u64,
+            location: Self::location(data, original, offset),
+            expected: original.get(offset).copied(),
+            found: written.get(offset).copied(),
+        })
+    }
+
+    fn location(data: &NmdFileData, original: ByteArr, offset: usize) -> NmdFileLocation {
+        let header = &data.header;
+        let header_end = NmdFileHeader::CHUNK_SIZE as usize;
+        let bone_end = header_end + header.bone_data_length();
+        let physics_start = header.physics_data_address() as usize;
+        let blob_start = header.blob_data_address as usize;
+        let name_start = header.bone_name_data_address as usize;
+
+        if offset < header_end {
+            NmdFileLocation::Header(NmdFileToken::header_token_at(offset))
+        } else if offset < bone_end {
+            let index = (offset - header_end) / NmdFileBone::CHUNK_SIZE as usize;
+            let bone_start = header_end + index * NmdFileBone::CHUNK_SIZE as usize;
+            let id_opt = NmdFileToken::BoneId.decode(original, bone_start, data.version);
+
+            match (id_opt, NmdFileToken::bone_token_at(offset - bone_start)) {
+                (Some(id), Some(token)) => NmdFileLocation::Bone(index, id, token),
+                _ => NmdFileLocation::Unmapped,
+            }
+        } else if offset >= name_start {
+            NmdFileLocation::BoneNameData
+        } else if offset >= blob_start {
+            NmdFileLocation::BlobData
+        } else if offset >= physics_start {
+            NmdFileLocation::PhysicsData
+        } else {
+            NmdFileLocation::Unmapped
+        }
+    }
+
+    /// Write the data back out unchanged (in memory) and compare it to the
+    /// original bytes.
+    pub fn verify(&self) -> NmdResult<Option<NmdFileDivergence>> {
+        let written = NmdFileWriter::new(Cursor::new(ByteVec::new()))
+            .write_new(&self.data, &self.data.bones, &self.data.conflicts, &self.data.physics, &self.data.conflict_physics)?
+            .into_inner();
+
+        Ok(Self::divergence(&self.data, &self.bytes, &written))
+    }
+}
+
+impl TryFrom<&PathBuf> for NmdFileVerifier {
+    type Error = NmdError;
+
+    fn try_from(path: &PathBuf) -> NmdResult<Self> {
+        Self::try_from(fs::read(path)?)
+    }
+}
+
+impl TryFrom<ByteVec> for NmdFileVerifier {
+    type Error = NmdError;
+
+    fn try_from(bytes: ByteVec) -> NmdResult<Self> {
+        let mut reader = NmdFileReader::try_from(bytes.to_vec())?;
+
+        Ok(Self {
+            data: NmdFileData::try_from(&mut reader)?,
+            bytes: bytes,
+        })
+    }
+}
+
+impl Display for NmdFileDivergence {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let byte_text = |byte: Option<u8>| match byte {
+            Some(byte) => format!("{:#04X}", byte),
+            None => String::from("end of file"),
+        };
+
+        write!(formatter, "{:#010X}: {}: expected {}, found {}",
+            self.offset,
+            self.location,
+            byte_text(self.expected),
+            byte_text(self.found))
+    }
+}
+
+impl Display for NmdFileLocation {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Header(Some(token))       => write!(formatter, "header, {:?}", token),
+            Self::Header(None)              => write!(formatter, "header, untokenized byte"),
+            Self::Bone(index, id, token)    => write!(formatter, "bone record {} (ID {:#04X}), {:?}", index, id, token),
+            Self::PhysicsData               => write!(formatter, "physics data"),
+            Self::BlobData                  => write!(formatter, "blob data"),
+            Self::BoneNameData              => write!(formatter, "bone name data"),
+            Self::Unmapped                  => write!(formatter, "outside any known block"),
+        }
+    }
+}
@@ -3,211 +3,559 @@ use {
         anatomy::{
             token::*,
             NmdFileAddress,
+            NmdFileBlobSegment,
             NmdFileBone,
             NmdFileBoneFlag,
             NmdFileHeader,
+            NmdFilePhysics,
+            NmdFileVersion,
         },
-        data::NmdFileData,
+        data::{
+            ByteArr,
+            ByteVec,
+            NmdFileData,
+        },
+        NmdError,
+        NmdResult,
     },
     std::{
-        collections::BTreeMap,
+        collections::{
+            BTreeMap,
+            HashMap,
+            HashSet,
+            VecDeque,
+        },
         io::{
-            self,
-            Error,
-            ErrorKind,
-            Read,
-            Result,
             Seek,
             SeekFrom,
             Write,
         },
-        fs::File,
-        path::PathBuf,
-        mem,
     },
 };
 
-/*
- * TODO:
- * ~ Test writing an outrageous number of bones. Error handling not robust
- */
-
+// Shift an address or count by a signed delta, as `$type`, failing with
+// `AddressOutOfBounds` for the token at `$offset` if it no longer fits
 macro_rules! delta {
-    ($value:expr, $type:ty, $delta:expr) => {
-        (($value as isize) + $delta) as $type
+    ($value:expr, $type:ty, $delta:expr, $offset:expr, $token:expr, $bone_id:expr) => {
+        isize::try_from($value).ok()
+            .and_then(|value| value.checked_add($delta))
+            .and_then(|value| <$type>::try_from(value).ok())
+            .ok_or(NmdError::AddressOutOfBounds {
+                offset: $offset,
+                token: $token,
+                bone_id: $bone_id,
+                address: (($value as i128) + ($delta as i128)) as u64,
+            })
     }
 }
 
-macro_rules! write {
-    ($writer:ident, $data:expr, $type:ty) => {
-        $writer.write::<$type, { mem::size_of::<$type>() }>(&$data)
-    };
-
-    ($writer:ident, $data:expr, $type:ty, 'little_endian) => {
-        $writer.write::<$type, { mem::size_of::<$type>() }>(&$data.to_le())
-    };
+type BoneIterable<'a> = Vec<&'a NmdFileBone>;
 
-    ($writer:ident, $data:expr, $type:ty, 'little_endian_f32) => {
-        // Could also use `to_le_bytes()` but have to modify caller
-        $writer.write::<$type, { mem::size_of::<$type>() }>(&$data.to_bits().to_le())
-    };
+/// Order bone records (and their names) are written in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NmdFileBoneOrder {
+    // As read, with new bones after in ID order, so an unedited file comes out
+    // the same
+    #[default]
+    Original,
+    ById,
 }
 
-macro_rules! write_at {
-    ($writer:ident, $data:expr, $type:ty, $token:path $(, $endian_switch:tt)?) => {
-        {
-            $writer.seek_token($token)?;
+// Where each physics record goes in the written physics block
+#[derive(Default)]
+struct PhysicsLayout<'a> {
+    // Records written back over the original block, by offset into it
+    kept: Vec<(usize, &'a NmdFilePhysics)>,
+    // Records appended to the block, e.g. for pasted bones
+    fresh: Vec<&'a NmdFilePhysics>,
+    // Offset into the block of each appended record, by position of its bone
+    // in the order written
+    fresh_offsets: HashMap<usize, usize>,
+    // Bytes appended to the block
+    growth: usize,
+}
 
-            write!($writer, $data, $type $(, $endian_switch)?)
-        }
+impl PhysicsLayout<'_> {
+    // Whether `length` bytes at `offset` would run into a kept record
+    fn overlaps(&self, offset: usize, length: usize) -> bool {
+        self.kept.iter()
+            .any(|(kept_offset, kept)| offset < kept_offset + kept.len() && *kept_offset < offset + length)
     }
 }
 
-type BoneIterable<'a> = Vec<&'a NmdFileBone>;
-
-pub struct NmdFileWriter {
-    file: File,
+/// Writes NMD data into any seekable sink, e.g. an `NmdFileSave` to save a
+/// file, or a `Cursor` to export in memory.
+pub struct NmdFileWriter<W: Write + Seek> {
+    sink: W,
+    // Taken from the data being written unless given
+    raw_header_opt: Option<ByteVec>,
+    // Taken from the data being written unless given
+    version_opt: Option<NmdFileVersion>,
+    bone_order: NmdFileBoneOrder,
 }
 
-impl NmdFileWriter {
-    fn bones_sorted<'a>(new_bones: &'a BTreeMap<u16, NmdFileBone>) -> Vec<&'a NmdFileBone> {
-        let mut bones: Vec<_> = new_bones.values().collect();
+impl<W: Write + Seek> NmdFileWriter<W> {
+    pub fn new(sink: W) -> Self {
+        Self {
+            sink: sink,
+            raw_header_opt: None,
+            version_opt: None,
+            bone_order: NmdFileBoneOrder::default(),
+        }
+    }
+
+    // Bones that were read keep their record order unless sorting by ID; new
+    // bones follow in ID order. Conflicting records go back where they were
+    // read from, or after the bone they share an ID with, and come with their
+    // record index
+    fn bones_ordered<'a>(&self, data: &NmdFileData, new_bones: &'a BTreeMap<u16, NmdFileBone>, conflicts: &'a BTreeMap<usize, NmdFileBone>) -> Vec<(&'a NmdFileBone, Option<usize>)> {
+        let mut written = HashSet::<u16>::new();
+        let mut bones: Vec<_> = data.records().iter()
+            .enumerate()
+            .filter_map(|(i, record)| match conflicts.get(&i) {
+                Some(conflict) => Some((conflict, Some(i))),
+                None => new_bones.get(&record.id)
+                    .filter(|bone_data| written.insert(bone_data.id))
+                    .map(|bone_data| (bone_data, None)),
+            })
+            .collect();
+
+        bones.extend(new_bones.values().filter(|bone_data| written.insert(bone_data.id)).map(|bone_data| (bone_data, None)));
+        bones.extend(conflicts.range(data.records().len()..).map(|(i, conflict)| (conflict, Some(*i))));
+
+        if self.bone_order == NmdFileBoneOrder::ById {
+            bones.sort_by_key(|(bone_data, _)| bone_data.id);
+        }
 
-        bones.sort_by_key(|bone_data| bone_data.id);
         bones
     }
 
-    fn error(error_message: &str) -> Result<()> {
-        Err(Error::new(ErrorKind::Other, error_message))
+    // Checked before anything is written
+    fn encode_bone_names(bone_data: &Vec<&NmdFileBone>) -> NmdResult<Vec<ByteVec>> {
+        bone_data.iter()
+            .map(|bone_data| NmdFileBone::encode_name(&bone_data.name)
+                .map_err(|character| NmdError::UnencodableName {
+                    bone_id: bone_data.id,
+                    name: bone_data.name.to_owned(),
+                    character: character,
+                }))
+            .collect()
+    }
+
+    // Bone-indexed segments follow the bones being written
+    fn blob_length(data: &NmdFileData, bone_count: usize) -> usize {
+        if data.blob.is_empty() {
+            return data.raw_blob().len();
+        }
+
+        data.blob.segments.iter()
+            .map(|segment| match segment {
+                NmdFileBlobSegment::BoneIdTable(_) => bone_count * NmdFileBlobSegment::TABLE_ENTRY_SIZE,
+                _ => segment.len(),
+            })
+            .sum()
+    }
+
+    // Physics and blob bytes outside every decoded structure are copied as
+    // they were read, so can't be written in another byte order
+    fn check_byte_order(&self, data: &NmdFileData) -> NmdResult<()> {
+        if data.export_versions().contains(&self.version()) {
+            Ok(())
+        } else {
+            Err(NmdError::ByteOrderConversion(data.version, self.version()))
+        }
     }
 
-    fn file_delta(data: &NmdFileData, new_bones: &BTreeMap<u16, NmdFileBone>) -> (isize, isize) {
-        let bone_delta = new_bones.len() as isize - data.header.bone_count as isize;
+    fn file_delta(data: &NmdFileData, bone_count: usize) -> (isize, isize) {
+        let bone_delta = bone_count as isize - data.header.bone_count as isize;
 
         (bone_delta, (NmdFileBone::CHUNK_SIZE as isize) * bone_delta)
     }
 
+    /// Write bone records (and the name table) in this order.
+    pub fn with_bone_order(mut self, bone_order: NmdFileBoneOrder) -> Self {
+        self.bone_order = bone_order;
+        self
+    }
+
+    /// Target a game other than the one the data was read as.
+    pub fn for_version(mut self, version: NmdFileVersion) -> Self {
+        self.version_opt = Some(version);
+        self
+    }
+
+    /// Copy these header bytes in place of the ones read, e.g. with untokenized
+    /// bytes edited. Tokenized fields are still written from the data.
+    pub fn with_raw_header(mut self, bytes: ByteArr) -> Self {
+        self.raw_header_opt = Some(bytes.to_vec());
+        self
+    }
+
     fn nth_bone_address(n: usize) -> u64 {
         NmdFileHeader::CHUNK_SIZE + ((n as u64) * NmdFileBone::CHUNK_SIZE)
     }
 
-    fn physics_data_address(bone_data: &NmdFileBone, byte_delta: isize) -> NmdFileAddress {
-        if bone_data.is_phys() {
-            delta!(bone_data.physics_data_address, NmdFileAddress, byte_delta)
+    // A physics bone gets a fresh record if its address doesn't start a record
+    // of the original block, or if another bone has taken that record and the
+    // two didn't share it when read (as a pasted copy doesn't). Records are
+    // looked up per bone record, so bones sharing an ID keep their own
+    fn physics_layout<'a>(data: &'a NmdFileData, bones: &[(&NmdFileBone, Option<usize>)], physics: &'a BTreeMap<u16, NmdFilePhysics>, conflict_physics: &'a BTreeMap<usize, NmdFilePhysics>) -> NmdResult<PhysicsLayout<'a>> {
+        let block_address = data.header.physics_data_address() as usize;
+        let block_length = data.raw_physics_data().len();
+        let mut claimed = HashMap::<usize, (u16, &NmdFilePhysics)>::new();
+        let mut layout = PhysicsLayout::default();
+        let mut entries = vec!();
+
+        for (i, (bone_data, record_index_opt)) in bones.iter().enumerate().filter(|(_, (bone_data, _))| bone_data.is_phys()) {
+            let (physics_opt, read_opt) = match record_index_opt {
+                Some(record_index) => (conflict_physics.get(record_index), data.conflicts.get(record_index)),
+                None => (physics.get(&bone_data.id), data.get(bone_data.id)),
+            };
+            let read_here = read_opt
+                .is_some_and(|read| read.physics_data_address == bone_data.physics_data_address);
+
+            if let Some(physics) = physics_opt {
+                entries.push((i, bone_data, physics, read_here));
+            }
+        }
+
+        // Bones still pointing where they were read claim their records first,
+        // so a copy never takes the record of the bone it was copied from
+        entries.sort_by_key(|(_, _, _, read_here)| !read_here);
+
+        for (i, bone_data, physics, read_here) in entries {
+            let offset_opt = (bone_data.physics_data_address as usize)
+                .checked_sub(block_address)
+                .filter(|offset| offset + physics.len() <= block_length);
+
+            match offset_opt.map(|offset| (offset, claimed.get(&offset).copied())) {
+                // A record edited to overlap one already placed gets a fresh
+                // one instead, like any that doesn't start where one did
+                Some((offset, None)) if !layout.overlaps(offset, physics.len()) => {
+                    claimed.insert(offset, (bone_data.id, physics));
+                    layout.kept.push((offset, physics));
+                }
+                // Bones that shared a record when read still share it, which
+                // only works while they agree on what's in it
+                Some((_, Some((other_id, other)))) if read_here => {
+                    if !physics.same_as(other) {
+                        return Err(NmdError::SharedPhysicsRecord {
+                            address: bone_data.physics_data_address as u64,
+                            bone_ids: (other_id, bone_data.id),
+                        });
+                    }
+                }
+                _ => {
+                    layout.fresh_offsets.insert(i, block_length + layout.growth);
+                    layout.fresh.push(physics);
+                    layout.growth += physics.len();
+                }
+            }
+        }
+
+        // Records no bone written points to any more stay where they were, so
+        // that they're written in the target's byte order like the rest
+        let read_records = data.bones.values()
+            .filter_map(|bone_data| data.physics.get(&bone_data.id).map(|physics| (bone_data, physics)))
+            .chain(data.conflicts.iter()
+                .filter_map(|(record_index, bone_data)| data.conflict_physics.get(record_index).map(|physics| (bone_data, physics))));
+
+        for (bone_data, physics) in read_records {
+            let offset_opt = (bone_data.physics_data_address as usize)
+                .checked_sub(block_address)
+                .filter(|offset| offset + physics.len() <= block_length);
+
+            if let Some(offset) = offset_opt.filter(|offset| !layout.overlaps(*offset, physics.len())) {
+                layout.kept.push((offset, physics));
+            }
+        }
+
+        layout.kept.sort_by_key(|(offset, _)| *offset);
+        Ok(layout)
+    }
+
+    fn physics_data_address(&self, i: usize, bone_data: &NmdFileBone, byte_delta: isize, new_block_address: usize, layout: &PhysicsLayout) -> NmdResult<NmdFileAddress> {
+        let token = NmdFileToken::BonePhysicsDataAddress;
+        let offset = self.field_offset(Self::nth_bone_address(i), token);
+
+        if let Some(fresh_offset) = layout.fresh_offsets.get(&i) {
+            delta!(new_block_address, NmdFileAddress, *fresh_offset as isize, offset, token, Some(bone_data.id))
+        } else if bone_data.is_phys() {
+            delta!(bone_data.physics_data_address, NmdFileAddress, byte_delta, offset, token, Some(bone_data.id))
         } else {
             // We don't offset, just preserve the original data - which might be
             // garbage, or might mean something else
-            bone_data.physics_data_address
+            Ok(bone_data.physics_data_address)
         }
     }
 
-    fn seek(&mut self, address: u64) -> Result<u64> {
-        self.file.seek(SeekFrom::Start(address))
+    // Where `token` is written in the chunk at `address`
+    fn field_offset(&self, address: u64, token: NmdFileToken) -> u64 {
+        token.range_for(self.version(), address as usize).start as u64
     }
 
-    fn seek_token(&mut self, token: NmdFileToken) -> Result<u64> {
-        let NmdFileTokenValue {
-            is_relative,
-            offset,
-            ..
-        } = token.value();
-
-        self.file.seek(match is_relative {
-            true  => SeekFrom::Current(offset as i64),
-            false => SeekFrom::Start(offset as u64),
-        })
+    fn seek(&mut self, address: u64) -> NmdResult<u64> {
+        Ok(self.sink.seek(SeekFrom::Start(address))?)
     }
 
-    fn write<T, const T_SIZE: usize>(&mut self, data: &T) -> Result<()> {
-        unsafe {
-            let bytes: &mut [u8; T_SIZE] = mem::transmute_copy(&data);
+    fn version(&self) -> NmdFileVersion {
+        self.version_opt.unwrap_or_default()
+    }
 
-            self.file.write(bytes)?;
-        }
+    fn write_value<T: NmdFileTokenCodec>(&mut self, value: &T) -> NmdResult<()> {
+        self.sink.write_all(&value.encode(self.version().byte_order()))?;
 
         Ok(())
     }
 
-    pub fn write_new(mut self, data: &NmdFileData, new_bones: &BTreeMap<u16, NmdFileBone>) -> Result<()> {
-        let bone_data_sorted = Self::bones_sorted(new_bones);
-        let file_delta @ (_, byte_delta) = Self::file_delta(data, new_bones);
+    // Encode a token's value into a buffer for the block that starts at
+    // `address` in the file
+    fn encode_token<T: NmdFileTokenCodec>(&self, bytes: &mut [u8], address: u64, token: NmdFileToken, value: T) -> NmdResult<()> {
+        token.encode(&value, bytes, 0, self.version())
+            .ok_or_else(|| NmdError::UnexpectedLength {
+                offset: address + token.range_for(self.version(), 0).start as u64,
+                expected: token.value_for(self.version()).format.size() as u64,
+                found: T::SIZE as u64,
+            })
+    }
+
+    /// Write `new_bones` and their `physics` records over the file `data` was
+    /// read from, along with any conflicting records that are still
+    /// unresolved and theirs (`conflict_physics`, by record index). Gives back
+    /// the sink, flushed.
+    pub fn write_new(mut self, data: &NmdFileData, new_bones: &BTreeMap<u16, NmdFileBone>, conflicts: &BTreeMap<usize, NmdFileBone>, physics: &BTreeMap<u16, NmdFilePhysics>, conflict_physics: &BTreeMap<usize, NmdFilePhysics>) -> NmdResult<W> {
+        let bones_ordered = self.bones_ordered(data, new_bones, conflicts);
+        let bone_data_ordered: Vec<&NmdFileBone> = bones_ordered.iter().map(|(bone_data, _)| *bone_data).collect();
+        let names = Self::encode_bone_names(&bone_data_ordered)?;
+        let (bone_delta, byte_delta) = Self::file_delta(data, bone_data_ordered.len());
+        let physics_layout = Self::physics_layout(data, &bones_ordered, physics, conflict_physics)?;
+        // Blob and name data move with both the bone and physics blocks, and
+        // name data with the blob too
+        let blob_delta = byte_delta + physics_layout.growth as isize;
+        let name_delta = blob_delta + Self::blob_length(data, bone_data_ordered.len()) as isize - data.raw_blob().len() as isize;
+
+        self.version_opt.get_or_insert(data.version);
+        self.check_byte_order(data)?;
+
+        // Header bytes that no token covers are copied as-is
+        let raw_header = self.raw_header_opt.take().unwrap_or_else(|| data.raw_header().to_vec());
+
+        if raw_header.len() as u64 != NmdFileHeader::CHUNK_SIZE {
+            return Err(NmdError::UnexpectedLength {
+                offset: 0,
+                expected: NmdFileHeader::CHUNK_SIZE,
+                found: raw_header.len() as u64,
+            });
+        }
+
+        self.write_header(&raw_header, &data.header, (bone_delta, blob_delta, name_delta))?;
+        self.write_bone_data(data, &bone_data_ordered, byte_delta, &physics_layout)?;
+        self.write_physics_data(data, &physics_layout)?;
+        self.write_blob_data(data, &bone_data_ordered)?;
+
+        // Names are only kept where they were read while bones are too, so
+        // the name table follows the order bones are written in
+        let name_addresses_opt = match self.bone_order {
+            NmdFileBoneOrder::Original => Self::original_bone_name_addresses(data, &bone_data_ordered, &names),
+            NmdFileBoneOrder::ById => None,
+        };
+
+        match name_addresses_opt {
+            Some(name_addresses) => self.write_original_bone_name_data(data, &bone_data_ordered, &name_addresses, name_delta)?,
+            None => self.write_bone_name_data(&bone_data_ordered, &names)?,
+        }
+
+        self.sink.flush()?;
+
+        Ok(self.sink)
+    }
 
-        self.file.write(data.raw_header())?;
-        self.write_header_revisions(&data.header, file_delta)?;
-        self.write_bone_data(&bone_data_sorted, byte_delta)?;
-        self.file.write(data.raw_physics_data())?;
-        self.file.write(data.raw_blob())?;
-        self.write_bone_name_data(&bone_data_sorted)?;
+    fn write_blob_data(&mut self, data: &NmdFileData, bone_data: &Vec<&NmdFileBone>) -> NmdResult<()> {
+        if data.blob.is_empty() {
+            self.sink.write_all(data.raw_blob())?;
+        }
 
-        self.file.sync_all()?;
+        for segment in &data.blob.segments {
+            match segment {
+                NmdFileBlobSegment::Raw(bytes) => self.sink.write_all(bytes)?,
+                NmdFileBlobSegment::BoneIdTable(_) => {
+                    for bone_data in bone_data {
+                        self.write_value(&bone_data.id)?;
+                    }
+                }
+            }
+        }
 
         Ok(())
     }
 
-    fn write_bone_data(&mut self, bone_data: &Vec<&NmdFileBone>, byte_delta: isize) -> Result<()> {
-        let stream_start_position = self.file.stream_position()?;
-        let stream_final_position;
+    fn write_bone_data(&mut self, data: &NmdFileData, bone_data: &Vec<&NmdFileBone>, byte_delta: isize, physics_layout: &PhysicsLayout) -> NmdResult<()> {
+        // The physics block moves with the bone count
+        let new_physics_address = delta!(data.header.physics_data_address(), usize, byte_delta, self.field_offset(0, NmdFileToken::HeaderBoneCount), NmdFileToken::HeaderBoneCount, None)?;
+        let stream_start_position = self.sink.stream_position()?;
 
         let mut i = 0;
-
-        // All numerical values are written as little endian, but `f32` values
-        // are first cast to bits (`u32` value with same bytes).
+        // Each chunk is laid out by the token table, in the target's byte order
         for bone_data in bone_data {
-            write!(self, bone_data.collision_data, [u8; 16])?;
-            write!(self, bone_data.translation_x, u32, 'little_endian_f32)?;
-            write!(self, bone_data.translation_y, u32, 'little_endian_f32)?;
-            write!(self, bone_data.translation_z, u32, 'little_endian_f32)?;
-            write!(self, bone_data.unknown_data_a, [u8; 4])?;
-            write!(self, bone_data.rotation_x, u32, 'little_endian_f32)?;
-            write!(self, bone_data.rotation_y, u32, 'little_endian_f32)?;
-            write!(self, bone_data.rotation_z, u32, 'little_endian_f32)?;
-            write!(self, bone_data.unknown_data_b, [u8; 4])?;
-            write!(self, 0x0, NmdFileAddress)?; // Later becomes name address
-            write!(self, bone_data.unknown_data_c, [u8; 4])?;
-            write!(self, Self::physics_data_address(bone_data, byte_delta), NmdFileAddress, 'little_endian);
-            write!(self, bone_data.unknown_data_d, [u8; 4])?;
-            write!(self, bone_data.translation_x_next, u32, 'little_endian_f32)?;
-            write!(self, bone_data.gravity_x, i16, 'little_endian)?;
-            write!(self, bone_data.gravity_y, i16, 'little_endian)?;
-            write!(self, bone_data.physics_constraint_x_max, i8)?;
-            write!(self, bone_data.physics_constraint_x_min, i8)?;
-            write!(self, bone_data.physics_constraint_y_max, i8)?;
-            write!(self, bone_data.physics_constraint_y_min, i8)?;
-            write!(self, bone_data.unknown_data_e, [u8; 19])?;
-            write!(self, u8::from(bone_data.flag), u8)?;
-            write!(self, bone_data.parent_id, u16, 'little_endian)?;
-            write!(self, bone_data.id, u16, 'little_endian)?;
-            write!(self, bone_data.unknown_data_f, [u8; 12])?;
+            use NmdFileToken::*;
+
+            let address = Self::nth_bone_address(i as usize);
+            let physics_data_address = self.physics_data_address(i as usize, bone_data, byte_delta, new_physics_address, physics_layout)?;
+            let mut chunk = vec![0u8; NmdFileBone::CHUNK_SIZE as usize];
+
+            self.encode_token(&mut chunk, address, BoneCollisionData, bone_data.collision_data)?;
+            self.encode_token(&mut chunk, address, BoneTranslationX, bone_data.translation_x)?;
+            self.encode_token(&mut chunk, address, BoneTranslationY, bone_data.translation_y)?;
+            self.encode_token(&mut chunk, address, BoneTranslationZ, bone_data.translation_z)?;
+            self.encode_token(&mut chunk, address, BoneUnknownDataA, bone_data.unknown_data_a)?;
+            self.encode_token(&mut chunk, address, BoneRotationX, bone_data.rotation_x)?;
+            self.encode_token(&mut chunk, address, BoneRotationY, bone_data.rotation_y)?;
+            self.encode_token(&mut chunk, address, BoneRotationZ, bone_data.rotation_z)?;
+            self.encode_token(&mut chunk, address, BoneUnknownDataB, bone_data.unknown_data_b)?;
+            // The name address is filled in once names are written
+            self.encode_token(&mut chunk, address, BoneUnknownDataC, bone_data.unknown_data_c)?;
+            self.encode_token(&mut chunk, address, BonePhysicsDataAddress, physics_data_address)?;
+            self.encode_token(&mut chunk, address, BoneUnknownDataD, bone_data.unknown_data_d)?;
+            self.encode_token(&mut chunk, address, BoneTranslationXNext, bone_data.translation_x_next)?;
+            self.encode_token(&mut chunk, address, BoneGravityX, bone_data.gravity_x)?;
+            self.encode_token(&mut chunk, address, BoneGravityY, bone_data.gravity_y)?;
+            self.encode_token(&mut chunk, address, BonePhysicsConstraintXPos, bone_data.physics_constraint_x_max)?;
+            self.encode_token(&mut chunk, address, BonePhysicsConstraintXNeg, bone_data.physics_constraint_x_min)?;
+            self.encode_token(&mut chunk, address, BonePhysicsConstraintYPos, bone_data.physics_constraint_y_max)?;
+            self.encode_token(&mut chunk, address, BonePhysicsConstraintYNeg, bone_data.physics_constraint_y_min)?;
+            self.encode_token(&mut chunk, address, BoneUnknownDataE, bone_data.unknown_data_e)?;
+            self.encode_token(&mut chunk, address, BoneFlag, u8::from(bone_data.flag))?;
+            self.encode_token(&mut chunk, address, BoneParentId, bone_data.parent_id)?;
+            self.encode_token(&mut chunk, address, BoneId, bone_data.id)?;
+            self.encode_token(&mut chunk, address, BoneUnknownDataF, bone_data.unknown_data_f)?;
+
+            self.sink.write_all(&chunk)?;
 
             i += 1;
         }
 
-        stream_final_position = self.file.stream_position()?;
+        let stream_final_position = self.sink.stream_position()?;
 
         if (stream_final_position - stream_start_position) == (i * NmdFileBone::CHUNK_SIZE) {
             Ok(()) 
         } else {
-            Self::error("[NmdFileWriter::write_bone_data] Wrote an unexpected number of bytes")
+            Err(NmdError::UnexpectedLength {
+                offset: stream_start_position,
+                expected: i * NmdFileBone::CHUNK_SIZE,
+                found: stream_final_position - stream_start_position,
+            })
         }
     }
 
-    fn write_bone_name_data(&mut self, bone_data: &Vec<&NmdFileBone>) -> Result<()> {
-        const BYTE_SHIFT: u8 = NmdFileBone::ASCII_BYTE_SHIFT;
+    // Kept records are written over the original block where they were read
+    // from, so bytes no record covers are kept; fresh records follow it
+    fn write_physics_data(&mut self, data: &NmdFileData, physics_layout: &PhysicsLayout) -> NmdResult<()> {
+        let block = data.raw_physics_data();
+        let mut position = 0;
+
+        // Kept records never overlap, so each starts at or after the last end
+        for (offset, physics) in &physics_layout.kept {
+            self.sink.write_all(&block[position..*offset])?;
+            self.write_physics_record(physics)?;
 
-        for (i, NmdFileBone { name, .. }) in bone_data.into_iter().enumerate() {
-            let name_address = self.file.stream_position()?;
+            position = offset + physics.len();
+        }
 
-            self.write_nth_bone_name_address(i, name_address)?;
+        self.sink.write_all(&block[position..])?;
 
-            self.seek(name_address)?;
-            self.file.write(&name.as_bytes().iter().map(|b| b + BYTE_SHIFT).collect::<Vec<_>>()[..])?;
-            self.file.write(&[BYTE_SHIFT])?;
+        for physics in &physics_layout.fresh {
+            self.write_physics_record(physics)?;
         }
 
         Ok(())
     }
 
-    fn write_header_revisions(&mut self, header: &NmdFileHeader, (bone_delta, byte_delta): (isize, isize)) -> Result<()>
+    fn write_physics_record(&mut self, physics: &NmdFilePhysics) -> NmdResult<()> {
+        use NmdFileToken::*;
+
+        let address = self.sink.stream_position()?;
+        let mut head = vec![0u8; NmdFilePhysics::HEAD_SIZE as usize];
+
+        self.encode_token(&mut head, address, PhysicsParameterA, physics.parameter_a)?;
+        self.encode_token(&mut head, address, PhysicsParameterB, physics.parameter_b)?;
+        self.encode_token(&mut head, address, PhysicsParameterC, physics.parameter_c)?;
+
+        self.sink.write_all(&head)?;
+        self.sink.write_all(&physics.unknown_data)?;
+
+        Ok(())
+    }
+
+    // Bones with the same name share one string
+    fn write_bone_name_data(&mut self, bone_data: &Vec<&NmdFileBone>, names: &Vec<ByteVec>) -> NmdResult<()> {
+        let mut name_addresses = HashMap::<&ByteVec, u64>::new();
+
+        for (i, (bone_data, name)) in bone_data.iter().zip(names).enumerate() {
+            let end_address = self.sink.stream_position()?;
+            let name_address = *name_addresses.entry(name).or_insert(end_address);
+
+            self.write_nth_bone_name_address(i, bone_data.id, name_address)?;
+            self.seek(end_address)?;
+
+            if name_address == end_address {
+                self.sink.write_all(name)?;
+                self.sink.write_all(&[NmdFileBone::ASCII_BYTE_SHIFT])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // The original name data can be kept as-is (along with anything trailing
+    // it) when every bone is written back over a record of the same ID, in
+    // whatever order, and its name is still found at that record's name
+    // address
+    fn original_bone_name_addresses(data: &NmdFileData, bone_data: &Vec<&NmdFileBone>, names: &Vec<ByteVec>) -> Option<Vec<NmdFileAddress>> {
+        let name_data = data.raw_bone_name_data();
+        let name_data_address = data.header.bone_name_data_address;
+        // Records sharing an ID are matched up in record order
+        let mut records_by_id = HashMap::<u16, VecDeque<NmdFileAddress>>::new();
+
+        if bone_data.len() != data.records().len() {
+            return None;
+        }
+
+        for record in data.records() {
+            records_by_id.entry(record.id).or_default().push_back(record.name_address);
+        }
+
+        bone_data.iter()
+            .zip(names)
+            .map(|(bone_data, name)| {
+                let name_address = records_by_id.get_mut(&bone_data.id)?.pop_front()?;
+                let offset = (name_address.checked_sub(name_data_address)?) as usize;
+                let terminator = *name_data.get(offset + name.len())?;
+
+                (name_data[offset..].starts_with(&name[..])
+                    && terminator <= NmdFileBone::ASCII_BYTE_SHIFT)
+                    .then_some(name_address)
+            })
+            .collect()
+    }
+
+    fn write_original_bone_name_data(&mut self, data: &NmdFileData, bone_data: &[&NmdFileBone], name_addresses: &[NmdFileAddress], byte_delta: isize) -> NmdResult<()> {
+        let name_token = NmdFileToken::BoneNameAddress;
+        let block_token = NmdFileToken::HeaderBoneNameDataAddress;
+
+        for (i, (name_address, bone_data)) in name_addresses.iter().zip(bone_data).enumerate() {
+            let offset = self.field_offset(Self::nth_bone_address(i), name_token);
+
+            self.write_nth_bone_name_address(i, bone_data.id, delta!(*name_address, u64, byte_delta, offset, name_token, Some(bone_data.id))?)?;
+        }
+
+        self.seek(delta!(data.header.bone_name_data_address, u64, byte_delta, self.field_offset(0, block_token), block_token, None)?)?;
+        self.sink.write_all(data.raw_bone_name_data())?;
+
+        Ok(())
+    }
+
+    // Header bytes that no token covers are copied from `raw_header`
+    fn write_header(&mut self, raw_header: ByteArr, header: &NmdFileHeader, (bone_delta, blob_delta, name_delta): (isize, isize, isize)) -> NmdResult<()>
     {
         use NmdFileToken::*;
 
@@ -215,46 +563,41 @@ impl NmdFileWriter {
             bone_count,
             bone_name_data_address,
             blob_data_address,
-            ..
+            bone_data_address,
         } = header;
 
-        let new_bone_count             = delta!(*bone_count,             u16,            bone_delta);
-        let new_bone_name_data_address = delta!(*bone_name_data_address, NmdFileAddress, byte_delta);
-        let new_blob_data_address      = delta!(*blob_data_address,      NmdFileAddress, byte_delta);
+        let new_bone_count             = delta!(*bone_count,             u16,            bone_delta, self.field_offset(0, HeaderBoneCount),           HeaderBoneCount,           None)?;
+        let new_bone_name_data_address = delta!(*bone_name_data_address, NmdFileAddress, name_delta, self.field_offset(0, HeaderBoneNameDataAddress), HeaderBoneNameDataAddress, None)?;
+        let new_blob_data_address      = delta!(*blob_data_address,      NmdFileAddress, blob_delta, self.field_offset(0, HeaderBlobDataAddress),     HeaderBlobDataAddress,     None)?;
+        let mut bytes = raw_header.to_vec();
 
-        write_at!(self, new_bone_count,             u16,            HeaderBoneCount,           'little_endian)?;
-        write_at!(self, new_bone_name_data_address, NmdFileAddress, HeaderBoneNameDataAddress, 'little_endian)?;
-        write_at!(self, new_blob_data_address,      NmdFileAddress, HeaderBlobDataAddress,     'little_endian)?;
-        write_at!(self, new_bone_count,             u16,            HeaderBoneCountEcho,       'little_endian)?;
+        self.encode_token(&mut bytes, 0, HeaderBoneCount,           new_bone_count)?;
+        self.encode_token(&mut bytes, 0, HeaderBoneNameDataAddress, new_bone_name_data_address)?;
+        self.encode_token(&mut bytes, 0, HeaderBlobDataAddress,     new_blob_data_address)?;
+        self.encode_token(&mut bytes, 0, HeaderBoneDataAddress,     *bone_data_address)?;
+        self.encode_token(&mut bytes, 0, HeaderBoneCountEcho,       new_bone_count)?;
 
-        self.seek(NmdFileHeader::CHUNK_SIZE)?;
+        self.seek(0)?;
+        self.sink.write_all(&bytes)?;
 
         Ok(())
     }
 
-    fn write_nth_bone_name_address(&mut self, n: usize, name_address: u64) -> Result<()> {
-        let bone_address = Self::nth_bone_address(n);
-
-        self.seek(bone_address);
-        self.seek_token(NmdFileToken::BoneNameAddress);
+    fn write_nth_bone_name_address(&mut self, n: usize, bone_id: u16, name_address: u64) -> NmdResult<()> {
+        let token = NmdFileToken::BoneNameAddress;
+        let offset = self.field_offset(Self::nth_bone_address(n), token);
 
         match NmdFileAddress::try_from(name_address) {
             Ok(name_address) => {
-                write!(self, name_address, NmdFileAddress, 'little_endian)?;
-
-                Ok(())
+                self.seek(offset)?;
+                self.write_value(&name_address)
             }
-            _ => Self::error("[NmdFileWriter::write_nth_bone_name_address] Name address out of bounds")
+            _ => Err(NmdError::AddressOutOfBounds {
+                offset: offset,
+                token: token,
+                bone_id: Some(bone_id),
+                address: name_address,
+            })
         }
     }
 }
-
-impl TryFrom<&PathBuf> for NmdFileWriter {
-    type Error = Error;
-
-    fn try_from(path: &PathBuf) -> Result<Self> {
-        Ok(Self {
-            file: File::create(path)?,
-        })
-    }
-}
@@ -0,0 +1,365 @@
+use {
+    crate::io::nmd::{
+        anatomy::{
+            NmdFileBone,
+            NmdFilePhysics,
+        },
+        data::{
+            tree::{
+                NmdFileBoneTree,
+                NmdFileBoneTreeNode,
+                NmdFileBoneTreeRoot,
+            },
+            NmdFileData,
+        },
+    },
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    serde_json::Value,
+    std::{
+        collections::BTreeMap,
+        fs,
+        io::{
+            self,
+            ErrorKind,
+        },
+        path::PathBuf,
+    },
+};
+
+/// A skeleton as a glTF 2.0 document (`.gltf`, JSON with no buffers), for
+/// looking at the rig in e.g. Blender. Each bone is a node under its parent,
+/// with `translation_*` (in centimetres) as the node's translation in metres
+/// and `rotation_*` (degrees about the fixed X, then Y, then Z axes) as its
+/// rotation quaternion. Anything else glTF has no place for is kept in the
+/// node's `extras`:
+///
+/// ```json
+/// {
+///   "name": "KOSHI",
+///   "translation": [0.0, 0.015, -0.02],
+///   "rotation": [0.0008, 0.0017, 0.0026, 1.0],
+///   "extras": {
+///     "id": 0,
+///     "parent_id": 65535,
+///     "flag": "Standard",
+///     "translation_x_next": 0.5,
+///     "gravity_x": 3,
+///     …
+///     "physics": { "parameter_a": 1.0, "parameter_b": 0.5, "parameter_c": 0.0 }
+///   }
+/// }
+/// ```
+///
+/// Every bone is also a joint of one skin, which is what importers take to
+/// mean an armature. Bones whose parent is missing are roots of the scene.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NmdFileGltf {
+    asset: NmdFileGltfAsset,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scene: Option<usize>,
+    #[serde(default)]
+    scenes: Vec<NmdFileGltfScene>,
+    #[serde(default)]
+    pub nodes: Vec<NmdFileGltfNode>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    skins: Vec<NmdFileGltfSkin>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NmdFileGltfAsset {
+    version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    generator: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NmdFileGltfScene {
+    #[serde(default)]
+    nodes: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NmdFileGltfSkin {
+    joints: Vec<usize>,
+}
+
+// Only what a skeleton uses; other properties of nodes read from elsewhere
+// are ignored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NmdFileGltfNode {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<usize>,
+    #[serde(default)]
+    pub translation: [f32; 3],
+    #[serde(default = "NmdFileGltfNode::identity_rotation")]
+    pub rotation: [f32; 4],
+    // Column-major, in place of `translation` and `rotation`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matrix: Option<[f32; 16]>,
+    // Kept as any JSON, since other tools may have added their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extras: Option<Value>,
+}
+
+/// What a bone's node keeps in `extras`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NmdFileGltfBoneExtras {
+    pub id: u16,
+    pub parent_id: u16,
+    pub flag: String,
+    pub translation_x_next: f32,
+    pub gravity_x: i16,
+    pub gravity_y: i16,
+    pub physics_constraint_x_max: i8,
+    pub physics_constraint_x_min: i8,
+    pub physics_constraint_y_max: i8,
+    pub physics_constraint_y_min: i8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub physics: Option<NmdFileGltfPhysics>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NmdFileGltfPhysics {
+    pub parameter_a: f32,
+    pub parameter_b: f32,
+    pub parameter_c: f32,
+}
+
+impl NmdFileGltf {
+    // glTF lengths are in metres
+    pub const METRES_PER_UNIT: f64 = 0.01;
+
+    fn add_node(&mut self, tree: &NmdFileBoneTree<NmdFileBone>, physics: &BTreeMap<u16, NmdFilePhysics>) -> usize {
+        let index = self.nodes.len();
+
+        self.nodes.push(NmdFileGltfNode::new(tree.data(), physics.get(&tree.id())));
+
+        for child in tree.children() {
+            let child_index = self.add_node(child, physics);
+
+            self.nodes[index].children.push(child_index);
+        }
+
+        index
+    }
+
+    pub fn from_json(text: &str) -> io::Result<Self> {
+        let gltf: Self = serde_json::from_str(text).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+
+        gltf.parents()?;
+
+        Ok(gltf)
+    }
+
+    /// The glTF document for a bone tree, with physics records by bone ID.
+    pub fn from_tree(tree: &NmdFileBoneTreeRoot<NmdFileBone>, physics: &BTreeMap<u16, NmdFilePhysics>) -> Self {
+        let mut gltf = Self {
+            asset: NmdFileGltfAsset {
+                version: String::from("2.0"),
+                generator: Some(format!("nmde {}", env!("CARGO_PKG_VERSION"))),
+            },
+            scene: Some(0),
+            scenes: vec![],
+            nodes: vec![],
+            skins: vec![],
+        };
+        let roots = tree.children()
+            .iter()
+            .map(|child| gltf.add_node(child, physics))
+            .collect();
+
+        gltf.scenes.push(NmdFileGltfScene {
+            nodes: roots,
+        });
+
+        if !gltf.nodes.is_empty() {
+            gltf.skins.push(NmdFileGltfSkin {
+                joints: (0..gltf.nodes.len()).collect(),
+            });
+        }
+
+        gltf
+    }
+
+    /// Each node's parent, if it has one. Fails unless the nodes form trees.
+    pub fn parents(&self) -> io::Result<Vec<Option<usize>>> {
+        let invalid = |error: String| io::Error::new(ErrorKind::InvalidData, error);
+        let mut parents = vec![None; self.nodes.len()];
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            for child_index in &node.children {
+                match parents.get_mut(*child_index) {
+                    None => return Err(invalid(format!("node {} has a child {} that doesn't exist", index, child_index))),
+                    Some(Some(_)) => return Err(invalid(format!("node {} has more than one parent", child_index))),
+                    Some(parent) => *parent = Some(index),
+                }
+            }
+        }
+
+        // With one parent each, a node with no root above it is in a cycle
+        for index in 0..parents.len() {
+            let mut ancestor_opt = parents[index];
+
+            for _ in 0..parents.len() {
+                ancestor_opt = ancestor_opt.and_then(|ancestor| parents[ancestor]);
+            }
+
+            if ancestor_opt.is_some() {
+                return Err(invalid(format!("node {} has a cycle of parents above it", index)));
+            }
+        }
+
+        Ok(parents)
+    }
+
+    pub fn save(&self, path: &PathBuf) -> io::Result<()> {
+        fs::write(path, self.to_json()?)
+    }
+
+    pub fn to_json(&self) -> io::Result<String> {
+        serde_json::to_string_pretty(self).map_err(io::Error::other)
+    }
+}
+
+impl NmdFileGltfNode {
+    /// `extras` as written for a bone, if they are.
+    pub fn bone_extras(&self) -> Option<NmdFileGltfBoneExtras> {
+        self.extras.to_owned().and_then(|extras| serde_json::from_value(extras).ok())
+    }
+
+    fn identity_rotation() -> [f32; 4] {
+        [0.0, 0.0, 0.0, 1.0]
+    }
+
+    /// Translation and rotation quaternion, from `matrix` if there is one.
+    pub fn placement(&self) -> ([f32; 3], [f32; 4]) {
+        match self.matrix {
+            Some(matrix) => ([matrix[12], matrix[13], matrix[14]], matrix_rotation(&matrix)),
+            None => (self.translation, self.rotation),
+        }
+    }
+
+    fn new(bone_data: &NmdFileBone, physics_opt: Option<&NmdFilePhysics>) -> Self {
+        let extras = NmdFileGltfBoneExtras {
+            id: bone_data.id,
+            parent_id: bone_data.parent_id,
+            flag: bone_data.flag.to_string(),
+            translation_x_next: bone_data.translation_x_next,
+            gravity_x: bone_data.gravity_x,
+            gravity_y: bone_data.gravity_y,
+            physics_constraint_x_max: bone_data.physics_constraint_x_max,
+            physics_constraint_x_min: bone_data.physics_constraint_x_min,
+            physics_constraint_y_max: bone_data.physics_constraint_y_max,
+            physics_constraint_y_min: bone_data.physics_constraint_y_min,
+            physics: physics_opt.map(|physics| NmdFileGltfPhysics {
+                parameter_a: physics.parameter_a,
+                parameter_b: physics.parameter_b,
+                parameter_c: physics.parameter_c,
+            }),
+        };
+
+        Self {
+            name: Some(bone_data.name.to_owned()),
+            children: vec![],
+            translation: translation(bone_data),
+            rotation: rotation(bone_data),
+            matrix: None,
+            extras: serde_json::to_value(extras).ok(),
+        }
+    }
+}
+
+impl TryFrom<&PathBuf> for NmdFileGltf {
+    type Error = io::Error;
+
+    fn try_from(path: &PathBuf) -> io::Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+impl From<&NmdFileData> for NmdFileGltf {
+    fn from(data: &NmdFileData) -> Self {
+        Self::from_tree(&data.tree_with(NmdFileBone::to_owned), &data.physics)
+    }
+}
+
+/// Rotation degrees about the fixed X, then Y, then Z axes, for a glTF
+/// quaternion; the reverse of `rotation`.
+pub(crate) fn euler_degrees(quaternion: [f32; 4]) -> [f32; 3] {
+    let length = quaternion.iter().map(|value| (*value as f64).powi(2)).sum::<f64>().sqrt();
+    let [x, y, z, w] = match length > 0.0 {
+        true => quaternion.map(|value| value as f64 / length),
+        false => [0.0, 0.0, 0.0, 1.0],
+    };
+
+    [
+        (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y)),
+        (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin(),
+        (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z)),
+    ].map(|radians| radians.to_degrees() as f32)
+}
+
+// The rotation of a column-major transform, with any scale taken out
+fn matrix_rotation(matrix: &[f32; 16]) -> [f32; 4] {
+    let scale = |column: usize| (0..3).map(|row| (matrix[column * 4 + row] as f64).powi(2)).sum::<f64>().sqrt().max(f64::EPSILON);
+    let scales = [scale(0), scale(1), scale(2)];
+    let r = |row: usize, column: usize| matrix[column * 4 + row] as f64 / scales[column];
+    let trace = r(0, 0) + r(1, 1) + r(2, 2);
+
+    // Divided by whichever component is largest, to stay accurate
+    let [x, y, z, w] = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        [(r(2, 1) - r(1, 2)) / s, (r(0, 2) - r(2, 0)) / s, (r(1, 0) - r(0, 1)) / s, s / 4.0]
+    } else if r(0, 0) > r(1, 1) && r(0, 0) > r(2, 2) {
+        let s = (1.0 + r(0, 0) - r(1, 1) - r(2, 2)).sqrt() * 2.0;
+        [s / 4.0, (r(0, 1) + r(1, 0)) / s, (r(0, 2) + r(2, 0)) / s, (r(2, 1) - r(1, 2)) / s]
+    } else if r(1, 1) > r(2, 2) {
+        let s = (1.0 + r(1, 1) - r(0, 0) - r(2, 2)).sqrt() * 2.0;
+        [(r(0, 1) + r(1, 0)) / s, s / 4.0, (r(1, 2) + r(2, 1)) / s, (r(0, 2) - r(2, 0)) / s]
+    } else {
+        let s = (1.0 + r(2, 2) - r(0, 0) - r(1, 1)).sqrt() * 2.0;
+        [(r(0, 2) + r(2, 0)) / s, (r(1, 2) + r(2, 1)) / s, s / 4.0, (r(1, 0) - r(0, 1)) / s]
+    };
+
+    [x as f32, y as f32, z as f32, w as f32]
+}
+
+/// A bone's rotation as a glTF quaternion, `[x, y, z, w]`.
+pub(crate) fn rotation(bone_data: &NmdFileBone) -> [f32; 4] {
+    let half_angle = |degrees: f32| (degrees as f64).to_radians() / 2.0;
+    let about = |axis: usize, degrees: f32| {
+        let mut quaternion = [0.0, 0.0, 0.0, half_angle(degrees).cos()];
+
+        quaternion[axis] = half_angle(degrees).sin();
+        quaternion
+    };
+
+    // X first, so it's rightmost
+    let [x, y, z, w] = multiply(
+        about(2, bone_data.rotation_z),
+        multiply(about(1, bone_data.rotation_y), about(0, bone_data.rotation_x)),
+    );
+
+    [x as f32, y as f32, z as f32, w as f32]
+}
+
+/// A bone's translation as a glTF translation, in metres.
+pub(crate) fn translation(bone_data: &NmdFileBone) -> [f32; 3] {
+    [bone_data.translation_x, bone_data.translation_y, bone_data.translation_z]
+        .map(|value| (value as f64 * NmdFileGltf::METRES_PER_UNIT) as f32)
+}
+
+// Hamilton product of `[x, y, z, w]` quaternions
+fn multiply([ax, ay, az, aw]: [f64; 4], [bx, by, bz, bw]: [f64; 4]) -> [f64; 4] {
+    [
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+        aw * bw - ax * bx - ay * by - az * bz,
+    ]
+}
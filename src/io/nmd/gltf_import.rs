@@ -0,0 +1,293 @@
+use {
+    crate::io::nmd::{
+        anatomy::{
+            NmdFileBone,
+            NmdFileBoneFlag,
+            NmdFilePhysics,
+        },
+        gltf,
+        NmdFileBoneTableChange,
+        NmdFileGltf,
+    },
+    std::{
+        collections::{
+            BTreeMap,
+            HashMap,
+        },
+        io::{
+            self,
+            ErrorKind,
+        },
+        iter,
+    },
+};
+
+/// A glTF skeleton matched against a file's bones by node name, giving the
+/// placements it would set on matched bones and the nodes and bones left
+/// over. Placements are worked out against the bones at the time they're
+/// applied, so an import can be previewed and applied after other edits.
+///
+/// Where several bones share a name, nodes claim them in ID order, except that
+/// a node exported by this program claims the bone with the ID in its
+/// `extras`. A node whose placement is what its bone would be exported with
+/// leaves that bone as it is, so a round trip through glTF changes nothing.
+#[derive(Debug, Clone)]
+pub struct NmdFileGltfImport {
+    gltf: NmdFileGltf,
+    parents: Vec<Option<usize>>,
+    // Matched node for each bone, and the reverse
+    matches: BTreeMap<u16, usize>,
+    matched_bones: HashMap<usize, u16>,
+    unmatched_bone_ids: Vec<u16>,
+    unmatched_nodes: Vec<usize>,
+    // Flag and physics record each unmatched node would be made with, where
+    // its `extras` give them
+    flags: HashMap<usize, NmdFileBoneFlag>,
+    physics: HashMap<usize, NmdFilePhysics>,
+}
+
+/// Where an unmatched node would go as a new bone.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NmdFileGltfParent {
+    // The parent node's matched bone
+    Bone(u16),
+    // A parent node that's unmatched as well
+    Node(usize),
+    Root,
+}
+
+impl NmdFileGltfImport {
+    /// The placement a node gives a bone, keeping any value it would have been
+    /// exported with as is.
+    pub fn apply(&self, bone_data: &NmdFileBone) -> NmdFileBone {
+        let mut applied = bone_data.to_owned();
+
+        if let Some(node_index) = self.matches.get(&bone_data.id) {
+            let (translation, rotation) = self.gltf.nodes[*node_index].placement();
+            let exported_rotation = gltf::rotation(bone_data);
+            let translations = [&mut applied.translation_x, &mut applied.translation_y, &mut applied.translation_z];
+
+            for ((field, value), exported_value) in translations.into_iter().zip(translation).zip(gltf::translation(bone_data)) {
+                if !nearly_equal(value, exported_value) {
+                    *field = (value as f64 / NmdFileGltf::METRES_PER_UNIT) as f32;
+                }
+            }
+
+            // A quaternion and its negative are the same rotation
+            let unrotated = [1.0, -1.0].into_iter().any(|sign: f32| {
+                rotation.iter().zip(exported_rotation).all(|(value, exported_value)| nearly_equal(value * sign, exported_value))
+            });
+
+            if !unrotated {
+                [applied.rotation_x, applied.rotation_y, applied.rotation_z] = gltf::euler_degrees(rotation);
+            }
+        }
+
+        applied
+    }
+
+    /// Bones with a matching node.
+    pub fn bone_ids(&self) -> impl Iterator<Item = u16> + '_ {
+        self.matches.keys().copied()
+    }
+
+    /// Placement fields that would change the given bones.
+    pub fn changes(&self, bones: &BTreeMap<u16, NmdFileBone>) -> Vec<NmdFileBoneTableChange> {
+        let mut changes = vec![];
+
+        for bone_data in self.bone_ids().filter_map(|bone_id| bones.get(&bone_id)) {
+            let applied = self.apply(bone_data);
+            let fields = [
+                ("translation_x", bone_data.translation_x, applied.translation_x),
+                ("translation_y", bone_data.translation_y, applied.translation_y),
+                ("translation_z", bone_data.translation_z, applied.translation_z),
+                ("rotation_x", bone_data.rotation_x, applied.rotation_x),
+                ("rotation_y", bone_data.rotation_y, applied.rotation_y),
+                ("rotation_z", bone_data.rotation_z, applied.rotation_z),
+            ];
+
+            for (column, value, applied_value) in fields {
+                if value.to_string() != applied_value.to_string() {
+                    changes.push(NmdFileBoneTableChange {
+                        bone_id: bone_data.id,
+                        name: bone_data.name.to_owned(),
+                        column: String::from(column),
+                        replaced_text: value.to_string(),
+                        text: applied_value.to_string(),
+                    });
+                }
+            }
+        }
+
+        changes
+    }
+
+    /// Whether a node can be made into a bone: one with a physics flag needs
+    /// the physics parameters in its `extras` and a record of a bone with the
+    /// same flag to take the bytes glTF doesn't carry from.
+    pub fn creatable(&self, node_index: usize) -> bool {
+        !self.flags.get(&node_index).is_some_and(NmdFileBoneFlag::is_phys) || self.physics.contains_key(&node_index)
+    }
+
+    /// Match a glTF skeleton's nodes against bones, with their physics records
+    /// to model new ones on.
+    pub fn new(gltf: NmdFileGltf, bones: &BTreeMap<u16, NmdFileBone>, physics: &BTreeMap<u16, NmdFilePhysics>) -> io::Result<Self> {
+        let parents = gltf.parents()?;
+        let mut candidates = HashMap::<&str, Vec<u16>>::new();
+        let mut matches = BTreeMap::new();
+        let mut unmatched_nodes = vec![];
+
+        for bone_data in bones.values() {
+            candidates.entry(&bone_data.name).or_default().push(bone_data.id);
+        }
+
+        for (node_index, node) in gltf.nodes.iter().enumerate() {
+            match node.name.as_deref().and_then(|name| candidates.get_mut(name)).filter(|bone_ids| !bone_ids.is_empty()) {
+                Some(bone_ids) => {
+                    let position = node.bone_extras()
+                        .and_then(|extras| bone_ids.iter().position(|bone_id| *bone_id == extras.id))
+                        .unwrap_or(0);
+
+                    matches.insert(bone_ids.remove(position), node_index);
+                }
+                None => unmatched_nodes.push(node_index),
+            }
+        }
+
+        // Parents first, so they exist by the time their children are made
+        let depth = |node_index: usize| iter_ancestors(&parents, node_index).count();
+
+        unmatched_nodes.sort_by_key(|node_index| depth(*node_index));
+
+        let mut flags = HashMap::new();
+        let mut new_physics = HashMap::new();
+
+        for node_index in &unmatched_nodes {
+            let extras = match gltf.nodes[*node_index].bone_extras() {
+                Some(extras) => extras,
+                None => continue,
+            };
+            let flag: NmdFileBoneFlag = extras.flag.parse()
+                .map_err(|error| io::Error::new(ErrorKind::InvalidData, format!("node {}: {}", node_name(&gltf, *node_index), error)))?;
+            let template_opt = bones.values()
+                .filter(|bone_data| bone_data.flag == flag)
+                .find_map(|bone_data| physics.get(&bone_data.id));
+
+            if let (true, Some(node_physics), Some(template)) = (flag.is_phys(), extras.physics, template_opt) {
+                new_physics.insert(*node_index, NmdFilePhysics {
+                    id: template.id,
+                    parameter_a: node_physics.parameter_a,
+                    parameter_b: node_physics.parameter_b,
+                    parameter_c: node_physics.parameter_c,
+                    unknown_data: template.unknown_data.to_owned(),
+                });
+            }
+
+            flags.insert(*node_index, flag);
+        }
+
+        Ok(Self {
+            matched_bones: matches.iter().map(|(bone_id, node_index)| (*node_index, *bone_id)).collect(),
+            unmatched_bone_ids: bones.keys().filter(|bone_id| !matches.contains_key(bone_id)).copied().collect(),
+            unmatched_nodes: unmatched_nodes,
+            matches: matches,
+            parents: parents,
+            gltf: gltf,
+            flags: flags,
+            physics: new_physics,
+        })
+    }
+
+    /// A bone made from an unmatched node, with its physics record if it's
+    /// `creatable` with one. Fields glTF doesn't carry are zero, but for the
+    /// physics record's, which are copied from the record it's modelled on.
+    pub fn new_bone(&self, node_index: usize, bone_id: u16, parent_id: u16) -> (NmdFileBone, Option<NmdFilePhysics>) {
+        let node = &self.gltf.nodes[node_index];
+        let (translation, rotation) = node.placement();
+        let [translation_x, translation_y, translation_z] = translation
+            .map(|value| (value as f64 / NmdFileGltf::METRES_PER_UNIT) as f32);
+        let [rotation_x, rotation_y, rotation_z] = gltf::euler_degrees(rotation);
+        let mut bone_data = NmdFileBone {
+            id: bone_id,
+            parent_id: parent_id,
+            name: self.node_name(node_index),
+            translation_x: translation_x,
+            translation_y: translation_y,
+            translation_z: translation_z,
+            rotation_x: rotation_x,
+            rotation_y: rotation_y,
+            rotation_z: rotation_z,
+            ..Default::default()
+        };
+
+        if let Some(extras) = node.bone_extras() {
+            bone_data.flag = self.flags.get(&node_index).copied().unwrap_or_default();
+            bone_data.translation_x_next = extras.translation_x_next;
+            bone_data.gravity_x = extras.gravity_x;
+            bone_data.gravity_y = extras.gravity_y;
+            bone_data.physics_constraint_x_max = extras.physics_constraint_x_max;
+            bone_data.physics_constraint_x_min = extras.physics_constraint_x_min;
+            bone_data.physics_constraint_y_max = extras.physics_constraint_y_max;
+            bone_data.physics_constraint_y_min = extras.physics_constraint_y_min;
+        }
+
+        let physics_opt = self.physics.get(&node_index).map(|physics| NmdFilePhysics {
+            id: bone_id,
+            ..physics.to_owned()
+        });
+
+        (bone_data, physics_opt)
+    }
+
+    /// A node's name, or one made up from its index if it has none.
+    pub fn node_name(&self, node_index: usize) -> String {
+        node_name(&self.gltf, node_index)
+    }
+
+    /// The node a node sits under, if any.
+    pub fn parent_node(&self, node_index: usize) -> Option<usize> {
+        self.parents[node_index]
+    }
+
+    /// Where a node sits, in terms of bones where it can be.
+    pub fn parent(&self, node_index: usize) -> NmdFileGltfParent {
+        match self.parents[node_index] {
+            Some(parent_index) => match self.matched_bones.get(&parent_index) {
+                Some(bone_id) => NmdFileGltfParent::Bone(*bone_id),
+                None => NmdFileGltfParent::Node(parent_index),
+            },
+            None => NmdFileGltfParent::Root,
+        }
+    }
+
+    pub fn unmatched_bone_ids(&self) -> &[u16] {
+        &self.unmatched_bone_ids
+    }
+
+    /// Nodes with no bone by their name, parents before children.
+    pub fn unmatched_nodes(&self) -> &[usize] {
+        &self.unmatched_nodes
+    }
+}
+
+fn iter_ancestors(parents: &[Option<usize>], node_index: usize) -> impl Iterator<Item = usize> + '_ {
+    let mut ancestor_opt = parents[node_index];
+
+    iter::from_fn(move || {
+        let ancestor = ancestor_opt?;
+
+        ancestor_opt = parents[ancestor];
+        Some(ancestor)
+    })
+}
+
+fn node_name(gltf: &NmdFileGltf, node_index: usize) -> String {
+    gltf.nodes[node_index].name
+        .to_owned()
+        .unwrap_or_else(|| format!("NODE_{}", node_index))
+}
+
+// Close enough to have come from the same `f32` through another program
+fn nearly_equal(a: f32, b: f32) -> bool {
+    (a - b).abs() <= 1e-6 * a.abs().max(b.abs()).max(1.0)
+}
@@ -1,12 +1,83 @@
 pub mod anatomy;
 pub mod data;
 
+mod annotation;
+mod bone_table;
+mod corpus;
+mod error;
 mod file;
+mod file_map;
+mod file_save;
 mod file_reader;
+mod file_validator;
+mod file_verifier;
 mod file_writer;
+mod gltf;
+mod gltf_import;
+mod skeleton;
 
 pub use {
+    annotation::{
+        NmdFileAnnotation,
+        NmdFileAnnotationFormat,
+        NmdFileAnnotationScope,
+        NmdFileAnnotations,
+        NmdFileFlagAnnotation,
+    },
+    bone_table::{
+        NmdFileBoneTable,
+        NmdFileBoneTableChange,
+    },
+    corpus::{
+        NmdFileCorpus,
+        NmdFileCorrelation,
+        NmdFileTokenStatistics,
+        NmdFileUnknownFlag,
+        NmdFileValueDistribution,
+    },
+    error::{
+        NmdError,
+        NmdResult,
+    },
     file::NmdFile,
+    file_map::{
+        NmdFileBlock,
+        NmdFileMap,
+        NmdFileSpan,
+    },
     file_reader::NmdFileReader,
-    file_writer::NmdFileWriter,
+    file_save::NmdFileSave,
+    file_validator::{
+        NmdFileFinding,
+        NmdFileFindingKind,
+        NmdFileSeverity,
+        NmdFileValidator,
+    },
+    file_verifier::{
+        NmdFileDivergence,
+        NmdFileLocation,
+        NmdFileVerifier,
+    },
+    file_writer::{
+        NmdFileBoneOrder,
+        NmdFileWriter,
+    },
+    gltf::{
+        NmdFileGltf,
+        NmdFileGltfBoneExtras,
+        NmdFileGltfNode,
+        NmdFileGltfPhysics,
+    },
+    gltf_import::{
+        NmdFileGltfImport,
+        NmdFileGltfParent,
+    },
+    skeleton::{
+        NmdFileSkeleton,
+        NmdFileSkeletonBlobSegment,
+        NmdFileSkeletonBone,
+        NmdFileSkeletonHeader,
+        NmdFileSkeletonPhysics,
+        NmdFileSkeletonRaw,
+    },
 };
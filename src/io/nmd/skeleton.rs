@@ -0,0 +1,575 @@
+use {
+    crate::io::nmd::{
+        anatomy::{
+            NmdFileAddress,
+            NmdFileBlob,
+            NmdFileBlobSegment,
+            NmdFileBone,
+            NmdFileBoneFlag,
+            NmdFileHeader,
+            NmdFilePhysics,
+            NmdFileVersion,
+        },
+        data::{
+            ByteVec,
+            NmdFileData,
+            NmdFileRawData,
+            NmdFileRecord,
+        },
+    },
+    std::{
+        collections::{
+            BTreeMap,
+            HashSet,
+        },
+        ffi::OsStr,
+        fs,
+        io::{
+            self,
+            ErrorKind,
+        },
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+/// A file's skeleton as text, for editing by hand and diffing, in TOML or JSON
+/// (going by the file extension). An unedited skeleton writes back the file it
+/// was made from byte for byte:
+///
+/// ```toml
+/// nmde_skeleton = 1   # format version
+/// game = "SCVI"       # "SCIV", "SCV" or "SCVI"
+///
+/// [header]
+/// bone_count = 2
+/// bone_data_address = 32
+/// blob_data_address = 592
+/// bone_name_data_address = 608
+///
+/// [[bones]]
+/// id = 0
+/// parent_id = 65535                   # 65535 for a root bone
+/// name = "KOSHI"
+/// flag = "Standard"                   # or e.g. "Unknown(0x06)"
+/// translation_x = 0.0
+/// translation_y = "0x7FC00001"        # as its bits if no number reads back
+///                                     # exactly, e.g. NaN or infinity
+/// # …each named field of `NmdFileBone`…
+/// unknown_data_a = "00 00 80 3F"      # unknown bytes as hex, in file order
+/// name_address = 608                  # where the name was read from; leave
+///                                     # out for new bones
+///
+/// [bones.physics]                     # physics bones only
+/// parameter_a = 1.0
+/// parameter_b = 0.5
+/// parameter_c = 0.0
+/// unknown_data = "…"
+///
+/// [[blob]]
+/// raw = "…"
+///
+/// [[blob]]
+/// bone_id_table = [0, 1]              # rewritten from the bones on export
+///
+/// [raw]                               # blocks as read, for whatever no field covers
+/// header = "…"
+/// physics = "…"
+/// blob = "…"                          # only used if there's no `[[blob]]`
+/// bone_names = "…"
+/// ```
+///
+/// Bones are listed in record order; a bone whose ID an earlier one already
+/// has is kept as a conflicting record.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NmdFileSkeleton {
+    nmde_skeleton: u32,
+    #[serde(with = "text")]
+    pub game: NmdFileVersion,
+    pub header: NmdFileSkeletonHeader,
+    pub bones: Vec<NmdFileSkeletonBone>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blob: Vec<NmdFileSkeletonBlobSegment>,
+    pub raw: NmdFileSkeletonRaw,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NmdFileSkeletonHeader {
+    pub bone_count: u16,
+    pub bone_data_address: NmdFileAddress,
+    pub blob_data_address: NmdFileAddress,
+    pub bone_name_data_address: NmdFileAddress,
+}
+
+// Fields as in `NmdFileBone`, with floats as written (or as their bits) and
+// bytes as hex. Plain values come before the physics table, which TOML needs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NmdFileSkeletonBone {
+    pub id: u16,
+    pub parent_id: u16,
+    pub name: String,
+    #[serde(with = "text")]
+    pub flag: NmdFileBoneFlag,
+    #[serde(with = "float")]
+    pub translation_x: f32,
+    #[serde(with = "float")]
+    pub translation_y: f32,
+    #[serde(with = "float")]
+    pub translation_z: f32,
+    #[serde(with = "float")]
+    pub rotation_x: f32,
+    #[serde(with = "float")]
+    pub rotation_y: f32,
+    #[serde(with = "float")]
+    pub rotation_z: f32,
+    #[serde(with = "float")]
+    pub translation_x_next: f32,
+    pub gravity_x: i16,
+    pub gravity_y: i16,
+    pub physics_constraint_x_max: i8,
+    pub physics_constraint_x_min: i8,
+    pub physics_constraint_y_max: i8,
+    pub physics_constraint_y_min: i8,
+    pub physics_data_address: NmdFileAddress,
+    #[serde(with = "hex")]
+    pub collision_data: [u8; 16],
+    #[serde(with = "hex")]
+    pub unknown_data_a: [u8; 4],
+    #[serde(with = "hex")]
+    pub unknown_data_b: [u8; 4],
+    #[serde(with = "hex")]
+    pub unknown_data_c: [u8; 4],
+    #[serde(with = "hex")]
+    pub unknown_data_d: [u8; 4],
+    #[serde(with = "hex")]
+    pub unknown_data_e: [u8; 19],
+    #[serde(with = "hex")]
+    pub unknown_data_f: [u8; 12],
+    // Where the record's name was read from; `None` for new bones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_address: Option<NmdFileAddress>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub physics: Option<NmdFileSkeletonPhysics>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NmdFileSkeletonPhysics {
+    #[serde(with = "float")]
+    pub parameter_a: f32,
+    #[serde(with = "float")]
+    pub parameter_b: f32,
+    #[serde(with = "float")]
+    pub parameter_c: f32,
+    #[serde(with = "hex")]
+    pub unknown_data: ByteVec,
+}
+
+// Told apart by their one key, as TOML has no enums
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum NmdFileSkeletonBlobSegment {
+    Raw {
+        #[serde(with = "hex")]
+        raw: ByteVec,
+    },
+    BoneIdTable {
+        bone_id_table: Vec<u16>,
+    },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NmdFileSkeletonRaw {
+    #[serde(with = "hex")]
+    pub header: ByteVec,
+    #[serde(default, with = "hex")]
+    pub physics: ByteVec,
+    #[serde(default, with = "hex")]
+    pub blob: ByteVec,
+    #[serde(default, with = "hex")]
+    pub bone_names: ByteVec,
+}
+
+// Read first, so a skeleton from a newer version fails on that rather than on
+// whatever changed
+#[derive(Deserialize)]
+struct NmdFileSkeletonVersion {
+    nmde_skeleton: u32,
+}
+
+impl NmdFileSkeleton {
+    pub const FORMAT_VERSION: u32 = 1;
+
+    fn check_version(version: u32) -> io::Result<()> {
+        match version {
+            Self::FORMAT_VERSION => Ok(()),
+            version => Err(io::Error::new(ErrorKind::InvalidData,
+                format!("skeleton format version {} isn't supported (expected {})", version, Self::FORMAT_VERSION))),
+        }
+    }
+
+    pub fn from_json(text: &str) -> io::Result<Self> {
+        let invalid = |error| io::Error::new(ErrorKind::InvalidData, error);
+
+        Self::check_version(serde_json::from_str::<NmdFileSkeletonVersion>(text).map_err(invalid)?.nmde_skeleton)?;
+
+        serde_json::from_str(text).map_err(invalid)
+    }
+
+    pub fn from_toml(text: &str) -> io::Result<Self> {
+        let invalid = |error| io::Error::new(ErrorKind::InvalidData, error);
+
+        Self::check_version(toml::from_str::<NmdFileSkeletonVersion>(text).map_err(invalid)?.nmde_skeleton)?;
+
+        toml::from_str(text).map_err(invalid)
+    }
+
+    fn is_json(path: &Path) -> bool {
+        path.extension().and_then(OsStr::to_str).is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+    }
+
+    /// Write as JSON if `path` ends in `.json`, otherwise as TOML.
+    pub fn save(&self, path: &PathBuf) -> io::Result<()> {
+        let text = match Self::is_json(path) {
+            true => self.to_json()?,
+            false => self.to_toml()?,
+        };
+
+        fs::write(path, text)
+    }
+
+    pub fn to_json(&self) -> io::Result<String> {
+        serde_json::to_string_pretty(self).map_err(io::Error::other)
+    }
+
+    pub fn to_toml(&self) -> io::Result<String> {
+        toml::to_string(self).map_err(io::Error::other)
+    }
+}
+
+impl NmdFileSkeletonBone {
+    fn new(bone_data: &NmdFileBone, name_address: Option<NmdFileAddress>, physics: Option<&NmdFilePhysics>) -> Self {
+        Self {
+            id: bone_data.id,
+            parent_id: bone_data.parent_id,
+            name: bone_data.name.to_owned(),
+            flag: bone_data.flag,
+            translation_x: bone_data.translation_x,
+            translation_y: bone_data.translation_y,
+            translation_z: bone_data.translation_z,
+            rotation_x: bone_data.rotation_x,
+            rotation_y: bone_data.rotation_y,
+            rotation_z: bone_data.rotation_z,
+            translation_x_next: bone_data.translation_x_next,
+            gravity_x: bone_data.gravity_x,
+            gravity_y: bone_data.gravity_y,
+            physics_constraint_x_max: bone_data.physics_constraint_x_max,
+            physics_constraint_x_min: bone_data.physics_constraint_x_min,
+            physics_constraint_y_max: bone_data.physics_constraint_y_max,
+            physics_constraint_y_min: bone_data.physics_constraint_y_min,
+            physics_data_address: bone_data.physics_data_address,
+            collision_data: bone_data.collision_data,
+            unknown_data_a: bone_data.unknown_data_a,
+            unknown_data_b: bone_data.unknown_data_b,
+            unknown_data_c: bone_data.unknown_data_c,
+            unknown_data_d: bone_data.unknown_data_d,
+            unknown_data_e: bone_data.unknown_data_e,
+            unknown_data_f: bone_data.unknown_data_f,
+            name_address: name_address,
+            physics: physics.map(|physics| NmdFileSkeletonPhysics {
+                parameter_a: physics.parameter_a,
+                parameter_b: physics.parameter_b,
+                parameter_c: physics.parameter_c,
+                unknown_data: physics.unknown_data.to_owned(),
+            }),
+        }
+    }
+
+    fn to_bone(&self) -> NmdFileBone {
+        NmdFileBone {
+            collision_data: self.collision_data,
+            translation_x: self.translation_x,
+            translation_y: self.translation_y,
+            translation_z: self.translation_z,
+            unknown_data_a: self.unknown_data_a,
+            rotation_x: self.rotation_x,
+            rotation_y: self.rotation_y,
+            rotation_z: self.rotation_z,
+            unknown_data_b: self.unknown_data_b,
+            name: self.name.to_owned(),
+            unknown_data_c: self.unknown_data_c,
+            physics_data_address: self.physics_data_address,
+            unknown_data_d: self.unknown_data_d,
+            translation_x_next: self.translation_x_next,
+            gravity_x: self.gravity_x,
+            gravity_y: self.gravity_y,
+            physics_constraint_x_max: self.physics_constraint_x_max,
+            physics_constraint_x_min: self.physics_constraint_x_min,
+            physics_constraint_y_max: self.physics_constraint_y_max,
+            physics_constraint_y_min: self.physics_constraint_y_min,
+            unknown_data_e: self.unknown_data_e,
+            flag: self.flag,
+            parent_id: self.parent_id,
+            id: self.id,
+            unknown_data_f: self.unknown_data_f,
+        }
+    }
+
+    fn to_physics(&self) -> Option<NmdFilePhysics> {
+        self.physics.as_ref().map(|physics| NmdFilePhysics {
+            id: self.id,
+            parameter_a: physics.parameter_a,
+            parameter_b: physics.parameter_b,
+            parameter_c: physics.parameter_c,
+            unknown_data: physics.unknown_data.to_owned(),
+        })
+    }
+}
+
+// Bones in the order the writer would write them
+impl From<&NmdFileData> for NmdFileSkeleton {
+    fn from(data: &NmdFileData) -> Self {
+        let mut listed = HashSet::<u16>::new();
+        let mut bones = vec!();
+
+        // Conflicting records have their physics by record index, the rest by
+        // bone ID
+        for (i, record) in data.records().iter().enumerate() {
+            match data.conflicts.get(&i) {
+                Some(bone_data) => bones.push(NmdFileSkeletonBone::new(bone_data, Some(record.name_address), data.conflict_physics.get(&i))),
+                None => if let Some(bone_data) = data.bones.get(&record.id) {
+                    if listed.insert(bone_data.id) {
+                        bones.push(NmdFileSkeletonBone::new(bone_data, Some(record.name_address), data.physics.get(&bone_data.id)));
+                    }
+                }
+            }
+        }
+
+        for bone_data in data.bones.values() {
+            if listed.insert(bone_data.id) {
+                bones.push(NmdFileSkeletonBone::new(bone_data, None, data.physics.get(&bone_data.id)));
+            }
+        }
+
+        for (i, bone_data) in data.conflicts.range(data.records().len()..) {
+            bones.push(NmdFileSkeletonBone::new(bone_data, None, data.conflict_physics.get(i)));
+        }
+
+        Self {
+            nmde_skeleton: Self::FORMAT_VERSION,
+            game: data.version,
+            header: NmdFileSkeletonHeader {
+                bone_count: data.header.bone_count,
+                bone_data_address: data.header.bone_data_address,
+                blob_data_address: data.header.blob_data_address,
+                bone_name_data_address: data.header.bone_name_data_address,
+            },
+            bones: bones,
+            blob: data.blob.segments.iter()
+                .map(|segment| match segment {
+                    NmdFileBlobSegment::Raw(bytes) => NmdFileSkeletonBlobSegment::Raw { raw: bytes.to_owned() },
+                    NmdFileBlobSegment::BoneIdTable(ids) => NmdFileSkeletonBlobSegment::BoneIdTable { bone_id_table: ids.to_owned() },
+                })
+                .collect(),
+            raw: NmdFileSkeletonRaw {
+                header: data.raw_header().to_vec(),
+                physics: data.raw_physics_data().to_owned(),
+                blob: data.raw_blob().to_owned(),
+                bone_names: data.raw_bone_name_data().to_owned(),
+            },
+        }
+    }
+}
+
+impl From<&NmdFileSkeleton> for NmdFileData {
+    fn from(skeleton: &NmdFileSkeleton) -> Self {
+        let mut bones = BTreeMap::new();
+        let mut conflicts = BTreeMap::new();
+        let mut physics = BTreeMap::new();
+        let mut conflict_physics = BTreeMap::new();
+        // Conflicts are keyed by record index, as read; bones with no record
+        // take the indices after the last record, in list order
+        let record_count = skeleton.bones.iter().filter(|entry| entry.name_address.is_some()).count();
+        let mut recorded = 0;
+        let mut unrecorded = 0;
+
+        for entry in &skeleton.bones {
+            let i = match entry.name_address {
+                Some(_) => {
+                    recorded += 1;
+                    recorded - 1
+                }
+                None => {
+                    unrecorded += 1;
+                    record_count + unrecorded - 1
+                }
+            };
+
+            if bones.contains_key(&entry.id) {
+                if let Some(entry_physics) = entry.to_physics() {
+                    conflict_physics.insert(i, entry_physics);
+                }
+
+                conflicts.insert(i, entry.to_bone());
+                continue;
+            }
+
+            if let Some(entry_physics) = entry.to_physics() {
+                physics.insert(entry.id, entry_physics);
+            }
+
+            bones.insert(entry.id, entry.to_bone());
+        }
+
+        Self {
+            header: NmdFileHeader {
+                bone_count: skeleton.header.bone_count,
+                bone_data_address: skeleton.header.bone_data_address,
+                blob_data_address: skeleton.header.blob_data_address,
+                bone_name_data_address: skeleton.header.bone_name_data_address,
+            },
+            blob: NmdFileBlob {
+                segments: skeleton.blob.iter()
+                    .map(|segment| match segment {
+                        NmdFileSkeletonBlobSegment::Raw { raw } => NmdFileBlobSegment::Raw(raw.to_owned()),
+                        NmdFileSkeletonBlobSegment::BoneIdTable { bone_id_table } => NmdFileBlobSegment::BoneIdTable(bone_id_table.to_owned()),
+                    })
+                    .collect(),
+            },
+            bones: bones,
+            conflicts: conflicts,
+            conflict_physics: conflict_physics,
+            physics: physics,
+            version: skeleton.game,
+            other_versions: vec!(),
+            bytes: NmdFileRawData {
+                header: skeleton.raw.header.to_owned(),
+                physics: skeleton.raw.physics.to_owned(),
+                blob: skeleton.raw.blob.to_owned(),
+                bone_names: skeleton.raw.bone_names.to_owned(),
+                records: skeleton.bones.iter()
+                    .filter_map(|entry| entry.name_address.map(|name_address| NmdFileRecord {
+                        id: entry.id,
+                        name_address: name_address,
+                    }))
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// Read as JSON if `path` ends in `.json`, otherwise as TOML.
+impl TryFrom<&PathBuf> for NmdFileSkeleton {
+    type Error = io::Error;
+
+    fn try_from(path: &PathBuf) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+
+        match Self::is_json(path) {
+            true => Self::from_json(&text),
+            false => Self::from_toml(&text),
+        }
+    }
+}
+
+// Bytes as space-separated hex pairs, e.g. "00 1A FF"
+mod hex {
+    use {
+        crate::io::nmd::data::{
+            hex_bytes,
+            hex_text,
+        },
+        serde::{
+            de::Error,
+            Deserialize,
+            Deserializer,
+            Serializer,
+        },
+    };
+
+    pub fn serialize<S: Serializer>(bytes: impl AsRef<[u8]>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex_text(bytes.as_ref()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: TryFrom<Vec<u8>>>(deserializer: D) -> Result<T, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let bytes = hex_bytes(&text)
+            .ok_or_else(|| D::Error::custom(format!("`{}` isn't hex bytes", text)))?;
+        let length = bytes.len();
+
+        T::try_from(bytes).map_err(|_| D::Error::custom(format!("`{}` is the wrong length ({} bytes)", text, length)))
+    }
+}
+
+// Floats by their shortest decimal form, rather than widened to 64 bits, which
+// would turn e.g. 0.1 into 0.10000000149011612. Read back through text, so the
+// value comes out the same. Those no number gets back bit for bit (NaN, which
+// JSON can't hold and loses its payload in TOML, and the infinities) are
+// written as their bits instead, e.g. "0x7FC00001"
+mod float {
+    use serde::{
+        de::Error,
+        Deserialize,
+        Deserializer,
+        Serializer,
+    };
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Float {
+        Number(f64),
+        Bits(String),
+    }
+
+    pub fn serialize<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = value.to_string();
+
+        match text.parse::<f32>() {
+            Ok(parsed) if value.is_finite() && parsed.to_bits() == value.to_bits()
+                => serializer.serialize_f64(text.parse().map_err(serde::ser::Error::custom)?),
+            _ => serializer.collect_str(&format_args!("{:#010X}", value.to_bits())),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+        match Float::deserialize(deserializer)? {
+            Float::Number(value) => value.to_string().parse().map_err(D::Error::custom),
+            Float::Bits(text) => text.strip_prefix("0x")
+                .or_else(|| text.strip_prefix("0X"))
+                .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                .map(f32::from_bits)
+                .ok_or_else(|| D::Error::custom(format!("`{}` isn't a number or the bits of one (e.g. \"0x7FC00001\")", text))),
+        }
+    }
+}
+
+// Values by their `Display` text, read back with `FromStr`
+mod text {
+    use {
+        serde::{
+            de::Error,
+            Deserialize,
+            Deserializer,
+            Serializer,
+        },
+        std::{
+            fmt::Display,
+            str::FromStr,
+        },
+    };
+
+    pub fn serialize<S: Serializer, T: Display>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: FromStr>(deserializer: D) -> Result<T, D::Error>
+        where T::Err: Display
+    {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
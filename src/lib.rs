@@ -0,0 +1,19 @@
+#![allow(unused)]
+#![allow(clippy::mutable_key_type)]
+// Struct fields are initialised as `field: field` throughout
+#![allow(clippy::redundant_field_names)]
+
+//! Reading, editing and writing of NMD skeleton files.
+//!
+//! The format itself lives under [`io::nmd`] (reader, writer, file data and
+//! the bone tree), and [`utils::filter`] provides the filter expression
+//! parser used to search bones. None of this depends on the GUI, which is
+//! built on top of it under the `gui` feature (on by default).
+
+pub mod io;
+pub mod utils;
+
+#[cfg(feature = "gui")]
+pub mod ui;
+
+pub use io::nmd;
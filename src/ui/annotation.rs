@@ -0,0 +1,21 @@
+use {
+    crate::io::nmd::NmdFileAnnotations,
+    std::{
+        io::ErrorKind,
+        path::PathBuf,
+    },
+    lazy_static::lazy_static,
+};
+
+lazy_static! {
+    pub static ref UI_ANNOTATIONS: NmdFileAnnotations = {
+        match NmdFileAnnotations::try_from(&PathBuf::from(NmdFileAnnotations::DEFAULT_PATH)) {
+            Ok(annotations) => return annotations,
+            // Annotations are optional
+            Err(error) if error.kind() == ErrorKind::NotFound => {}
+            Err(error) => eprintln!("{}: {}", NmdFileAnnotations::DEFAULT_PATH, error),
+        }
+
+        NmdFileAnnotations::default()
+    };
+}
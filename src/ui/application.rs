@@ -78,7 +78,7 @@ impl NmdApp {
         }
     }
 
-    pub(crate) fn run() {
+    pub fn run() {
         use options::*;
 
         let app_options = eframe::NativeOptions {
@@ -58,11 +58,12 @@ impl Color {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 #[serde(from = "TomlColors")]
 pub struct Colors {
     pub common: CommonColors,
     pub editor: EditorColors,
+    pub inspector: InspectorColors,
     pub menu: MenuColors,
     pub tree: TreeColors,
 }
@@ -81,6 +82,19 @@ pub struct EditorColors {
     pub modified: Color,
 }
 
+#[derive(Deserialize)]
+#[serde(from = "TomlInspectorColors")]
+pub struct InspectorColors {
+    pub blob: Color,
+    pub bone: Color,
+    pub bone_name: Color,
+    pub bone_unknown: Color,
+    pub header: Color,
+    pub highlighted: Color,
+    pub physics: Color,
+    pub selected: Color,
+}
+
 #[derive(Deserialize)]
 #[serde(from = "TomlMenuColors")]
 pub struct MenuColors {
@@ -90,6 +104,7 @@ pub struct MenuColors {
 #[derive(Deserialize)]
 #[serde(from = "TomlTreeColors")]
 pub struct TreeColors {
+    pub conflicted: Color,
     pub copied: Color,
     pub copy_pasted: Color,
     pub cut_pasted: Color,
@@ -104,6 +119,7 @@ pub struct TreeColors {
 #[serde(default)]
 struct TomlColors {
     editor: TomlEditorColors,
+    inspector: TomlInspectorColors,
     menu: TomlMenuColors,
     tree: TomlTreeColors,
 }
@@ -115,6 +131,19 @@ struct TomlEditorColors {
     modified: u32,
 }
 
+#[derive(Deserialize)]
+#[serde(default)]
+struct TomlInspectorColors {
+    blob: u32,
+    bone: u32,
+    bone_name: u32,
+    bone_unknown: u32,
+    header: u32,
+    highlighted: u32,
+    physics: u32,
+    selected: u32,
+}
+
 #[derive(Deserialize)]
 #[serde(default)]
 struct TomlMenuColors {
@@ -124,6 +153,7 @@ struct TomlMenuColors {
 #[derive(Deserialize)]
 #[serde(default)]
 struct TomlTreeColors {
+    conflicted: u32,
     copied: u32,
     copy_pasted: u32,
     cut_pasted: u32,
@@ -150,6 +180,7 @@ impl From<TomlColors> for Colors {
         Self {
             common: CommonColors::default(),
             editor: EditorColors::from(toml.editor),
+            inspector: InspectorColors::from(toml.inspector),
             menu: MenuColors::from(toml.menu),
             tree: TreeColors::from(toml.tree),
         }
@@ -165,6 +196,21 @@ impl From<TomlEditorColors> for EditorColors {
     }
 }
 
+impl From<TomlInspectorColors> for InspectorColors {
+    fn from(toml: TomlInspectorColors) -> Self {
+        Self {
+            blob: Color::from(toml.blob),
+            bone: Color::from(toml.bone),
+            bone_name: Color::from(toml.bone_name),
+            bone_unknown: Color::from(toml.bone_unknown),
+            header: Color::from(toml.header),
+            highlighted: Color::from(toml.highlighted),
+            physics: Color::from(toml.physics),
+            selected: Color::from(toml.selected),
+        }
+    }
+}
+
 impl From<TomlMenuColors> for MenuColors {
     fn from(toml: TomlMenuColors) -> Self {
         Self {
@@ -176,6 +222,7 @@ impl From<TomlMenuColors> for MenuColors {
 impl From<TomlTreeColors> for TreeColors {
     fn from(toml: TomlTreeColors) -> Self {
         Self {
+            conflicted: Color::from(toml.conflicted),
             copied: Color::from(toml.copied),
             copy_pasted: Color::from(toml.copy_pasted),
             cut_pasted: Color::from(toml.cut_pasted),
@@ -188,17 +235,6 @@ impl From<TomlTreeColors> for TreeColors {
     }
 }
 
-impl Default for Colors {
-    fn default() -> Self {
-        Self {
-            common: CommonColors::default(),
-            editor: EditorColors::default(),
-            menu: MenuColors::default(),
-            tree: TreeColors::default(),
-        }
-    }
-}
-
 impl Default for CommonColors {
     fn default() -> Self {
         Self {
@@ -216,6 +252,12 @@ impl Default for EditorColors {
     }
 }
 
+impl Default for InspectorColors {
+    fn default() -> Self {
+        Self::from(TomlInspectorColors::default())
+    }
+}
+
 impl Default for MenuColors {
     fn default() -> Self {
         Self::from(TomlMenuColors::default())
@@ -237,6 +279,21 @@ impl Default for TomlEditorColors {
     }
 }
 
+impl Default for TomlInspectorColors {
+    fn default() -> Self {
+        Self {
+            blob: 0x79E8B4,
+            bone: 0xA7D4E7,
+            bone_name: 0xE8C179,
+            bone_unknown: 0xE87979,
+            header: 0xBD79E8,
+            highlighted: 0x1F2842,
+            physics: 0x79ADE8,
+            selected: 0x1A4646,
+        }
+    }
+}
+
 impl Default for TomlMenuColors {
     fn default() -> Self {
         Self {
@@ -248,6 +305,7 @@ impl Default for TomlMenuColors {
 impl Default for TomlTreeColors {
     fn default() -> Self {
         Self {
+            conflicted: 0xE87979,
             copied: 0x79ADE8,
             copy_pasted: 0xBD79E8,
             cut_pasted: 0xE8C179,
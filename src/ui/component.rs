@@ -1,7 +1,8 @@
 use {
-    crate::io::nmd::anatomy::NmdFileBoneFlag,
+    crate::io::nmd::anatomy::{NmdFileBone, NmdFileBoneFlag, NmdFilePhysics, NmdFileVersion},
+    crate::io::nmd::{NmdFileBoneOrder, NmdFileBoneTable, NmdFileGltfImport},
     crate::ui::region::NodeView,
-    std::collections::{HashMap, HashSet},
+    std::collections::{BTreeSet, HashMap, HashSet},
     std::path::PathBuf,
     eframe::egui::Color32,
 };
@@ -12,20 +13,30 @@ use {
 
 #[derive(Debug)]
 pub enum UiComponent {
+    BoneTableApply(NmdFileBoneTable),
+    GltfApply(Box<NmdFileGltfImport>, BTreeSet<usize>), // Set: unmatched nodes to make bones of
+    MenuBoneTableExport(PathBuf),
+    MenuBoneTableImport(PathBuf),
     MenuCommit,
     MenuImport(PathBuf), // Later: paramaterize to IV, V, VI
-    MenuExport(PathBuf), // Later: paramaterize to IV, V, VI
+    MenuExport(PathBuf, NmdFileVersion, bool, NmdFileBoneOrder), // Bool: whether to back up the file replaced
+    MenuGltfExport(PathBuf),
+    MenuGltfImport(PathBuf),
+    MenuHexInspector,
     MenuHideListIds(bool),
     MenuProjectOpen(PathBuf),
     MenuProjectSaveAs(PathBuf),
     MenuTab(usize),
     MenuTabClose(usize),
+    MenuValidationReport,
+    TreeConflictRenumber(usize),
     TreeFilterClear,
     TreeNode(u16, String),
     TreeNodeCopy(u16, String),
     TreeNodeCopySingle(u16, String),
     TreeNodeCut(u16, String),
     TreeNodeDelete(u16),
+    TreeNodeMakeRoot(u16),
     TreeNodePaste(u16),
     TreeNodePasteAfter(u16),
     TreeNodeExpansion(u16),
@@ -34,6 +45,7 @@ pub enum UiComponent {
     TreeNodeUnroot,
     TreeNodeUnrootAll,
     TreeNodePin(u16, String),
+    TreeNodeReparent(u16, u16),
     TreeNodePinRemove(u16),
     TreeNodeScroll(u16),
     TreeNodeFocus(u16, String),
@@ -45,10 +57,14 @@ pub enum UiComponent {
 #[derive(Debug)]
 pub enum UiState {
     BoneData(u16, bool),
+    // Bone re-read from its edited bytes, by the ID it had before
+    BoneDecoded(u16, NmdFileBone),
     BoneName(u16, String),
     BoneFlag(u16, NmdFileBoneFlag),
+    TreeConflictRenumber(usize, u16),
     TreeEditStatus(bool),
     TreeNodeCopyPaste(HashMap<u16, (u16, String)>, u16),
+    TreeNodeCreate(Vec<(NmdFileBone, Option<NmdFilePhysics>)>),
     TreeNodeCutPaste(u16, u16),
     TreeNodeDelete(u16, HashSet<u16>),
     TreeNodeScrollDone,
@@ -1,3 +1,15 @@
+// Widgets are declared before the layout code that builds them, responses are
+// checked one condition per `if`, events are dispatched by `match` arm, and
+// multi-line text is built with `concat!` even when it has one line
+#![allow(
+    clippy::collapsible_if,
+    clippy::collapsible_match,
+    clippy::needless_late_init,
+    clippy::single_match,
+    clippy::useless_concat,
+)]
+
+pub mod annotation;
 pub mod color;
 pub mod region;
 pub mod style;
@@ -9,6 +21,7 @@ mod options;
 mod view;
 
 pub use {
+    annotation::UI_ANNOTATIONS as UiAnnotations,
     application::NmdApp,
     color::UI_COLORS as UiColor,
     component::{
@@ -0,0 +1,199 @@
+use {
+    crate::{
+        io::{
+            fifo::{
+                Message,
+                MessageSender,
+            },
+            nmd::{
+                NmdFileBoneTable,
+                NmdFileBoneTableChange,
+            },
+        },
+        ui::{
+            region,
+            region::NmdAppRegion,
+            UiColor,
+            UiComponent,
+        },
+    },
+    eframe::egui::*,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::path::PathBuf,
+};
+
+/// Preview of the cells an imported bone table would change, which are
+/// applied through the editor as ordinary edits once accepted.
+#[derive(Serialize, Deserialize)]
+pub struct NmdAppBoneTableRegion {
+    #[serde(skip)]
+    message_sender: Option<MessageSender>,
+    // Open while there's something to show
+    #[serde(skip)]
+    preview_opt: Option<BoneTablePreview>,
+    uuid_source: u64,
+}
+
+struct BoneTablePreview {
+    path: PathBuf,
+    // The table, or why it couldn't be read
+    table_result: Result<NmdFileBoneTable, String>,
+    changes: Vec<NmdFileBoneTableChange>,
+    unmatched_ids: Vec<u16>,
+}
+
+impl NmdAppBoneTableRegion {
+    pub fn new(message_sender: &MessageSender) -> Self {
+        let mut region = Self::default();
+
+        region.emit_with(message_sender);
+        region
+    }
+
+    fn emit_focus(&self, bone_id: u16, name: &String) {
+        self.emit(Message::UiSelect(UiComponent::TreeNodeFocus(bone_id, name.to_owned())));
+    }
+
+    pub fn emit_with(&mut self, message_sender: &MessageSender) {
+        self.message_sender = Some(message_sender.to_owned());
+    }
+
+    /// Show what a table read from `path` would change, with the changes and
+    /// unmatched IDs worked out against the bones being edited.
+    pub fn preview(&mut self, path: &PathBuf, table_result: Result<NmdFileBoneTable, String>, changes: Vec<NmdFileBoneTableChange>, unmatched_ids: Vec<u16>) {
+        self.preview_opt = Some(BoneTablePreview {
+            path: path.to_owned(),
+            table_result: table_result,
+            changes: changes,
+            unmatched_ids: unmatched_ids,
+        });
+    }
+
+    fn ui_changes(&self, ui: &mut Ui, preview: &BoneTablePreview) {
+        if preview.changes.is_empty() {
+            ui.label(RichText::new("No cells differ from the bones being edited").weak());
+        } else if let Some((bone_id, name)) = ui_change_grid(ui, self.uuid().with("bone_table$grid"), &preview.changes) {
+            self.emit_focus(bone_id, &name);
+        }
+    }
+
+    fn ui_unmatched(&self, ui: &mut Ui, preview: &BoneTablePreview) {
+        if !preview.unmatched_ids.is_empty() {
+            let ids: Vec<String> = preview.unmatched_ids.iter()
+                .map(|bone_id| format!("{:#06X}", bone_id))
+                .collect();
+
+            ui.label(RichText::new(format!("Skipped, with no bone by that ID: {}", ids.join(", "))).weak());
+        }
+    }
+}
+
+/// Grid of fields an import would change, giving the bone clicked on, if any.
+pub(super) fn ui_change_grid(ui: &mut Ui, id: Id, changes: &[NmdFileBoneTableChange]) -> Option<(u16, String)> {
+    let mut clicked_opt = None;
+
+    ui.label(format!("{} fields differ:", changes.len()));
+
+    ScrollArea::vertical()
+        .id_source(id.with("scroll"))
+        .max_height(320.0)
+        .show(ui, |ui|
+    {
+        Grid::new(id)
+            .striped(true)
+            .show(ui, |ui|
+        {
+            for heading in ["Bone", "Field", "Was", "Now"] {
+                ui.label(RichText::new(heading).strong());
+            }
+
+            ui.end_row();
+
+            for change in changes {
+                if ui.add(Label::new(format!("{} ({:#06X})", change.name, change.bone_id)).sense(Sense::click()))
+                    .on_hover_cursor(CursorIcon::PointingHand)
+                    .on_hover_text_at_pointer(format!("Go to {}", change.name))
+                    .clicked()
+                {
+                    clicked_opt = Some((change.bone_id, change.name.to_owned()));
+                }
+
+                ui.label(RichText::new(&change.column).monospace());
+                ui.label(RichText::new(&change.replaced_text).monospace().weak());
+                ui.label(RichText::new(&change.text).monospace());
+                ui.end_row();
+            }
+        });
+    });
+
+    clicked_opt
+}
+
+impl Default for NmdAppBoneTableRegion {
+    fn default() -> Self {
+        Self {
+            message_sender: None,
+            preview_opt: None,
+            uuid_source: region::generate_uuid_source(),
+        }
+    }
+}
+
+impl NmdAppRegion for NmdAppBoneTableRegion {
+    fn message_sender(&self) -> Option<&MessageSender> {
+        self.message_sender.as_ref()
+    }
+
+    fn ui(&mut self, ctx: &Context) {
+        let preview = match &self.preview_opt {
+            Some(preview) => preview,
+            None => return,
+        };
+        let mut open = true;
+        let mut closing = false;
+
+        Window::new("Import bone table")
+            .id(self.uuid().with("bone_table"))
+            .open(&mut open)
+            .default_width(560.0)
+            .show(ctx, |ui|
+        {
+            ui.label(RichText::new(preview.path.display().to_string()).weak());
+            ui.separator();
+
+            let table = match &preview.table_result {
+                Ok(table) => table,
+                Err(error) => {
+                    ui.label(RichText::new(error).color(UiColor.editor.error.normal()));
+                    return;
+                }
+            };
+
+            self.ui_changes(ui, preview);
+            self.ui_unmatched(ui, preview);
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!preview.changes.is_empty(), Button::new("Apply")).clicked() {
+                    self.emit(Message::UiSelect(UiComponent::BoneTableApply(table.to_owned())));
+                    closing = true;
+                }
+
+                if ui.button("Cancel").clicked() {
+                    closing = true;
+                }
+            });
+        });
+
+        if !open || closing {
+            self.preview_opt = None;
+        }
+    }
+
+    fn uuid_source(&self) -> u64 {
+        self.uuid_source
+    }
+}
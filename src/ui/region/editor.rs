@@ -5,16 +5,33 @@ use {
                 Message,
                 MessageSender,
             },
+            nmd::anatomy::token::NmdFileToken,
             nmd::anatomy::NmdFileBone,
             nmd::anatomy::NmdFileBoneFlag,
+            nmd::anatomy::NmdFileHeader,
+            nmd::anatomy::NmdFilePhysics,
+            nmd::anatomy::NmdFileVersion,
+            nmd::data::ByteVec,
             nmd::data::tree::NmdFileBoneTreeNode,
+            nmd::data::tree::NmdFileBoneTreeRoot,
             nmd::data::NmdFileData,
+            nmd::data::NmdFileParentSuggestion,
+            nmd::NmdFileAnnotationFormat,
+            nmd::NmdFileAnnotationScope,
+            nmd::NmdFileBoneOrder,
+            nmd::NmdFileBoneTable,
+            nmd::NmdFileBoneTableChange,
+            nmd::NmdFileGltf,
+            nmd::NmdFileGltfImport,
+            nmd::NmdFileSave,
             nmd::NmdFileWriter,
+            nmd::NmdResult,
         },
         ui::{
             region,
             region::NmdAppRegion,
             widget::*,
+            UiAnnotations,
             UiComponent,
             UiColor,
             UiState,
@@ -24,7 +41,9 @@ use {
         cell::Ref,
         collections::{BTreeMap, HashMap, HashSet},
         io,
+        iter,
         mem,
+        ops::Range,
         path::PathBuf,
         rc::Rc,
     },
@@ -46,6 +65,10 @@ use {
  *      -0.0000000000000000000100796926
  */
 
+// A glTF import, the placements it would change and the bones (with names) it
+// has no node for
+type GltfImportPreview = (NmdFileGltfImport, Vec<NmdFileBoneTableChange>, Vec<(u16, String)>);
+
 #[derive(Serialize, Deserialize)]
 struct ChainSnippet(Option<NmdFileBone>, NmdFileBone, Option<NmdFileBone>);
 
@@ -55,6 +78,27 @@ macro_rules! some_to_1 {
     };
 }
 
+macro_rules! take_decoded {
+    ($changes:ident, $bone_data:ident, $decoded:ident, { $($field:ident),* $(,)? }, 'debug { $($raw_field:ident),* $(,)? }) => {
+        $(
+            take_decoded!(@take $changes, $bone_data, $decoded, $field, $bone_data.$field.to_string(), $decoded.$field.to_string());
+        )*
+        $(
+            take_decoded!(@take $changes, $bone_data, $decoded, $raw_field, format!("{:?}", $bone_data.$raw_field), format!("{:?}", $decoded.$raw_field));
+        )*
+    };
+
+    // Compared as text, which is also how they're remembered
+    (@take $changes:ident, $bone_data:ident, $decoded:ident, $field:ident, $replaced_text:expr, $text:expr) => {
+        let (replaced_text, text) = ($replaced_text, $text);
+
+        if replaced_text != text {
+            $bone_data.$field = $decoded.$field;
+            $changes.push((stringify!($field), replaced_text, text));
+        }
+    };
+}
+
 macro_rules! ui_group {
     ($region:ident, $ui:ident, $heading:expr, { $($rest:tt)* }) => {
         let count = ui_group!(@count 0.0, $($rest)*);
@@ -311,9 +355,27 @@ pub struct NmdAppEditorRegion {
 #[derive(Serialize, Deserialize)]
 struct NmdAppEditorProjectState {
     chains: HashMap<u16, u16>,
+    // Records sharing an ID with a bone in `map`, by record index
+    #[serde(default)]
+    conflicts: BTreeMap<usize, NmdFileBone>,
+    // Physics records of conflicting records, by record index
+    #[serde(default)]
+    conflict_physics: BTreeMap<usize, NmdFilePhysics>,
     map: BTreeMap<u16, NmdFileBone>,
+    // Copy of the file's header, with untokenized bytes edited here; empty for
+    // projects saved before it was kept
+    #[serde(default)]
+    header_bytes: ByteVec,
+    // Header as it was before edits since the last commit
+    #[serde(default)]
+    header_unedited: Option<ByteVec>,
     // Would prefer memory field keys being `&str`
     memory: HashMap<u16, HashMap<String, Rc<String>>>,
+    #[serde(default)]
+    physics: BTreeMap<u16, NmdFilePhysics>,
+    // How each raw field is shown, by field name
+    #[serde(default)]
+    raw_formats: HashMap<String, NmdFileAnnotationFormat>,
     selected_id: Option<u16>,
     selection: Option<ChainSnippet>,
 }
@@ -324,6 +386,10 @@ struct NmdAppEditorTransientState {
     input_focus: bool,
     input_was_default: bool,
     memory_wipe: Option<HashSet<u16>>,
+    // Raw input being typed in, and its text
+    raw_input: Option<(Id, String)>,
+    // Game of the file, for the byte order of raw values
+    version: NmdFileVersion,
 }
 
 impl NmdAppEditorTransientState {
@@ -335,18 +401,40 @@ impl NmdAppEditorTransientState {
 }
 
 impl NmdAppEditorRegion {
+    const ORPHAN_SUGGESTION_COUNT: usize = 4;
+
+    // Bone fields with no known meaning, shown as raw bytes
+    const RAW_FIELDS: &'static [(&'static str, &'static str, NmdFileToken)] = {
+        use NmdFileToken::*;
+
+        &[
+            ("Collision", "collision_data", BoneCollisionData),
+            ("Unknown A", "unknown_data_a", BoneUnknownDataA),
+            ("Unknown B", "unknown_data_b", BoneUnknownDataB),
+            ("Unknown C", "unknown_data_c", BoneUnknownDataC),
+            ("Unknown D", "unknown_data_d", BoneUnknownDataD),
+            ("Unknown E", "unknown_data_e", BoneUnknownDataE),
+            ("Unknown F", "unknown_data_f", BoneUnknownDataF),
+        ]
+    };
+
     pub fn new(message_sender: &MessageSender, data: &NmdFileData) -> Self {
-        Self {
+        let mut region = Self {
             message_sender: Some(message_sender.to_owned()),
             state: NmdAppEditorProjectState::from(data),
             transient_state: Default::default(),
             uuid_source: region::generate_uuid_source(),
-        }
+        };
+
+        region.transient_state.version = data.version;
+        region
     }
 
     fn commit(&mut self) {
         let memory = mem::take(&mut self.state.memory);
 
+        self.state.header_unedited = None;
+
         self.transient_state.memory_wipe = Some(memory.into_keys().collect());
     }
 
@@ -366,6 +454,10 @@ impl NmdAppEditorRegion {
         self.emit(Message::UiState(UiState::BoneName(bone_id, name.to_owned())));
     }
 
+    fn emit_reparent(&self, bone_id: u16, parent_id: u16) {
+        self.emit(Message::UiSelect(UiComponent::TreeNodeReparent(bone_id, parent_id)));
+    }
+
     fn emit_select_node(&self, bone_id: u16, name: &String) {
         self.emit(Message::UiSelect(UiComponent::TreeNode(bone_id, name.to_owned())));
     }
@@ -454,10 +546,34 @@ impl NmdAppEditorRegion {
         }
     }
 
-    fn reject_bone_name(name: &String) -> bool {
+    fn reject_bone_name(name: &str) -> bool {
         name.chars().any(|c| !(c == '_' || c.is_ascii_alphanumeric()))
     }
 
+    /// Fill in what projects saved before the header was kept lack.
+    pub fn on_serialized(&mut self, data: &NmdFileData) {
+        if self.state.header_bytes.is_empty() {
+            self.state.header_bytes = data.raw_header().to_vec();
+        }
+
+        self.transient_state.version = data.version;
+    }
+
+    fn raw_field_mut(bone_data: &mut NmdFileBone, token: NmdFileToken) -> &mut [u8] {
+        use NmdFileToken::*;
+
+        match token {
+            BoneCollisionData   => &mut bone_data.collision_data,
+            BoneUnknownDataA    => &mut bone_data.unknown_data_a,
+            BoneUnknownDataB    => &mut bone_data.unknown_data_b,
+            BoneUnknownDataC    => &mut bone_data.unknown_data_c,
+            BoneUnknownDataD    => &mut bone_data.unknown_data_d,
+            BoneUnknownDataE    => &mut bone_data.unknown_data_e,
+            BoneUnknownDataF    => &mut bone_data.unknown_data_f,
+            _   => &mut [],
+        }
+    }
+
     fn remove_in_ui_memory(&mut self, ctx: &Context, ids: &HashSet<u16>) {
         for id in ids {
             input_mem_utils::remove(ctx, ui_id!(self, id (#direct), translation_x));
@@ -473,6 +589,9 @@ impl NmdAppEditorRegion {
             input_mem_utils::remove(ctx, ui_id!(self, id (#direct), gravity_x));
             input_mem_utils::remove(ctx, ui_id!(self, id (#direct), gravity_y));
             input_mem_utils::remove(ctx, ui_id!(self, id (#direct), translation_x_next));
+            input_mem_utils::remove(ctx, ui_id!(self, id (#direct), parameter_a));
+            input_mem_utils::remove(ctx, ui_id!(self, id (#direct), parameter_b));
+            input_mem_utils::remove(ctx, ui_id!(self, id (#direct), parameter_c));
         }
     }
 
@@ -482,14 +601,96 @@ impl NmdAppEditorRegion {
         }
     }
 
+    // Remember a field's text from before an edit, or forget it if the edit
+    // undoes the earlier ones
+    fn update_memory(&mut self, bone_id: u16, field: &'static str, replaced_text: &String, text: &String) {
+        if self.state.memory_equals(bone_id, field, text) {
+            self.remove_memory(bone_id, field);
+        } else if replaced_text != text {
+            self.insert_memory(bone_id, field, replaced_text);
+        }
+    }
+
     fn remove_memory(&mut self, bone_id: u16, field: &'static str) {
-        if let Some(_) = self.state.remove_memory(bone_id, field) {
+        if self.state.remove_memory(bone_id, field).is_some() {
             if !self.state.edited(bone_id) {
                 self.emit_edit(bone_id, false);
             }
         }
     }
 
+    fn set_header_bytes(&mut self, range: Range<usize>, bytes: &[u8]) {
+        self.state.header_unedited
+            .get_or_insert_with(|| self.state.header_bytes.to_owned());
+
+        self.state.header_bytes[range].copy_from_slice(bytes);
+
+        if self.state.header_unedited.as_ref() == Some(&self.state.header_bytes) {
+            self.state.header_unedited = None;
+        }
+    }
+
+    fn set_raw_field(&mut self, bone_data: &mut NmdFileBone, field: &'static str, token: NmdFileToken, bytes: &[u8]) {
+        let field_bytes = Self::raw_field_mut(bone_data, token);
+        let replaced_text = format!("{:?}", field_bytes);
+
+        field_bytes.copy_from_slice(bytes);
+
+        let text = format!("{:?}", field_bytes);
+
+        self.update_memory(bone_data.id, field, &replaced_text, &text);
+    }
+
+    /// Take on a bone re-read from edited bytes, e.g. in the hex inspector.
+    /// The ID, parent and addresses are kept, since those change through the
+    /// tree and on export.
+    //
+    // ** Don't call this from a UI function, because during that time the
+    // editor does not own the selection.
+    fn set_decoded(&mut self, bone_id: u16, decoded: &NmdFileBone) {
+        let mut changes = Vec::<(&'static str, String, String)>::new();
+        let flag_opt = self.without_selection(|region| {
+            let bone_data = region.state.get_mut(bone_id)?;
+
+            take_decoded!(changes, bone_data, decoded, {
+                translation_x, translation_y, translation_z,
+                rotation_x, rotation_y, rotation_z,
+                translation_x_next, gravity_x, gravity_y,
+                physics_constraint_x_max, physics_constraint_x_min,
+                physics_constraint_y_max, physics_constraint_y_min,
+            }, 'debug {
+                collision_data, unknown_data_a, unknown_data_b, unknown_data_c,
+                unknown_data_d, unknown_data_e, unknown_data_f,
+            });
+
+            if bone_data.name != decoded.name {
+                changes.push(("name", bone_data.name.to_owned(), decoded.name.to_owned()));
+                bone_data.name = decoded.name.to_owned();
+            }
+
+            Some(bone_data.flag)
+        });
+
+        for (field, replaced_text, text) in &changes {
+            self.update_memory(bone_id, field, replaced_text, text);
+
+            if *field == "name" {
+                self.emit_name(bone_id, text);
+            }
+        }
+
+        // Flags go through the tree as well, so take the usual route
+        if flag_opt.is_some_and(|flag| flag != decoded.flag) {
+            self.emit_flag(bone_id, decoded.flag);
+        }
+
+        if !changes.is_empty() {
+            self.transient_state.memory_wipe
+                .get_or_insert_with(HashSet::new)
+                .insert(bone_id);
+        }
+    }
+
     /// Set the flag for the bone with the given ID.
     //
     // ** Don't call this from a UI function, because during that time the
@@ -499,10 +700,10 @@ impl NmdAppEditorRegion {
             if let Some(bone_data) = region.state.get_mut(bone_id) {
                 let replaced_flag = mem::replace(&mut bone_data.flag, flag);
 
-                if region.state.memory_equals(bone_id, "flag", &flag.to_string()) {
+                if region.state.memory_equals(bone_id, "flag", &UiAnnotations.flag_label(flag)) {
                     region.remove_memory(bone_id, "flag");
                 } else {
-                    region.insert_memory(bone_id, "flag", &replaced_flag.to_string());
+                    region.insert_memory(bone_id, "flag", &UiAnnotations.flag_label(replaced_flag));
                 }
 
                 region.state.on_flag_changed(bone_id, replaced_flag, flag);
@@ -512,15 +713,111 @@ impl NmdAppEditorRegion {
 
     // ** Don't call this from a UI function, because during that time the
     // editor does not own the selection.
-    pub fn try_export(&mut self, path: &PathBuf, data: &NmdFileData) -> io::Result<()> {
+    //
+    // Gives the path the replaced file was backed up to, if it was.
+    pub fn try_export(&mut self, path: &PathBuf, data: &NmdFileData, version: NmdFileVersion, backing_up: bool, bone_order: NmdFileBoneOrder) -> NmdResult<Option<PathBuf>> {
         self.without_selection(|region| {
-            match NmdFileWriter::try_from(path) {
-                Ok(writer) => writer.write_new(data, &region.state.map),
+            match NmdFileSave::try_from(path) {
+                Ok(save) => {
+                    let mut writer = NmdFileWriter::new(save.with_backup(backing_up))
+                        .with_bone_order(bone_order);
+
+                    if !region.state.header_bytes.is_empty() {
+                        writer = writer.with_raw_header(&region.state.header_bytes);
+                    }
+
+                    writer.for_version(version)
+                        .write_new(data, &region.state.map, &region.state.conflicts, &region.state.physics, &region.state.conflict_physics)?
+                        .commit()
+                }
                 Err(error) => Err(error),
             }
         })
     }
 
+    // ** Don't call this from a UI function, because during that time the
+    // editor does not own the selection.
+    pub fn try_export_bone_table(&mut self, path: &PathBuf) -> NmdResult<()> {
+        self.without_selection(|region| {
+            region.state.map.values()
+                .collect::<NmdFileBoneTable>()
+                .save(path)
+                .map_err(Into::into)
+        })
+    }
+
+    // ** Don't call this from a UI function, because during that time the
+    // editor does not own the selection.
+    pub fn try_export_gltf(&mut self, path: &PathBuf) -> NmdResult<()> {
+        self.without_selection(|region| {
+            let tree = NmdFileBoneTreeRoot::new_with(region.state.map.values(), NmdFileBone::to_owned);
+
+            NmdFileGltf::from_tree(&tree, &region.state.physics)
+                .save(path)
+                .map_err(Into::into)
+        })
+    }
+
+    /// A glTF skeleton matched against the bones being edited, the placements
+    /// it would change and the bones (with names) it has no node for.
+    //
+    // ** Don't call this from a UI function, because during that time the
+    // editor does not own the selection.
+    pub fn gltf_import(&mut self, gltf: NmdFileGltf) -> io::Result<GltfImportPreview> {
+        self.without_selection(|region| {
+            let import = NmdFileGltfImport::new(gltf.to_owned(), &region.state.map, &region.state.physics)?;
+            let unmatched_bones = import.unmatched_bone_ids()
+                .iter()
+                .filter_map(|bone_id| region.state.map.get(bone_id))
+                .map(|bone_data| (bone_data.id, bone_data.name.to_owned()))
+                .collect();
+
+            let changes = import.changes(&region.state.map);
+
+            Ok((import, changes, unmatched_bones))
+        })
+    }
+
+    /// Cells of an imported bone table that differ from the bones being edited,
+    /// and the IDs in it with no bone here.
+    //
+    // ** Don't call this from a UI function, because during that time the
+    // editor does not own the selection.
+    pub fn bone_table_changes(&mut self, table: &NmdFileBoneTable) -> (Vec<NmdFileBoneTableChange>, Vec<u16>) {
+        self.without_selection(|region| {
+            (table.changes(&region.state.map), table.unmatched_ids(&region.state.map))
+        })
+    }
+
+    // Against the bones as they are now, which may have been edited since the
+    // preview, so an import replaces only the fields it names
+    //
+    // ** Don't call this from a UI function, because during that time the
+    // editor does not own the selection.
+    fn apply_imported(&mut self, mut bone_ids: impl Iterator<Item = u16>, apply: impl Fn(&NmdFileBone) -> NmdFileBone) {
+        let applied: Vec<NmdFileBone> = self.without_selection(|region| {
+            bone_ids
+                .by_ref()
+                .filter_map(|bone_id| region.state.map.get(&bone_id))
+                .map(&apply)
+                .collect()
+        });
+
+        for bone_data in &applied {
+            self.set_decoded(bone_data.id, bone_data);
+        }
+    }
+
+    // ** Don't call this from a UI function, because during that time the
+    // editor does not own the selection.
+    fn renumber(&mut self, record_index: usize, new_id: u16) {
+        self.without_selection(|region| {
+            if let Some(old_id) = region.state.on_renumbered(record_index, new_id) {
+                region.insert_memory(new_id, "id", &format!("{:#04X}", old_id));
+            }
+        });
+    }
+
     fn ui_flag_input(&mut self, ui: &mut Ui, bone_data: &NmdFileBone) {
         ui.horizontal(|ui| {
             let mut flag = bone_data.flag;
@@ -538,12 +835,17 @@ impl NmdAppEditorRegion {
             flag_box_width = ui.available_width() - (ui.spacing().item_spacing.x + 0.5);
 
             ComboBox::from_id_source(ui_id!(self, bone_data, #flag))
-                .selected_text(bone_data.flag.to_string())
+                .selected_text(UiAnnotations.flag_label(bone_data.flag))
                 .width(flag_box_width)
                 .show_ui(ui, |ui|
             {
-                for flag_entry in NmdFileBoneFlag::iter() {
-                    if ui.selectable_value(&mut flag, flag_entry, flag_entry.to_string())
+                let unnamed_flag_opt = Some(bone_data.flag)
+                    .filter(|flag| !UiAnnotations.flags().any(|flag_entry| flag_entry == *flag));
+
+                // A flag of no known type, and not named in the annotations,
+                // is listed only for bones that have it
+                for flag_entry in UiAnnotations.flags().chain(unnamed_flag_opt) {
+                    if ui.selectable_value(&mut flag, flag_entry, UiAnnotations.flag_label(flag_entry))
                         .clicked()
                     {
                         // Delay `set_flag` to next frame
@@ -564,7 +866,11 @@ impl NmdAppEditorRegion {
         });
     }
 
-    fn ui_id(&self, ui: &mut Ui, bone_data: &NmdFileBone) {
+    fn ui_id(&mut self, ui: &mut Ui, bone_data: &NmdFileBone) {
+        if let Some(unedited_id) = self.state.unedited_text(bone_data.id, "id") {
+            self.ui_input_edited_mark(ui, &unedited_id);
+        }
+
         ui.scope(|ui| {
             // `LayoutJob` to not highlight the text as a button
             let id_text = LayoutJob::single_section(
@@ -600,17 +906,17 @@ impl NmdAppEditorRegion {
     }
 
     fn ui_interactive_heading_style(&self, ui: &mut Ui) {
-        ui.visuals_mut().widgets.active.fg_stroke = (*UiColor).common.gray.normal_stroke();
-        ui.visuals_mut().widgets.hovered.fg_stroke = (*UiColor).common.gray.normal_stroke();
-        ui.visuals_mut().widgets.inactive.fg_stroke = (*UiColor).common.gray.normal_stroke();
+        ui.visuals_mut().widgets.active.fg_stroke = UiColor.common.gray.normal_stroke();
+        ui.visuals_mut().widgets.hovered.fg_stroke = UiColor.common.gray.normal_stroke();
+        ui.visuals_mut().widgets.inactive.fg_stroke = UiColor.common.gray.normal_stroke();
     }
 
-    fn ui_input_edited_mark(&mut self, ui: &mut Ui, original_display_text: &String) {
+    fn ui_input_edited_mark(&mut self, ui: &mut Ui, original_display_text: &str) {
         ui.add(
             Label::new(
                 RichText::new("✱")
                     .small()
-                    .color((*UiColor).editor.modified.normal()))
+                    .color(UiColor.editor.modified.normal()))
                 // TODO: Want to make hover text appear on mouse down; can
                 // achieve this by sensing clicks, but then element is
                 // "interactable" therefore gets tab focus; don't want this...
@@ -620,7 +926,7 @@ impl NmdAppEditorRegion {
             let mut text = LayoutJob::default();
 
             text.append("Modified from: ", 0.0, TextFormat::default());
-            text.append(original_display_text, 0.0, TextFormat { color: (*UiColor).common.weak_gray.normal(), ..Default::default() });
+            text.append(original_display_text, 0.0, TextFormat { color: UiColor.common.weak_gray.normal(), ..Default::default() });
 
             ui.label(text);
         }).surrender_focus();
@@ -657,8 +963,8 @@ impl NmdAppEditorRegion {
         }
     }
 
-    fn ui_name_rejected_mark(&self, ui: &mut Ui, bone_name: &String) {
-        ui.label(RichText::new("！").color((*UiColor).editor.error.normal()))
+    fn ui_name_rejected_mark(&self, ui: &mut Ui, bone_name: &str) {
+        ui.label(RichText::new("！").color(UiColor.editor.error.normal()))
             .on_hover_cursor(CursorIcon::Help)
             .on_hover_ui(|ui|
         {
@@ -667,6 +973,62 @@ impl NmdAppEditorRegion {
         });
     }
 
+    fn ui_orphan(&self, ui: &mut Ui, orphan: &NmdFileBone, suggestions: &[NmdFileParentSuggestion]) {
+        ui.horizontal(|ui| {
+            if ui.add(Label::new(RichText::new(&orphan.name).strong()).sense(Sense::click()))
+                .on_hover_cursor(CursorIcon::PointingHand)
+                .clicked()
+            {
+                self.emit_select_node(orphan.id, &orphan.name);
+            }
+
+            ui.label(RichText::new(format!("ID: {:#04X}, parent {:#04X} not found", orphan.id, orphan.parent_id)).weak());
+        });
+
+        ui.indent(ui_id!(self, orphan, #orphan), |ui| {
+            if suggestions.is_empty() {
+                ui.label(RichText::new("No suggestions").weak());
+            }
+
+            for suggestion in suggestions.iter().take(Self::ORPHAN_SUGGESTION_COUNT) {
+                ui.horizontal(|ui| {
+                    if ui.button(&suggestion.parent_name)
+                        .on_hover_text_at_pointer(format!("Move under {:#04X}", suggestion.parent_id))
+                        .clicked()
+                    {
+                        self.emit_reparent(orphan.id, suggestion.parent_id);
+                    }
+
+                    ui.label(RichText::new(suggestion.reason.to_string()).weak());
+                });
+            }
+        });
+    }
+
+    fn ui_orphans(&self, ctx: &Context) {
+        let orphans = self.state.orphans();
+
+        if !orphans.is_empty() {
+            TopBottomPanel::bottom(self.uuid().with("orphans"))
+                .resizable(true)
+                .show(ctx, |ui|
+            {
+                ui.add_space(4.0);
+                ui.label(RichText::new("Orphans").heading());
+                ui.separator();
+
+                ScrollArea::vertical()
+                    .id_source("editor$orphans")
+                    .show(ui, |ui|
+                {
+                    for (orphan, suggestions) in &orphans {
+                        self.ui_orphan(ui, orphan, suggestions);
+                    }
+                });
+            });
+        }
+    }
+
     fn ui_numeric_inputs(&mut self, ui: &mut Ui, ChainSnippet (prev_opt, current, next_opt): &mut ChainSnippet) {
         // NOTE: Fields used here should have corresponding line in
         // `Self::remove_in_ui_memory`
@@ -701,6 +1063,20 @@ impl NmdAppEditorRegion {
                 ],
             });
 
+            // Held out of the map while shown, like the selection
+            if let Some(mut physics) = self.state.take_physics(current) {
+                ui_group!(self, ui, "Physics", {
+                    ["Record", "region$editor$physics"],
+                    [
+                        ["a:", physics, parameter_a],
+                        ["b:", physics, parameter_b],
+                        ["c:", physics, parameter_c],
+                    ],
+                });
+
+                self.state.give_physics(physics);
+            }
+
             if prev_opt.is_some() || next_opt.is_some() {
                 ui_group!(self, ui, "Chain", {
                     'if_some: prev_opt {
@@ -730,6 +1106,172 @@ impl NmdAppEditorRegion {
         });
     }
 
+    fn ui_raw_format(&mut self, ui: &mut Ui, id: Id, key: &str, length: usize) -> NmdFileAnnotationFormat {
+        let mut format = self.state.raw_formats.get(key).copied().unwrap_or_default();
+
+        ComboBox::from_id_source(id.with("format"))
+            .selected_text(format.to_string())
+            .width(64.0)
+            .show_ui(ui, |ui|
+        {
+            for format_entry in NmdFileAnnotationFormat::FORMATS {
+                let enabled = format_entry.word_size(length).is_some();
+
+                if ui.add_enabled(enabled, SelectableLabel::new(format == *format_entry, format_entry.to_string()))
+                    .clicked()
+                {
+                    format = *format_entry;
+                }
+            }
+        });
+
+        self.state.raw_formats.insert(key.to_string(), format);
+
+        format
+    }
+
+    /// Show `bytes` in a text input; returns different bytes once some are
+    /// entered and the input loses focus.
+    fn ui_raw_input(&mut self, ui: &mut Ui, id: Id, bytes: &[u8], format: NmdFileAnnotationFormat) -> Option<Vec<u8>> {
+        let byte_order = self.transient_state.version.byte_order();
+        let display_text = format.display(bytes, byte_order);
+        let mut text = match &self.transient_state.raw_input {
+            Some((input_id, text)) if *input_id == id => text.to_owned(),
+            _ => display_text.to_owned(),
+        };
+        let response = ui.add(TextEdit::singleline(&mut text)
+            .id(id)
+            .font(TextStyle::Monospace)
+            .desired_width(ui.available_width()));
+
+        if response.has_focus() {
+            self.transient_state.raw_input = Some((id, text));
+        } else if response.lost_focus() {
+            self.transient_state.raw_input = None;
+
+            // Unreadable input is dropped, and the field shows its value again.
+            // Untouched text isn't read back, as e.g. a NaN wouldn't keep its
+            // bits
+            if text != display_text {
+                return format.parse(&text, bytes.len(), byte_order)
+                    .filter(|new_bytes| new_bytes != bytes);
+            }
+        }
+
+        None
+    }
+
+    fn ui_raw_annotation_rows(&mut self, ui: &mut Ui, id: Id, scope: NmdFileAnnotationScope, range: Range<usize>, bytes: &[u8]) -> Option<Vec<u8>> {
+        let mut new_bytes_opt = None;
+
+        for annotation in (*UiAnnotations).within(scope, range.to_owned()) {
+            let sub_range = annotation.offset - range.start..annotation.range().end - range.start;
+
+            ui.label(RichText::new(format!("{:#04X}", annotation.offset)).monospace().weak());
+            ui.label(format!("↳ {}", annotation.name));
+            ui.label(RichText::new(annotation.format.to_string()).weak());
+
+            if let Some(sub_bytes) = self.ui_raw_input(ui, id.with(&annotation.name), &bytes[sub_range.to_owned()], annotation.format) {
+                let mut new_bytes = bytes.to_vec();
+
+                new_bytes[sub_range].copy_from_slice(&sub_bytes);
+                new_bytes_opt = Some(new_bytes);
+            }
+
+            ui.end_row();
+        }
+
+        new_bytes_opt
+    }
+
+    /// Untokenized header bytes, which belong to the file rather than a bone.
+    fn ui_raw_header_inputs(&mut self, ui: &mut Ui) {
+        if self.state.header_bytes.len() != NmdFileHeader::CHUNK_SIZE as usize {
+            return;
+        }
+
+        ui.group(|ui| {
+            ui.heading("File header");
+            ui.separator();
+            Grid::new(self.uuid().with("editor$raw$header"))
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui|
+            {
+                for range in NmdFileHeader::unparsed_ranges() {
+                    let key = format!("header@{:#04X}", range.start);
+                    let id = self.uuid().with("raw").with(&key);
+                    let bytes = self.state.header_bytes[range.to_owned()].to_vec();
+
+                    ui.label(RichText::new(format!("{:#04X}", range.start)).monospace().weak());
+                    ui.horizontal(|ui| {
+                        ui.label("Untokenized");
+
+                        let unedited_opt = self.state.header_unedited.as_ref()
+                            .map(|header_unedited| header_unedited[range.to_owned()].to_vec())
+                            .filter(|unedited_bytes| *unedited_bytes != bytes);
+
+                        if let Some(unedited_bytes) = unedited_opt {
+                            self.ui_input_edited_mark(ui, &format!("{:?}", unedited_bytes));
+                        }
+                    });
+
+                    let format = self.ui_raw_format(ui, id, &key, range.len());
+                    let mut new_bytes_opt = self.ui_raw_input(ui, id, &bytes, format);
+
+                    ui.end_row();
+
+                    new_bytes_opt = self.ui_raw_annotation_rows(ui, id, NmdFileAnnotationScope::Header, range.to_owned(), &bytes)
+                        .or(new_bytes_opt);
+
+                    if let Some(new_bytes) = new_bytes_opt {
+                        self.set_header_bytes(range, &new_bytes);
+                    }
+                }
+            });
+        });
+    }
+
+    fn ui_raw_inputs(&mut self, ui: &mut Ui, bone_data: &mut NmdFileBone) {
+        ui.group(|ui| {
+            ui.heading("Raw");
+            ui.separator();
+            Grid::new(ui_id!(self, bone_data, #raw))
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui|
+            {
+                for (label, field, token) in Self::RAW_FIELDS {
+                    let value = token.value();
+                    let range = value.offset..value.offset + value.format.size();
+                    let id = self.uuid().with(bone_data.id).with("raw").with(field);
+                    let bytes = Self::raw_field_mut(bone_data, *token).to_vec();
+
+                    ui.label(RichText::new(format!("{:#04X}", range.start)).monospace().weak());
+                    ui.horizontal(|ui| {
+                        ui.label(*label);
+
+                        if let Some(unedited_text) = self.state.unedited_text(bone_data.id, field) {
+                            self.ui_input_edited_mark(ui, &unedited_text);
+                        }
+                    });
+
+                    let format = self.ui_raw_format(ui, id, field, range.len());
+                    let mut new_bytes_opt = self.ui_raw_input(ui, id, &bytes, format);
+
+                    ui.end_row();
+
+                    new_bytes_opt = self.ui_raw_annotation_rows(ui, id, NmdFileAnnotationScope::Bone, range, &bytes)
+                        .or(new_bytes_opt);
+
+                    if let Some(new_bytes) = new_bytes_opt {
+                        self.set_raw_field(bone_data, field, *token, &new_bytes);
+                    }
+                }
+            });
+        });
+    }
+
     fn ui_numeric_input_response(&mut self, ui: &mut Ui, bone_id: u16, field: &'static str, response: InputFieldResponse) {
         let memory = response.memory.lock()
             .unwrap();
@@ -785,10 +1327,16 @@ impl NmdAppRegion for NmdAppEditorRegion {
 
     fn receive_message(&mut self, message: &Message) {
         match message {
+            Message::UiState(UiState::BoneDecoded(id, bone_data))
+                => { self.set_decoded(*id, bone_data); }
             Message::UiState(UiState::BoneFlag(id, flag))
                 => { self.set_flag(*id, *flag); }
+            Message::UiState(UiState::TreeConflictRenumber(record_index, new_id))
+                => { self.renumber(*record_index, *new_id); }
             Message::UiState(UiState::TreeNodeCopyPaste(id_copy_map, parent_id))
                 => { self.state.on_copy_paste(id_copy_map, *parent_id); }
+            Message::UiState(UiState::TreeNodeCreate(created))
+                => { self.state.on_created(created); }
             Message::UiState(UiState::TreeNodeCutPaste(id, new_parent_id))
                 => { self.state.on_cut_paste(*id, *new_parent_id); }
             Message::UiState(UiState::TreeNodeDelete(root_id, removed_ids))
@@ -801,6 +1349,10 @@ impl NmdAppRegion for NmdAppEditorRegion {
         use UiComponent::*;
 
         match ui_component {
+            BoneTableApply(table)
+                => { self.apply_imported(table.bone_ids(), |bone_data| table.apply(bone_data)); }
+            GltfApply(import, _)
+                => { self.apply_imported(import.bone_ids(), |bone_data| import.apply(bone_data)); }
             MenuCommit
                 => { self.commit(); }
             TreeNode(bone_id, _)
@@ -812,6 +1364,7 @@ impl NmdAppRegion for NmdAppEditorRegion {
     fn ui(&mut self, ctx: &Context) {
         self.handle_keys(ctx);
         self.remove_in_ui_memory_if_pending(ctx);
+        self.ui_orphans(ctx);
 
         if self.state.has_selection() {
             self.with_selection(|region, chain_snippet| {
@@ -830,13 +1383,21 @@ impl NmdAppRegion for NmdAppEditorRegion {
                         region.ui_header(ui, chain_snippet.current_mut());
                         ui.separator();
                         region.ui_numeric_inputs(ui, chain_snippet);
+                        ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
+                            region.ui_raw_inputs(ui, chain_snippet.current_mut());
+                            region.ui_raw_header_inputs(ui);
+                        });
                     });
                 });
             });
         } else {
             CentralPanel::default()
                 .show(ctx, |ui|
-            {});
+            {
+                ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
+                    self.ui_raw_header_inputs(ui);
+                });
+            });
         }
     }
 
@@ -864,16 +1425,21 @@ impl NmdAppEditorProjectState {
         self.map.get(&bone_id)
     }
 
+    /// Every bone, including the selection.
+    fn iter_all(&self) -> impl Iterator<Item = &NmdFileBone> {
+        self.map
+            .values()
+            .chain(self.selection.iter().flat_map(|ChainSnippet (prev_opt, current, next_opt)| {
+                prev_opt.iter().chain(iter::once(current)).chain(next_opt.iter())
+            }))
+    }
+
     fn get_mut(&mut self, bone_id: u16) -> Option<&mut NmdFileBone> {
         self.map.get_mut(&bone_id)
     }
 
     fn get_parent_id(&self, bone_id: u16) -> Option<u16> {
-        if let Some(bone_data) = self.get(bone_id) {
-            Some(bone_data.parent_id)
-        } else {
-            None
-        }
+        self.get(bone_id).map(|bone_data| bone_data.parent_id)
     }
 
     /// Find a candidate for the next bone in a new chain. This is inefficient.
@@ -918,6 +1484,17 @@ impl NmdAppEditorProjectState {
         self.selection.as_ref()?.prev()
     }
 
+    /// Bones whose parent ID matches no bone, with ranked parent suggestions.
+    fn orphans(&self) -> Vec<(&NmdFileBone, Vec<NmdFileParentSuggestion>)> {
+        let ids: HashSet<u16> = self.iter_all().map(|bone_data| bone_data.id).collect();
+
+        self.iter_all()
+            .filter(|bone_data| bone_data.parent_id != NmdFileBone::ROOT_BONE_ID
+                                && !ids.contains(&bone_data.parent_id))
+            .map(|orphan| (orphan, NmdFileParentSuggestion::for_orphan(self.iter_all(), orphan.id)))
+            .collect()
+    }
+
     // ### Cat.: Memory
 
     fn insert_memory(&mut self, bone_id: u16, field: &str, field_memory: &String) {
@@ -997,6 +1574,10 @@ impl NmdAppEditorProjectState {
         }
     }
 
+    fn give_physics(&mut self, physics: NmdFilePhysics) {
+        self.physics.insert(physics.id, physics);
+    }
+
     fn give_selection(&mut self, chain_snippet: ChainSnippet) {
         self.selection = Some(chain_snippet);
     }
@@ -1051,6 +1632,13 @@ impl NmdAppEditorProjectState {
         }
     }
 
+    // Only a physics bone's record is shown
+    fn take_physics(&mut self, bone_data: &NmdFileBone) -> Option<NmdFilePhysics> {
+        bone_data.is_phys()
+            .then(|| self.physics.remove(&bone_data.id))
+            .flatten()
+    }
+
     fn take_selection(&mut self) -> Option<ChainSnippet> {
         mem::take(&mut self.selection)
     }
@@ -1097,6 +1685,11 @@ impl NmdAppEditorProjectState {
         self.memory.retain(|id, _| !ids.contains(id));
     }
 
+    #[inline]
+    fn delete_in_physics(&mut self, ids: &HashSet<u16>) {
+        self.physics.retain(|id, _| !ids.contains(id));
+    }
+
     fn on_copy_paste(&mut self, id_copy_map: &HashMap<u16, (u16, String)>, parent_id: u16) {
         self.without_selection(|state| {
             state.on_copy_paste_internal(id_copy_map, parent_id);
@@ -1112,7 +1705,7 @@ impl NmdAppEditorProjectState {
                 target.name = name.to_owned();
                 target.id = *target_id;
                 target.parent_id = *id_copy_map.get(&target.parent_id)
-                    .and_then(|(id, _)| Some(id))
+                    .map(|(id, _)| id)
                     .unwrap_or(&parent_id);
 
                 if target.parent_id == parent_id && target.is_phys() {
@@ -1124,6 +1717,12 @@ impl NmdAppEditorProjectState {
                     }
                 }
 
+                // Its own record, which the writer gives a fresh address
+                if let Some(mut physics) = self.physics.get(source_id).cloned() {
+                    physics.id = *target_id;
+                    self.physics.insert(physics.id, physics);
+                }
+
                 self.map.insert(target.id, target);
             }
 
@@ -1135,6 +1734,22 @@ impl NmdAppEditorProjectState {
         }
     }
 
+    fn on_created(&mut self, created: &Vec<(NmdFileBone, Option<NmdFilePhysics>)>) {
+        self.without_selection(|state| {
+            for (bone_data, physics_opt) in created {
+                if bone_data.is_phys() && state.chain_eligible(bone_data.parent_id) {
+                    state.chains.insert(bone_data.parent_id, bone_data.id);
+                }
+
+                if let Some(physics) = physics_opt {
+                    state.physics.insert(bone_data.id, physics.to_owned());
+                }
+
+                state.map.insert(bone_data.id, bone_data.to_owned());
+            }
+        });
+    }
+
     fn on_cut_paste(&mut self, bone_id: u16, new_parent_id: u16) {
         self.without_selection(|state| {
             state.on_cut_paste_internal(bone_id, new_parent_id);
@@ -1166,6 +1781,7 @@ impl NmdAppEditorProjectState {
             state.delete_in_chains(ids);
             state.delete_in_map(ids);
             state.delete_in_memory(ids);
+            state.delete_in_physics(ids);
 
             if let Some(parent_id) = parent_id_opt {
                 state.rechain_maybe(parent_id);
@@ -1173,6 +1789,24 @@ impl NmdAppEditorProjectState {
         });
     }
 
+    /// Move a conflicting record into the map under a new ID, returning the ID
+    /// it had.
+    fn on_renumbered(&mut self, record_index: usize, new_id: u16) -> Option<u16> {
+        let mut bone_data = self.conflicts.remove(&record_index)?;
+        let old_id = mem::replace(&mut bone_data.id, new_id);
+        let parent_id = bone_data.parent_id;
+
+        if let Some(mut physics) = self.conflict_physics.remove(&record_index) {
+            physics.id = new_id;
+            self.physics.insert(new_id, physics);
+        }
+
+        self.map.insert(new_id, bone_data);
+        self.rechain_maybe(parent_id);
+
+        Some(old_id)
+    }
+
     fn on_flag_changed(&mut self, bone_id: u16, flag_from: NmdFileBoneFlag, flag_to: NmdFileBoneFlag) {
         match (flag_from.is_phys(), flag_to.is_phys()) {
             (true, false) => {
@@ -1232,8 +1866,14 @@ impl From<&NmdFileData> for NmdAppEditorProjectState {
 
                 None
             }).collect(),
+            conflicts: data.conflicts.to_owned(),
+            conflict_physics: data.conflict_physics.to_owned(),
+            header_bytes: data.raw_header().to_vec(),
+            header_unedited: None,
             map: data.bones.to_owned(),
             memory: Default::default(),
+            physics: data.physics.to_owned(),
+            raw_formats: Default::default(),
             selected_id: None,
             selection: None,
         }
@@ -0,0 +1,210 @@
+use {
+    crate::{
+        io::{
+            fifo::{
+                Message,
+                MessageSender,
+            },
+            nmd::{
+                NmdFileBoneTableChange,
+                NmdFileGltfImport,
+            },
+        },
+        ui::{
+            region,
+            region::{
+                bone_table,
+                NmdAppRegion,
+            },
+            UiColor,
+            UiComponent,
+        },
+    },
+    eframe::egui::*,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::{
+        collections::BTreeSet,
+        path::PathBuf,
+    },
+};
+
+/// Preview of the placements a glTF skeleton would set, and of the nodes it
+/// has no bone for, any of which can be made into new bones on applying.
+#[derive(Serialize, Deserialize)]
+pub struct NmdAppGltfRegion {
+    #[serde(skip)]
+    message_sender: Option<MessageSender>,
+    // Open while there's something to show
+    #[serde(skip)]
+    preview_opt: Option<GltfPreview>,
+    uuid_source: u64,
+}
+
+struct GltfPreview {
+    path: PathBuf,
+    // The matched skeleton, or why it couldn't be read
+    import_result: Result<NmdFileGltfImport, String>,
+    changes: Vec<NmdFileBoneTableChange>,
+    unmatched_bones: Vec<(u16, String)>,
+    // Unmatched nodes ticked to be made into bones
+    creating: BTreeSet<usize>,
+}
+
+impl NmdAppGltfRegion {
+    pub fn new(message_sender: &MessageSender) -> Self {
+        let mut region = Self::default();
+
+        region.emit_with(message_sender);
+        region
+    }
+
+    fn emit_focus(&self, bone_id: u16, name: &String) {
+        self.emit(Message::UiSelect(UiComponent::TreeNodeFocus(bone_id, name.to_owned())));
+    }
+
+    pub fn emit_with(&mut self, message_sender: &MessageSender) {
+        self.message_sender = Some(message_sender.to_owned());
+    }
+
+    /// Show what a skeleton read from `path` would change, with the changes
+    /// and unmatched bones worked out against the bones being edited.
+    pub fn preview(&mut self, path: &PathBuf, import_result: Result<NmdFileGltfImport, String>, changes: Vec<NmdFileBoneTableChange>, unmatched_bones: Vec<(u16, String)>) {
+        self.preview_opt = Some(GltfPreview {
+            path: path.to_owned(),
+            import_result: import_result,
+            changes: changes,
+            unmatched_bones: unmatched_bones,
+            creating: BTreeSet::new(),
+        });
+    }
+
+    fn ui_changes(&self, ui: &mut Ui, preview: &GltfPreview) {
+        if preview.changes.is_empty() {
+            ui.label(RichText::new("No placements differ from the bones being edited").weak());
+        } else if let Some((bone_id, name)) = bone_table::ui_change_grid(ui, self.uuid().with("gltf$grid"), &preview.changes) {
+            self.emit_focus(bone_id, &name);
+        }
+    }
+
+    fn ui_unmatched_bones(&self, ui: &mut Ui, preview: &GltfPreview) {
+        if !preview.unmatched_bones.is_empty() {
+            let bones: Vec<String> = preview.unmatched_bones.iter()
+                .map(|(bone_id, name)| format!("{} ({:#06X})", name, bone_id))
+                .collect();
+
+            ui.label(RichText::new(format!("Left as they are, with no node by their name: {}", bones.join(", "))).weak());
+        }
+    }
+
+    fn ui_unmatched_nodes(ui: &mut Ui, id: Id, import: &NmdFileGltfImport, creating: &mut BTreeSet<usize>) {
+        let unmatched_nodes = import.unmatched_nodes();
+
+        if unmatched_nodes.is_empty() {
+            return;
+        }
+
+        ui.label(format!("{} nodes have no bone by their name:", unmatched_nodes.len()));
+
+        ScrollArea::vertical()
+            .id_source(id.with("scroll"))
+            .max_height(160.0)
+            .show(ui, |ui|
+        {
+            for node_index in unmatched_nodes {
+                let mut ticked = creating.contains(node_index);
+                let parent_name = import.parent_node(*node_index)
+                    .map(|parent_index| import.node_name(parent_index))
+                    .unwrap_or_else(|| String::from("the root"));
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(import.creatable(*node_index), Checkbox::new(&mut ticked, import.node_name(*node_index)))
+                        .on_hover_text("Make a new bone of this node on applying")
+                        .on_disabled_hover_text("Its flag calls for a physics record, which needs physics parameters in the node's extras and a bone with the same flag and a physics record here to copy the rest from")
+                        .changed()
+                    {
+                        if ticked {
+                            creating.insert(*node_index);
+                        } else {
+                            creating.remove(node_index);
+                        }
+                    }
+
+                    ui.label(RichText::new(format!("under {}", parent_name)).weak());
+                });
+            }
+        });
+    }
+}
+
+impl Default for NmdAppGltfRegion {
+    fn default() -> Self {
+        Self {
+            message_sender: None,
+            preview_opt: None,
+            uuid_source: region::generate_uuid_source(),
+        }
+    }
+}
+
+impl NmdAppRegion for NmdAppGltfRegion {
+    fn message_sender(&self) -> Option<&MessageSender> {
+        self.message_sender.as_ref()
+    }
+
+    fn ui(&mut self, ctx: &Context) {
+        let mut preview = match self.preview_opt.take() {
+            Some(preview) => preview,
+            None => return,
+        };
+        let mut open = true;
+        let mut closing = false;
+
+        Window::new("Import placements from glTF")
+            .id(self.uuid().with("gltf"))
+            .open(&mut open)
+            .default_width(560.0)
+            .show(ctx, |ui|
+        {
+            ui.label(RichText::new(preview.path.display().to_string()).weak());
+            ui.separator();
+
+            let import = match &preview.import_result {
+                Ok(import) => import,
+                Err(error) => {
+                    ui.label(RichText::new(error).color(UiColor.editor.error.normal()));
+                    return;
+                }
+            };
+
+            self.ui_changes(ui, &preview);
+            self.ui_unmatched_bones(ui, &preview);
+            ui.separator();
+            Self::ui_unmatched_nodes(ui, self.uuid().with("gltf$nodes"), import, &mut preview.creating);
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let applicable = !preview.changes.is_empty() || !preview.creating.is_empty();
+
+                if ui.add_enabled(applicable, Button::new("Apply")).clicked() {
+                    self.emit(Message::UiSelect(UiComponent::GltfApply(Box::new(import.to_owned()), preview.creating.to_owned())));
+                    closing = true;
+                }
+
+                if ui.button("Cancel").clicked() {
+                    closing = true;
+                }
+            });
+        });
+
+        if open && !closing {
+            self.preview_opt = Some(preview);
+        }
+    }
+
+    fn uuid_source(&self) -> u64 {
+        self.uuid_source
+    }
+}
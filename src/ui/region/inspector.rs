@@ -0,0 +1,387 @@
+use {
+    crate::{
+        io::{
+            fifo::{
+                Message,
+                MessageSender,
+            },
+            nmd::{
+                anatomy::{
+                    token::NmdFileToken,
+                    NmdFileBone,
+                },
+                data::ByteVec,
+                NmdFileBlock,
+                NmdFileMap,
+                NmdFileReader,
+            },
+        },
+        ui::{
+            region,
+            region::NmdAppRegion,
+            UiAnnotations,
+            UiColor,
+            UiComponent,
+            UiState,
+        },
+    },
+    std::ops::Range,
+    eframe::egui::*,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+/// The bytes of the file a project was imported from, marked with what each
+/// range holds. Bytes of bone chunks and bone names can be edited, after which
+/// the bone is read again and handed to the editor.
+#[derive(Serialize, Deserialize)]
+pub struct NmdAppInspectorRegion {
+    // As imported, plus edits made here; empty for projects saved before the
+    // inspector existed
+    #[serde(default)]
+    bytes: ByteVec,
+    // Recomputed from the bytes, so not kept in projects
+    #[serde(skip)]
+    map_opt: Option<NmdFileMap>,
+    #[serde(skip)]
+    message_sender: Option<MessageSender>,
+    #[serde(skip)]
+    transient_state: NmdAppInspectorTransientState,
+    uuid_source: u64,
+}
+
+#[derive(Default)]
+struct NmdAppInspectorTransientState {
+    byte_input: String,
+    // Chunk of the bone selected in the tree
+    highlight: Option<Range<usize>>,
+    map_error: Option<String>,
+    open: bool,
+    scroll_row: Option<usize>,
+    selected: Option<usize>,
+}
+
+impl NmdAppInspectorRegion {
+    const ROW_LENGTH: usize = 16;
+
+    pub fn new(message_sender: &MessageSender, bytes: ByteVec) -> Self {
+        let mut region = Self::default();
+
+        region.emit_with(message_sender);
+        region.bytes = bytes;
+        region.remap();
+        region
+    }
+
+    fn block_color(block_opt: Option<&NmdFileBlock>) -> Color32 {
+        use NmdFileToken::*;
+
+        match block_opt {
+            Some(NmdFileBlock::Header(_))
+                => UiColor.inspector.header.normal(),
+            Some(NmdFileBlock::Bone(_, _, BoneUnknownDataA | BoneUnknownDataB | BoneUnknownDataC
+                                          | BoneUnknownDataD | BoneUnknownDataE | BoneUnknownDataF))
+                => UiColor.inspector.bone_unknown.normal(),
+            Some(NmdFileBlock::Bone(..))
+                => UiColor.inspector.bone.normal(),
+            Some(NmdFileBlock::Physics(_, Some(_)))
+                => UiColor.inspector.physics.normal(),
+            Some(NmdFileBlock::Physics(_, None))
+                => UiColor.inspector.physics.dim(),
+            Some(NmdFileBlock::Blob(_))
+                => UiColor.inspector.blob.normal(),
+            Some(NmdFileBlock::BoneName(_))
+                => UiColor.inspector.bone_name.normal(),
+            None
+                => UiColor.common.weak_gray.normal(),
+        }
+    }
+
+    // What the byte at `offset` holds, by its annotated name too if it has one
+    fn block_text(&self, offset: usize) -> Option<String> {
+        let map = self.map_opt.as_ref()?;
+        let block_text_opt = map.span_at(offset).map(|span| span.block.to_string());
+
+        match (block_text_opt, map.annotation_at(&UiAnnotations, offset)) {
+            (Some(block_text), Some(annotation))    => Some(format!("{} ({})", block_text, annotation.name)),
+            (Some(block_text), None)                => Some(block_text),
+            (None, Some(annotation))                => Some(format!("header, {}", annotation.name)),
+            (None, None)                            => None,
+        }
+    }
+
+    /// Records to read again once the byte at `offset` is edited, or why it
+    /// can't be edited here.
+    fn edit_targets(&self, offset: usize) -> Result<Vec<(usize, u16)>, &'static str> {
+        use NmdFileToken::*;
+
+        let map = self.map_opt.as_ref().ok_or("the file couldn't be mapped")?;
+        // Later records sharing an ID aren't in the editor
+        let is_first = |index: usize, bone_id: u16| map.record_index(bone_id) == Some(index);
+
+        match map.span_at(offset).map(|span| &span.block) {
+            Some(NmdFileBlock::Bone(_, _, BoneId | BoneParentId))
+                => Err("IDs and parents are changed in the tree"),
+            Some(NmdFileBlock::Bone(_, _, BoneNameAddress | BonePhysicsDataAddress))
+                => Err("addresses are worked out on export"),
+            Some(NmdFileBlock::Bone(index, bone_id, _)) if is_first(*index, *bone_id)
+                => Ok(vec![(*index, *bone_id)]),
+            Some(NmdFileBlock::Bone(..))
+                => Err("this record shares its ID with an earlier one"),
+            Some(NmdFileBlock::BoneName(indices)) => {
+                let targets: Vec<_> = indices.iter()
+                    .map(|index| (*index, map.records()[*index].id))
+                    .filter(|(index, bone_id)| is_first(*index, *bone_id))
+                    .collect();
+
+                match targets.is_empty() {
+                    true  => Err("this name belongs to records sharing an ID with an earlier one"),
+                    false => Ok(targets),
+                }
+            }
+            _   => Err("only bone chunks and bone names can be edited"),
+        }
+    }
+
+    pub fn emit_with(&mut self, message_sender: &MessageSender) {
+        self.message_sender = Some(message_sender.to_owned());
+    }
+
+    fn jump_to_bone(&mut self, bone_id: u16) {
+        if let Some(map) = &self.map_opt {
+            let chunk_opt = map.record_index(bone_id).map(|index| map.bone_chunk(index));
+
+            if let Some(chunk) = &chunk_opt {
+                self.transient_state.scroll_row = Some(chunk.start / Self::ROW_LENGTH);
+            }
+
+            self.transient_state.highlight = chunk_opt;
+        }
+    }
+
+    /// Lay out the bytes again, e.g. once a project has been deserialized.
+    pub fn remap(&mut self) {
+        if self.bytes.is_empty() {
+            self.map_opt = None;
+            self.transient_state.map_error = None;
+            return;
+        }
+
+        match NmdFileMap::try_from(&self.bytes[..]) {
+            Ok(map) => {
+                self.map_opt = Some(map);
+                self.transient_state.map_error = None;
+            }
+            Err(error) => {
+                self.map_opt = None;
+                self.transient_state.map_error = Some(error.to_string());
+            }
+        }
+    }
+
+    fn select_byte(&mut self, offset: usize) {
+        self.transient_state.selected = Some(offset);
+        self.transient_state.byte_input = format!("{:02X}", self.bytes[offset]);
+    }
+
+    fn set_byte(&mut self, offset: usize, byte: u8) {
+        let targets = match self.edit_targets(offset) {
+            Ok(targets) => targets,
+            Err(_) => return,
+        };
+
+        if self.bytes[offset] == byte {
+            return;
+        }
+
+        self.bytes[offset] = byte;
+        self.remap();
+
+        if let Ok(mut reader) = NmdFileReader::try_from(self.bytes.to_owned()) {
+            for (index, bone_id) in targets {
+                if let Ok(bone_data) = reader.read_nth_bone(index) {
+                    self.emit(Message::UiState(UiState::BoneDecoded(bone_id, bone_data)));
+                }
+            }
+        }
+    }
+
+    // Column of characters, with names shown unshifted
+    fn text_column(&self, range: Range<usize>) -> String {
+        let name_start = self.map_opt.as_ref()
+            .map_or(usize::MAX, |map| map.header().bone_name_data_address as usize);
+
+        range.map(|offset| {
+            let byte = match offset >= name_start {
+                true  => self.bytes[offset].wrapping_sub(NmdFileBone::ASCII_BYTE_SHIFT),
+                false => self.bytes[offset],
+            };
+
+            match byte.is_ascii_graphic() {
+                true  => char::from(byte),
+                false => '.',
+            }
+        }).collect()
+    }
+
+    fn ui_byte(&mut self, ui: &mut Ui, offset: usize) {
+        let block_opt = self.map_opt.as_ref()
+            .and_then(|map| map.span_at(offset))
+            .map(|span| &span.block);
+        let hover_text = match self.block_text(offset) {
+            Some(block_text) => format!("{:#010X}: {}", offset, block_text),
+            None => format!("{:#010X}", offset),
+        };
+        let mut text = RichText::new(format!("{:02X}", self.bytes[offset]))
+            .monospace()
+            .color(Self::block_color(block_opt));
+
+        if self.transient_state.selected == Some(offset) {
+            text = text.background_color(UiColor.inspector.selected.normal());
+        } else if self.transient_state.highlight.as_ref().is_some_and(|chunk| chunk.contains(&offset)) {
+            text = text.background_color(UiColor.inspector.highlighted.normal());
+        }
+
+        if ui.add(Label::new(text).sense(Sense::click()))
+            .on_hover_text_at_pointer(hover_text)
+            .clicked()
+        {
+            self.select_byte(offset);
+        }
+    }
+
+    fn ui_rows(&mut self, ui: &mut Ui) {
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+        let row_count = self.bytes.len().div_ceil(Self::ROW_LENGTH);
+        let mut scroll_area = ScrollArea::vertical()
+            .id_source(self.uuid().with("inspector$rows"))
+            .auto_shrink([false; 2]);
+
+        if let Some(row) = self.transient_state.scroll_row.take() {
+            scroll_area = scroll_area.vertical_scroll_offset(row as f32 * (row_height + ui.spacing().item_spacing.y));
+        }
+
+        scroll_area.show_rows(ui, row_height, row_count, |ui, rows| {
+            for row in rows {
+                let start = row * Self::ROW_LENGTH;
+                let end = (start + Self::ROW_LENGTH).min(self.bytes.len());
+
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("{:08X}", start)).monospace().weak());
+                    ui.add_space(ui.spacing().item_spacing.x);
+
+                    for offset in start..end {
+                        self.ui_byte(ui, offset);
+                    }
+
+                    ui.add_space(ui.spacing().item_spacing.x);
+                    ui.label(RichText::new(self.text_column(start..end)).monospace().weak());
+                });
+            }
+        });
+    }
+
+    fn ui_selection(&mut self, ui: &mut Ui) {
+        let offset = match self.transient_state.selected {
+            Some(offset) if offset < self.bytes.len() => offset,
+            _ => {
+                ui.label(RichText::new("Click a byte to see what it holds").weak());
+                return;
+            }
+        };
+        let block_text = self.block_text(offset)
+            .unwrap_or_else(|| String::from("outside any known block"));
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(format!("{:#010X}", offset)).monospace());
+            ui.label(block_text);
+        });
+
+        ui.horizontal(|ui| {
+            match self.edit_targets(offset) {
+                Ok(_) => {
+                    ui.label("Value:");
+
+                    let response = ui.add(TextEdit::singleline(&mut self.transient_state.byte_input)
+                        .font(TextStyle::Monospace)
+                        .desired_width(24.0));
+
+                    if response.lost_focus() && ui.input().key_pressed(Key::Enter) {
+                        if let Ok(byte) = u8::from_str_radix(self.transient_state.byte_input.trim(), 16) {
+                            self.set_byte(offset, byte);
+                        }
+
+                        self.select_byte(offset);
+                    }
+                }
+                Err(reason) => {
+                    ui.label(RichText::new(format!("Read-only: {}", reason)).weak());
+                }
+            }
+        });
+    }
+}
+
+// For projects saved before the inspector existed
+impl Default for NmdAppInspectorRegion {
+    fn default() -> Self {
+        Self {
+            bytes: vec!(),
+            map_opt: None,
+            message_sender: None,
+            transient_state: Default::default(),
+            uuid_source: region::generate_uuid_source(),
+        }
+    }
+}
+
+impl NmdAppRegion for NmdAppInspectorRegion {
+    fn message_sender(&self) -> Option<&MessageSender> {
+        self.message_sender.as_ref()
+    }
+
+    fn select(&mut self, ui_component: &UiComponent) {
+        match ui_component {
+            UiComponent::MenuHexInspector
+                => { self.transient_state.open = !self.transient_state.open; }
+            UiComponent::TreeNode(bone_id, _)
+                => { self.jump_to_bone(*bone_id); }
+            _   => {}
+        }
+    }
+
+    fn ui(&mut self, ctx: &Context) {
+        let mut open = self.transient_state.open;
+
+        Window::new("Hex inspector")
+            .id(self.uuid().with("inspector"))
+            .open(&mut open)
+            .default_size([640.0, 420.0])
+            .show(ctx, |ui|
+        {
+            if self.bytes.is_empty() {
+                ui.label(RichText::new("This project has no file bytes; import the file again to inspect it").weak());
+                return;
+            }
+
+            if let Some(map_error) = &self.transient_state.map_error {
+                ui.label(RichText::new(format!("Can't lay out the file: {}", map_error))
+                    .color(UiColor.editor.error.normal()));
+            }
+
+            ui.label(RichText::new("Bytes as imported. Edits made here go to the editor; edits made in the editor don't show here.").weak());
+            ui.separator();
+            self.ui_selection(ui);
+            ui.separator();
+            self.ui_rows(ui);
+        });
+
+        self.transient_state.open = open;
+    }
+
+    fn uuid_source(&self) -> u64 {
+        self.uuid_source
+    }
+}
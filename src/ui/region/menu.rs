@@ -3,6 +3,8 @@ use eframe::egui::*;
 use {
     crate::{
         io::{
+            nmd::anatomy::NmdFileVersion,
+            nmd::NmdFileBoneOrder,
             utils as io_utils,
             fifo::{
                 Message,
@@ -14,7 +16,10 @@ use {
     },
     std::cmp::Ordering,
     std::ffi::OsStr,
-    std::path::PathBuf,
+    std::path::{
+        Path,
+        PathBuf,
+    },
     eframe::egui::*,
     eframe::egui::text::LayoutJob,
 };
@@ -25,6 +30,9 @@ pub enum MenuTab {
 }
 
 pub struct NmdAppMenuRegion {
+    // Whether exports copy the file they replace aside first
+    backing_up_exports: bool,
+    export_bone_order: NmdFileBoneOrder,
     message_sender: Option<MessageSender>,
     state: NmdAppMenuProjectState,
 }
@@ -37,15 +45,20 @@ struct NmdAppMenuProjectState {
 
 pub struct MenuTabData {
     edited: bool,
+    // Games the tab's file can be exported for
+    export_versions: Vec<NmdFileVersion>,
     hiding_ids: bool,
     name: String,
     path: PathBuf,
+    version: NmdFileVersion,
     view: MenuTabView,
 }
 
 impl NmdAppMenuRegion {
     pub fn new(message_sender: &MessageSender) -> Self {
         Self {
+            backing_up_exports: true,
+            export_bone_order: NmdFileBoneOrder::default(),
             message_sender: Some(message_sender.to_owned()),
             state: Default::default(),
         }
@@ -77,6 +90,14 @@ impl NmdAppMenuRegion {
         self.emit(Message::UiSelect(UiComponent::MenuTabClose(index)));
     }
 
+    fn emit_hex_inspector(&self) {
+        self.emit(Message::UiSelect(UiComponent::MenuHexInspector));
+    }
+
+    fn emit_validation_report(&self) {
+        self.emit(Message::UiSelect(UiComponent::MenuValidationReport));
+    }
+
     fn handle_keys(&self, ctx: &Context) {
         let mut input_state = ctx.input_mut();
 
@@ -98,10 +119,45 @@ impl NmdAppMenuRegion {
         self.state.in_project_tab()
     }
 
-    fn on_clicked_export(&self) {
+    fn on_clicked_bone_table_export(&self) {
+        if let Some(message_sender) = self.message_sender().cloned() {
+            io_utils::save_file("/", &[("CSV File", &["csv"])], move |path| {
+                message_sender.send(Message::UiSelect(UiComponent::MenuBoneTableExport(path)));
+            });
+        }
+    }
+
+    fn on_clicked_bone_table_import(&self) {
+        if let Some(message_sender) = self.message_sender().cloned() {
+            io_utils::open_file("/", &[("CSV File", &["csv"])], move |path| {
+                message_sender.send(Message::UiSelect(UiComponent::MenuBoneTableImport(path)));
+            });
+        }
+    }
+
+    fn on_clicked_export(&self, version: NmdFileVersion) {
+        let backing_up = self.backing_up_exports;
+        let bone_order = self.export_bone_order;
+
         if let Some(message_sender) = self.message_sender().cloned() {
             io_utils::save_file("/", &[("NMD File", &["nmd"])], move |path| {
-                message_sender.send(Message::UiSelect(UiComponent::MenuExport(path)));
+                message_sender.send(Message::UiSelect(UiComponent::MenuExport(path, version, backing_up, bone_order)));
+            });
+        }
+    }
+
+    fn on_clicked_gltf_export(&self) {
+        if let Some(message_sender) = self.message_sender().cloned() {
+            io_utils::save_file("/", &[("glTF File", &["gltf"])], move |path| {
+                message_sender.send(Message::UiSelect(UiComponent::MenuGltfExport(path)));
+            });
+        }
+    }
+
+    fn on_clicked_gltf_import(&self) {
+        if let Some(message_sender) = self.message_sender().cloned() {
+            io_utils::open_file("/", &[("glTF File", &["gltf"])], move |path| {
+                message_sender.send(Message::UiSelect(UiComponent::MenuGltfImport(path)));
             });
         }
     }
@@ -193,24 +249,71 @@ impl NmdAppMenuRegion {
                         ui.separator();
                     });
 
-                    ui.scope(|ui| {
-                        ui.set_enabled(false /* WIP */);
+                    let tab_version_opt = self.state.current_tab().map(MenuTab::version);
+                    let export_versions = self.state.current_tab()
+                        .map(|tab| tab.export_versions().to_owned())
+                        .unwrap_or_default();
 
-                        if ui.button("SCIV").clicked() {
-                            ui.close_menu();
-                        }
+                    for version in NmdFileVersion::VERSIONS {
+                        let label = if tab_version_opt == Some(*version) {
+                            format!("{} (detected)", version)
+                        } else {
+                            version.to_string()
+                        };
+
+                        if ui.add_enabled(export_versions.contains(version), Button::new(label))
+                            .on_disabled_hover_text("Some physics or blob bytes aren't decoded and are copied as read, so can't be written in this game's byte order")
+                            .clicked()
+                        {
+                            self.on_clicked_export(*version);
 
-                        if ui.button("SCV").clicked() {
                             ui.close_menu();
                         }
-                    });
-
-                    if ui.button("SCVI").clicked() {
-                        self.on_clicked_export();
-
-                        ui.close_menu();
                     }
+
+                    ui.separator();
+                    ui.radio_value(&mut self.export_bone_order, NmdFileBoneOrder::Original, "Original order")
+                        .on_hover_text("Bones as read, with new bones appended");
+                    ui.radio_value(&mut self.export_bone_order, NmdFileBoneOrder::ById, "Sorted by ID");
+                    ui.separator();
+                    ui.checkbox(&mut self.backing_up_exports, "Back up replaced file")
+                        .on_hover_text("Copy a file being exported over to <name>.<seconds since 1970>.bak first");
                 });
+
+                if ui.button("Export skeleton as glTF…")
+                    .on_hover_text("The bone hierarchy as glTF 2.0 nodes, e.g. for Blender")
+                    .clicked()
+                {
+                    ui.close_menu();
+
+                    self.on_clicked_gltf_export();
+                }
+
+                if ui.button("Import placements from glTF…")
+                    .on_hover_text("Preview the placements that differ, matching nodes to bones by name, then apply them as edits")
+                    .clicked()
+                {
+                    ui.close_menu();
+
+                    self.on_clicked_gltf_import();
+                }
+
+                ui.separator();
+
+                if ui.button("Export bone table as CSV…").clicked() {
+                    ui.close_menu();
+
+                    self.on_clicked_bone_table_export();
+                }
+
+                if ui.button("Import bone table from CSV…")
+                    .on_hover_text("Preview the cells that differ, then apply them as edits")
+                    .clicked()
+                {
+                    ui.close_menu();
+
+                    self.on_clicked_bone_table_import();
+                }
             });
 
             ui.add_space(2.0);
@@ -406,7 +509,7 @@ impl NmdAppMenuRegion {
                         hover_text.append("Target: ",
                             0.0, TextFormat::default());
                         hover_text.append(&path_to_string(path),
-                            0.0, TextFormat { color: (*UiColor).common.weak_gray.normal(), ..Default::default() });
+                            0.0, TextFormat { color: UiColor.common.weak_gray.normal(), ..Default::default() });
 
                         ui.label(hover_text);
                     });
@@ -469,6 +572,20 @@ impl NmdAppMenuRegion {
 
                 ui.close_menu();
             }
+
+            ui.separator();
+
+            if ui.add_enabled(self.state.in_tab(), Button::new("Validation report")).clicked() {
+                self.emit_validation_report();
+
+                ui.close_menu();
+            }
+
+            if ui.add_enabled(self.state.in_tab(), Button::new("Hex inspector")).clicked() {
+                self.emit_hex_inspector();
+
+                ui.close_menu();
+            }
         });
     }
 
@@ -478,9 +595,7 @@ impl NmdAppMenuRegion {
 
     fn ui_tabs(&mut self, ui: &mut Ui) {
         if let Some(mut tab_index) = self.state.tab_index_opt.to_owned() {
-            let mut i = 0;
-
-            for tab in &self.state.tabs {
+            for (i, tab) in self.state.tabs.iter().enumerate() {
                 let tab_button;
 
                 if i > 0 {
@@ -489,7 +604,7 @@ impl NmdAppMenuRegion {
 
                 tab_button = ui.selectable_value(&mut tab_index, i, tab.name())
                     .on_hover_cursor(CursorIcon::PointingHand)
-                    .on_hover_ui_at_pointer(|ui| { ui.label(&path_to_string(tab.path())); })
+                    .on_hover_ui_at_pointer(|ui| { ui.label(path_to_string(tab.path())); })
                     .context_menu(|ui|
                 {
                     if ui.button("Close").clicked() {
@@ -502,15 +617,13 @@ impl NmdAppMenuRegion {
                 if tab_button.clicked() {
                     self.emit_tab(i);
                 }
-
-                i += 1;
             }
         }
     }
 
     fn ui_tabs_style(&self, ui: &mut Ui) {
         ui.spacing_mut().button_padding.y = 6.0;
-        ui.visuals_mut().selection.bg_fill = (*UiColor).menu.selected_tab.normal();
+        ui.visuals_mut().selection.bg_fill = UiColor.menu.selected_tab.normal();
     }
 }
 
@@ -648,6 +761,10 @@ impl MenuTab {
         self.data().edited
     }
 
+    fn export_versions(&self) -> &Vec<NmdFileVersion> {
+        &self.data().export_versions
+    }
+
     fn hiding_ids(&self) -> bool {
         self.data().hiding_ids
     }
@@ -664,7 +781,9 @@ impl MenuTab {
         let mut tab = Self::for_project(path);
 
         tab.set_edited(self.edited());
+        tab.set_export_versions(self.export_versions().to_owned());
         tab.set_hiding_ids(self.hiding_ids());
+        tab.set_version(self.version());
         tab.set_view(self.view());
         tab
     }
@@ -685,14 +804,26 @@ impl MenuTab {
         self.data_mut().edited = edited;
     }
 
+    pub fn set_export_versions(&mut self, export_versions: Vec<NmdFileVersion>) {
+        self.data_mut().export_versions = export_versions;
+    }
+
     pub fn set_hiding_ids(&mut self, hiding_ids: bool) {
         self.data_mut().hiding_ids = hiding_ids;
     }
 
+    pub fn set_version(&mut self, version: NmdFileVersion) {
+        self.data_mut().version = version;
+    }
+
     pub fn set_view(&mut self, view: MenuTabView) {
         self.data_mut().view = view;
     }
 
+    fn version(&self) -> NmdFileVersion {
+        self.data().version
+    }
+
     fn view(&self) -> MenuTabView {
         self.data().view
     }
@@ -702,22 +833,24 @@ impl From<&PathBuf> for MenuTabData {
     fn from(path: &PathBuf) -> Self {
         Self {
             edited: false,
+            export_versions: NmdFileVersion::VERSIONS.to_vec(),
             hiding_ids: false,
             name: path_to_file_name(path),
             path: path.to_owned(),
+            version: NmdFileVersion::default(),
             view: MenuTabView::Tree,
         }
     }
 }
 
-fn path_to_file_name(path: &PathBuf) -> String {
+fn path_to_file_name(path: &Path) -> String {
     path.file_name()
         .unwrap_or_else(|| OsStr::new("?"))
         .to_string_lossy()
         .into_owned()
 }
 
-fn path_to_string(path: &PathBuf) -> String {
+fn path_to_string(path: &Path) -> String {
     path.to_string_lossy()
         .into_owned()
 }
@@ -1,13 +1,22 @@
+mod bone_table;
 mod editor;
+mod gltf;
 mod home;
+mod inspector;
 mod menu;
+#[allow(clippy::module_inception)]
 mod region;
+mod report;
 mod tree;
 
 pub use self::{
+    bone_table::NmdAppBoneTableRegion,
     editor::NmdAppEditorRegion,
+    gltf::NmdAppGltfRegion,
     home::NmdAppHomeRegion,
+    inspector::NmdAppInspectorRegion,
     menu::{NmdAppMenuRegion, MenuTab, MenuTabData},
     region::{NmdAppRegion, generate_uuid_source},
+    report::NmdAppReportRegion,
     tree::{NmdAppTreeRegion, NodeView},
 };
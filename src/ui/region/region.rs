@@ -55,7 +55,7 @@ pub fn generate_uuid_source() -> u64 {
         UUID_SOURCE.fetch_add(
             SystemTime::now()
                 .duration_since(UNIX_EPOCH)
-                .unwrap_or_else(|_| Duration::ZERO)
+                .unwrap_or(Duration::ZERO)
                 .as_millis() as u64,
             Ordering::Relaxed
         );
@@ -0,0 +1,172 @@
+use {
+    crate::{
+        io::{
+            fifo::{
+                Message,
+                MessageSender,
+            },
+            nmd::{
+                data::NmdFileData,
+                NmdFileFinding,
+                NmdFileFindingKind,
+                NmdFileSeverity,
+                NmdFileValidator,
+            },
+        },
+        ui::{
+            region,
+            region::NmdAppRegion,
+            UiColor,
+            UiComponent,
+        },
+    },
+    eframe::egui::*,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+/// Validator findings for the file a project was imported from.
+#[derive(Serialize, Deserialize)]
+pub struct NmdAppReportRegion {
+    #[serde(skip)]
+    message_sender: Option<MessageSender>,
+    #[serde(skip)]
+    open: bool,
+    // Recomputed from file data, so not kept in projects
+    #[serde(skip)]
+    rows: Vec<ReportRow>,
+    uuid_source: u64,
+}
+
+struct ReportRow {
+    finding: NmdFileFinding,
+    // Bone to focus on click, if it's in the tree
+    link_opt: Option<(u16, String)>,
+}
+
+impl NmdAppReportRegion {
+    pub fn new(message_sender: &MessageSender, data: &NmdFileData) -> Self {
+        let mut region = Self::default();
+
+        region.emit_with(message_sender);
+        region.validate(data);
+        region
+    }
+
+    fn emit_focus(&self, bone_id: u16, name: &String) {
+        self.emit(Message::UiSelect(UiComponent::TreeNodeFocus(bone_id, name.to_owned())));
+    }
+
+    pub fn emit_with(&mut self, message_sender: &MessageSender) {
+        self.message_sender = Some(message_sender.to_owned());
+    }
+
+    fn ui_row(&self, ui: &mut Ui, row: &ReportRow) {
+        let offset_text = match row.finding.offset {
+            Some(offset) => format!("{:#010X}", offset),
+            None => String::from("-"),
+        };
+        let color = match row.finding.severity() {
+            NmdFileSeverity::Error => UiColor.editor.error.normal(),
+            NmdFileSeverity::Warning => UiColor.common.light_gray.normal(),
+        };
+
+        ui.label(RichText::new(offset_text).monospace().weak());
+        ui.label(RichText::new(row.finding.severity().to_string()).color(color));
+
+        match &row.link_opt {
+            Some((bone_id, name)) => {
+                if ui.add(Label::new(row.finding.kind.to_string()).sense(Sense::click()))
+                    .on_hover_cursor(CursorIcon::PointingHand)
+                    .on_hover_text_at_pointer(format!("Go to {}", name))
+                    .clicked()
+                {
+                    self.emit_focus(*bone_id, name);
+                }
+            }
+            None => { ui.label(row.finding.kind.to_string()); }
+        }
+
+        ui.end_row();
+    }
+
+    /// Re-run the validator, e.g. once a project has been deserialized.
+    pub fn validate(&mut self, data: &NmdFileData) {
+        self.rows = NmdFileValidator::from(data)
+            .validate()
+            .into_iter()
+            .map(|finding| {
+                let link_opt = match finding.kind {
+                    // The ID leads to the earlier record's bone, not this one
+                    NmdFileFindingKind::DuplicateId(..) => None,
+                    _ => finding.bone_id()
+                        .and_then(|bone_id| data.get(bone_id))
+                        .map(|bone_data| (bone_data.id, bone_data.name.to_owned())),
+                };
+
+                ReportRow {
+                    finding: finding,
+                    link_opt: link_opt,
+                }
+            })
+            .collect();
+    }
+}
+
+// For projects saved before the report existed
+impl Default for NmdAppReportRegion {
+    fn default() -> Self {
+        Self {
+            message_sender: None,
+            open: false,
+            rows: vec!(),
+            uuid_source: region::generate_uuid_source(),
+        }
+    }
+}
+
+impl NmdAppRegion for NmdAppReportRegion {
+    fn message_sender(&self) -> Option<&MessageSender> {
+        self.message_sender.as_ref()
+    }
+
+    fn select(&mut self, ui_component: &UiComponent) {
+        if let UiComponent::MenuValidationReport = ui_component {
+            self.open = !self.open;
+        }
+    }
+
+    fn ui(&mut self, ctx: &Context) {
+        let mut open = self.open;
+
+        Window::new("Validation report")
+            .id(self.uuid().with("report"))
+            .open(&mut open)
+            .default_size([560.0, 240.0])
+            .vscroll(true)
+            .show(ctx, |ui|
+        {
+            if self.rows.is_empty() {
+                ui.label(RichText::new("No problems found").weak());
+                return;
+            }
+
+            Grid::new(self.uuid().with("report$grid"))
+                .striped(true)
+                .show(ui, |ui|
+            {
+                for row in &self.rows {
+                    self.ui_row(ui, row);
+                }
+            });
+        });
+
+        self.open = open;
+    }
+
+    fn uuid_source(&self) -> u64 {
+        self.uuid_source
+    }
+}
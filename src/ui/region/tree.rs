@@ -8,6 +8,8 @@ use {
             nmd::{
                 anatomy::{NmdFileBone, NmdFileBoneFlag},
                 data::NmdFileData,
+                NmdFileGltfImport,
+                NmdFileGltfParent,
                 data::tree::{
                     *,
                     NmdFileBoneTreeNode as Tree,
@@ -17,6 +19,7 @@ use {
         ui::{
             region,
             region::NmdAppRegion,
+            UiAnnotations,
             UiComponent,
             UiColor,
             UiStyle,
@@ -90,29 +93,32 @@ struct Recursed(bool);
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 struct Recursive(bool);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 enum NodeExpand {
     ToggleBeneath(u16, Option<bool>),
     Set(HashSet<u16>),
+    #[default]
     Nil,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
 enum NodePasteMode {
     Copy(Recursive, usize),
     Cut,
+    #[default]
     Nil,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
 enum NodeSortMode {
     Id(ModifiedFirst),
     Name(ModifiedFirst),
     Type(ModifiedFirst),
+    #[default]
     Nil,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 enum NodeState {
     Filtered,
     FilteredAncestor,
@@ -122,6 +128,7 @@ enum NodeState {
     ModifiedAncestor(u16),
     CopyPastedAncestor(u16),
     CutPastedAncestor(u16),
+    #[default]
     Nil,
 }
 
@@ -131,9 +138,10 @@ enum NodeStateSetOperation {
     Union,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub enum NodeView {
     List,
+    #[default]
     Tree,
 }
 
@@ -149,6 +157,9 @@ pub struct NmdAppTreeRegion {
 
 #[derive(Default, Serialize, Deserialize)]
 struct NmdAppTreeProjectState {
+    // Records sharing an ID with a bone in the tree, by record index
+    #[serde(default)]
+    conflicts: BTreeMap<usize, NodeTree>,
     #[serde(skip)]
     filter: NodeFilter,
     filter_text: String,
@@ -244,6 +255,10 @@ impl NmdAppTreeRegion {
         self.emit(Message::UiState(UiState::TreeEditStatus(edited)));
     }
 
+    fn emit_conflict_renumber(&self, record_index: usize) {
+        self.emit(Message::UiSelect(UiComponent::TreeConflictRenumber(record_index)));
+    }
+
     fn emit_copy(&self, node_id: u16, node_name: &String) {
         self.emit(Message::UiSelect(UiComponent::TreeNodeCopy(node_id, node_name.to_owned())));
     }
@@ -276,6 +291,10 @@ impl NmdAppTreeRegion {
         self.emit(Message::UiSelect(UiComponent::TreeNodeFocus(node_id, node_name.to_owned())));
     }
 
+    fn emit_make_root(&self, node_id: u16) {
+        self.emit(Message::UiSelect(UiComponent::TreeNodeMakeRoot(node_id)));
+    }
+
     fn emit_paste(&self, node_id: u16) {
         self.emit(Message::UiSelect(UiComponent::TreeNodePaste(node_id)));
     }
@@ -436,10 +455,54 @@ impl NmdAppTreeRegion {
     }
 
     fn ui_body_style(&self, ui: &mut Ui) {
-        ui.visuals_mut().widgets = (*UiStyle).interactive_text.to_owned();
+        ui.visuals_mut().widgets = UiStyle.interactive_text.to_owned();
         ui.set_min_size(ui.available_size());
     }
 
+    fn ui_conflict(&self, ui: &mut Ui, record_index: usize, conflict: &NodeTree) {
+        let (node, _) = conflict.data().as_tuple();
+
+        ui.horizontal(|ui| {
+            ui.add_space(18.0);
+
+            ui.add(
+                Label::new(RichText::new(&node.name).color(UiColor.tree.conflicted.normal()))
+                    .sense(Sense::click()))
+                .on_hover_text_at_pointer(format!("Record {} has ID {:#04X}, which an earlier record already has.\nRenumber it to edit it.", record_index, node.id))
+                .context_menu(|ui|
+            {
+                if ui.button("Renumber").clicked() {
+                    self.emit_conflict_renumber(record_index);
+
+                    ui.close_menu();
+                }
+            });
+        });
+    }
+
+    fn ui_conflicts(&mut self, ui: &mut Ui, subtrees: &[NodeTree]) {
+        let has_orphans = subtrees.iter().any(|subtree| subtree.is_orphan());
+        let show_conflicts = !self.state.conflicts.is_empty() && !self.state.filtered();
+
+        if has_orphans || show_conflicts {
+            CollapsingHeader::new(RichText::new("Conflicts").color(UiColor.tree.conflicted.normal()))
+                .id_source(self.uuid().with("conflicts"))
+                .default_open(true)
+                .show(ui, |ui|
+            {
+                for subtree in subtrees.iter().filter(|subtree| subtree.is_orphan()) {
+                    self.ui_subtree(ui, subtree, 1.0);
+                }
+
+                if show_conflicts {
+                    for (record_index, conflict) in &self.state.conflicts {
+                        self.ui_conflict(ui, *record_index, conflict);
+                    }
+                }
+            });
+        }
+    }
+
     fn ui_expand(&mut self, collapser: &mut CollapsingState, node: &Ref<Node>) {
         match &mut self.transient_state.expand {
             NodeExpand::Nil => {}
@@ -498,7 +561,7 @@ impl NmdAppTreeRegion {
         use NodeState::*;
 
         let stroke = if self.state.spotlighted(node.id) {
-            (*UiColor).tree.spotlighted.normal_stroke()
+            UiColor.tree.spotlighted.normal_stroke()
         } else {
             let state_set = if self.state.filtered() {
                 &metadata.filtered
@@ -511,25 +574,25 @@ impl NmdAppTreeRegion {
                 .next() // First in an ordered set
                 .unwrap_or(&NodeState::Nil)
             {
-                Filtered                => { (*UiColor).tree.filtered.normal_stroke() }
+                Filtered                => { UiColor.tree.filtered.normal_stroke() }
                 FilteredAncestor
-                    if use_dims         => { (*UiColor).common.near_weak_gray.normal_stroke() }
+                    if use_dims         => { UiColor.common.near_weak_gray.normal_stroke() }
                 _
-                    if metadata.copied  => { (*UiColor).tree.copied.normal_stroke() }
+                    if metadata.copied  => { UiColor.tree.copied.normal_stroke() }
                 _
-                    if metadata.cut     => { (*UiColor).common.near_weak_gray.normal_stroke() }
-                Modified                => { (*UiColor).tree.modified.normal_stroke() }
+                    if metadata.cut     => { UiColor.common.near_weak_gray.normal_stroke() }
+                Modified                => { UiColor.tree.modified.normal_stroke() }
                 ModifiedAncestor(_)
-                    if use_dims         => { (*UiColor).tree.modified.dim_stroke() }
-                CopyPasted              => { (*UiColor).tree.copy_pasted.normal_stroke() }
+                    if use_dims         => { UiColor.tree.modified.dim_stroke() }
+                CopyPasted              => { UiColor.tree.copy_pasted.normal_stroke() }
                 CopyPastedAncestor(_)
                     if use_dims
-                                        => { (*UiColor).tree.copy_pasted.dim_stroke() }
-                CutPasted               => { (*UiColor).tree.cut_pasted.normal_stroke() }
+                                        => { UiColor.tree.copy_pasted.dim_stroke() }
+                CutPasted               => { UiColor.tree.cut_pasted.normal_stroke() }
                 CutPastedAncestor(_)
                     if use_dims
-                                        => { (*UiColor).tree.cut_pasted.dim_stroke() }
-                _                       => { (*UiColor).common.light_gray.normal_stroke() }
+                                        => { UiColor.tree.cut_pasted.dim_stroke() }
+                _                       => { UiColor.common.light_gray.normal_stroke() }
             }
         };
 
@@ -539,11 +602,11 @@ impl NmdAppTreeRegion {
         
         if self.state.selected(node.id) {
             widget_style.active
-                .bg_fill = (*UiColor).tree.selected.normal();
+                .bg_fill = UiColor.tree.selected.normal();
             widget_style.inactive
-                .bg_fill = (*UiColor).tree.selected.normal();
+                .bg_fill = UiColor.tree.selected.normal();
             widget_style.hovered
-                .bg_fill = (*UiColor).tree.selected.normal();
+                .bg_fill = UiColor.tree.selected.normal();
         }
     }
 
@@ -577,7 +640,7 @@ impl NmdAppTreeRegion {
                             TextFormat {
                                 // Normal label text as opposed to the
                                 // slightly-lighter job text
-                                color: (*UiColor).common.light_gray.normal(),
+                                color: UiColor.common.light_gray.normal(),
                                 ..Default::default()
                             }
                         );
@@ -852,7 +915,7 @@ impl NmdAppTreeRegion {
     }
 
     fn ui_pins_style(&self, ui: &mut Ui) {
-        ui.visuals_mut().selection.bg_fill = (*UiColor).tree.pinned.normal();
+        ui.visuals_mut().selection.bg_fill = UiColor.tree.pinned.normal();
     }
 
     fn ui_root(&mut self, ui: &mut Ui) {
@@ -867,9 +930,9 @@ impl NmdAppTreeRegion {
                     let mut hover_text = LayoutJob::default();
 
                     hover_text.append("Tree has been", 0.0, TextFormat::default());
-                    hover_text.append(" rooted ", 0.0, TextFormat { color: (*UiColor).common.weak_gray.normal(), ..Default::default() });
+                    hover_text.append(" rooted ", 0.0, TextFormat { color: UiColor.common.weak_gray.normal(), ..Default::default() });
                     hover_text.append("under a parent node.\nClick to restore previous", 0.0, TextFormat::default());
-                    hover_text.append(" root.", 0.0, TextFormat { color: (*UiColor).common.weak_gray.normal(), ..Default::default() });
+                    hover_text.append(" root.", 0.0, TextFormat { color: UiColor.common.weak_gray.normal(), ..Default::default() });
 
                     ui.label(hover_text);
 
@@ -1051,6 +1114,14 @@ impl NmdAppTreeRegion {
                 ui.close_menu();
             }
 
+            if self.state.orphaned(node.id) {
+                if ui.button("Make root bone").clicked() {
+                    self.emit_make_root(node.id);
+
+                    ui.close_menu();
+                }
+            }
+
             ui.separator();
 
             ui.add_enabled(false, Button::new("New"));
@@ -1069,8 +1140,18 @@ impl NmdAppTreeRegion {
         }
 
         self.with_subtrees(|region, subtrees| {
-            for subtree in subtrees {
-                region.ui_subtree(ui, subtree, 1.0);
+            if region.state.rooted() {
+                for subtree in subtrees {
+                    region.ui_subtree(ui, subtree, 1.0);
+                }
+            } else {
+                // Orphans are shown apart from the tree, with conflicting
+                // records
+                for subtree in subtrees.iter().filter(|subtree| !subtree.is_orphan()) {
+                    region.ui_subtree(ui, subtree, 1.0);
+                }
+
+                region.ui_conflicts(ui, subtrees);
             }
         });
 
@@ -1137,10 +1218,14 @@ impl NmdAppRegion for NmdAppTreeRegion {
 
     fn select(&mut self, ui_component: &UiComponent) {
         match ui_component {
+            UiComponent::GltfApply(import, node_indices)
+                => { if let Some((node_id, ui_state)) = self.state.create_from_gltf(import, node_indices) { self.on_pasted(node_id, ui_state); } }
             UiComponent::MenuCommit
                 => { self.state.clear_modified_state(); }
             UiComponent::MenuHideListIds(hide)
                 => { self.state.hiding_ids = *hide; }
+            UiComponent::TreeConflictRenumber(record_index)
+                => { if let Some((node_id, ui_state)) = self.state.renumber(*record_index) { self.on_pasted(node_id, ui_state); } }
             UiComponent::TreeFilterClear
                 => { self.state.clear_filter(); }
             UiComponent::TreeNode(node_id, node_name)
@@ -1159,6 +1244,8 @@ impl NmdAppRegion for NmdAppTreeRegion {
                 => { self.state.filter_to(*node_id); }
             UiComponent::TreeNodeFocus(node_id, node_name)
                 => { self.focus(*node_id, node_name); }
+            UiComponent::TreeNodeMakeRoot(node_id)
+                => { if let Some((node_id, ui_state)) = self.state.make_root(*node_id) { self.on_pasted(node_id, ui_state); } }
             UiComponent::TreeNodePaste(parent_id)
                 => { if let Some((node_id, ui_state)) = self.state.paste(*parent_id, None)  { self.on_pasted(node_id, ui_state); } }
             UiComponent::TreeNodePasteAfter(sibling_id)
@@ -1167,6 +1254,8 @@ impl NmdAppRegion for NmdAppTreeRegion {
                 => { self.state.insert_pin(*pin_id, pin_name); }
             UiComponent::TreeNodePinRemove(pin_id)
                 => { self.state.remove_pin(*pin_id); }
+            UiComponent::TreeNodeReparent(node_id, new_parent_id)
+                => { if let Some((node_id, ui_state)) = self.state.reparent(*node_id, *new_parent_id) { self.on_pasted(node_id, ui_state); } }
             UiComponent::TreeNodeRoot(node_id)
                 => { self.state.push_root(*node_id); }
             UiComponent::TreeNodeScroll(node_id)
@@ -1333,7 +1422,7 @@ impl NmdAppTreeProjectState {
         // This is inefficient if done repeatedly but not expected to loop more
         // than once
         while let Some(root_id) = self.roots.last() {
-            if !self.ids.contains(&root_id) {
+            if !self.ids.contains(root_id) {
                 self.pop_root()
             } else {
                 break;
@@ -1384,6 +1473,46 @@ impl NmdAppTreeProjectState {
         matches!(self.paste_mode, NodePasteMode::Copy(_, _))
     }
 
+    /// Make a bone of each of the given unmatched glTF nodes, under the bone
+    /// made or matched for its parent node, or else the nearest ancestor's.
+    fn create_from_gltf(&mut self, import: &NmdFileGltfImport, node_indices: &BTreeSet<usize>) -> Option<(u16, UiState)> {
+        let mut created_ids = HashMap::<usize, u16>::new();
+        let mut created = vec![];
+
+        for node_index in import.unmatched_nodes().iter().filter(|node_index| node_indices.contains(node_index) && import.creatable(**node_index)) {
+            let new_id = match (0..NmdFileBone::ROOT_BONE_ID).find(|id| !self.ids.contains(id)) {
+                Some(new_id) => new_id,
+                None => break,
+            };
+            let parent_id = self.gltf_parent_id(import, *node_index, &created_ids);
+            let (bone_data, physics_opt) = import.new_bone(*node_index, new_id, parent_id);
+            let metadata = {
+                let mut metadata = NodeMetadata::default();
+
+                // Shown as pasted bones are, until committed
+                metadata.modified.insert(NodeState::CopyPasted);
+                metadata
+            };
+            let subtree = NodeTree::new(&bone_data, NodeWrapper::new(Node::from(&bone_data), metadata));
+
+            if let Ok(path_to_subtree) = self.paste_copy_internal(subtree, parent_id, None, Recursive(false)) {
+                if let Some(subtree) = self.tree.at_path(&path_to_subtree) {
+                    Self::integrate(subtree, &mut self.filter, &mut self.ids, &mut self.list);
+                }
+
+                created_ids.insert(*node_index, new_id);
+                created.push((bone_data, physics_opt));
+            }
+        }
+
+        let first_id = created.first()?.0.id;
+
+        self.on_paste_copy();
+        self.sort();
+
+        Some((first_id, UiState::TreeNodeCreate(created)))
+    }
+
     fn cut(&mut self, node_id: u16, node_name: &String) {
         self.cancel_paste();
         self.paste = Some(NodeSummary(node_id, node_name.to_owned()));
@@ -1475,6 +1604,13 @@ impl NmdAppTreeProjectState {
     #[inline]
     fn finalize_filter(&mut self) {
         self.filter = NodeFilter::from(&self.tree);
+
+        // Flag names may have changed in the annotations since this was saved
+        for node in self.filter.iter() {
+            let flag = node.borrow().flag;
+
+            node.borrow_mut().set_flag(flag);
+        }
     }
 
     #[inline]
@@ -1491,6 +1627,23 @@ impl NmdAppTreeProjectState {
         )
     }
 
+    fn gltf_parent_id(&self, import: &NmdFileGltfImport, node_index: usize, created_ids: &HashMap<usize, u16>) -> u16 {
+        let mut parent = import.parent(node_index);
+
+        loop {
+            match parent {
+                NmdFileGltfParent::Bone(bone_id) if self.ids.contains(&bone_id)
+                    => return bone_id,
+                NmdFileGltfParent::Node(parent_index)
+                    => match created_ids.get(&parent_index) {
+                        Some(bone_id) => return *bone_id,
+                        None => parent = import.parent(parent_index),
+                    },
+                _   => return NmdFileBone::ROOT_BONE_ID,
+            }
+        }
+    }
+
     fn has_history(&self) -> bool {
         !self.history.is_empty()
     }
@@ -1534,29 +1687,21 @@ impl NmdAppTreeProjectState {
         }
     }
 
-    fn iter_nodes<'a>(&'a self) -> impl Iterator<Item = Ref<Node>> + 'a {
+    fn iter_nodes<'a>(&'a self) -> impl Iterator<Item = Ref<'a, Node>> + 'a {
         // The filter iterates over all nodes directly so it's suitable
         let mut iter = self.filter.iter();
 
         iter_from(move || {
-            if let Some(node) = iter.next() {
-                Some(node.borrow())
-            } else {
-                None
-            }
+            iter.next().map(|node| node.borrow())
         })
     }
 
-    fn iter_nodes_mut<'a>(&'a mut self) -> impl Iterator<Item = RefMut<Node>> + 'a {
+    fn iter_nodes_mut<'a>(&'a mut self) -> impl Iterator<Item = RefMut<'a, Node>> + 'a {
         // The filter iterates over all nodes directly so it's suitable
         let mut iter = self.filter.iter();
 
         iter_from(move || {
-            if let Some(node) = iter.next() {
-                Some(node.borrow_mut())
-            } else {
-                None
-            }
+            iter.next().map(|node| node.borrow_mut())
         })
     }
 
@@ -1580,11 +1725,25 @@ impl NmdAppTreeProjectState {
         }
     }
 
+    /// Attach an orphan to the tree root.
+    fn make_root(&mut self, node_id: u16) -> Option<(u16, UiState)> {
+        let orphan = self.tree.children
+            .iter_mut()
+            .find(|child| child.id() == node_id && child.is_orphan())?;
+
+        orphan.set_parent_id(NmdFileBone::ROOT_BONE_ID);
+        Self::mark_cut_pasted(orphan, true);
+
+        self.on_paste_cut();
+
+        Some((node_id, UiState::TreeNodeCutPaste(node_id, NmdFileBone::ROOT_BONE_ID)))
+    }
+
     fn mark_copied(&mut self, node_id: u16, recursive: Recursive, copied: bool) -> Recursed {
         if let Some(subtree) = self.tree.find_mut(node_id) {
             Self::mark_copied_internal(subtree, recursive, copied);
 
-            Recursed(recursive.0 && subtree.children.len() > 0)
+            Recursed(recursive.0 && !subtree.children.is_empty())
         } else {
             Recursed(false)
         }
@@ -1836,7 +1995,7 @@ impl NmdAppTreeProjectState {
             let target_id = target.id();
 
             if let Some(predecessor_id) = predecessor_id_opt {
-                copy_parent.give_child_at_index(target, copy_parent.child_index(predecessor_id).and_then(|i| Some(i + 1)).unwrap_or(0));
+                copy_parent.give_child_at_index(target, copy_parent.child_index(predecessor_id).map(|i| i + 1).unwrap_or(0));
             } else {
                 copy_parent.give_child_at_index(target, 0);
             }
@@ -1897,7 +2056,7 @@ impl NmdAppTreeProjectState {
             }
 
             if let Some(predecessor_id) = predecessor_id_opt {
-                new_parent.give_child_at_index(target, new_parent.child_index(predecessor_id).and_then(|i| Some(i + 1)).unwrap_or(0));
+                new_parent.give_child_at_index(target, new_parent.child_index(predecessor_id).map(|i| i + 1).unwrap_or(0));
             } else {
                 new_parent.give_child_at_index(target, 0);
             }
@@ -1916,8 +2075,12 @@ impl NmdAppTreeProjectState {
         }
     }
 
+    fn orphaned(&self, node_id: u16) -> bool {
+        self.tree.orphans().any(|orphan| orphan.id() == node_id)
+    }
+
     fn paste_id(&self) -> Option<u16> {
-        self.paste.as_ref().and_then(|NodeSummary(node_id, _)| Some(*node_id))
+        self.paste.as_ref().map(|NodeSummary(node_id, _)| *node_id)
     }
 
     fn prepend_history(&mut self, node_id: u16, node_name: &String) {
@@ -1946,6 +2109,51 @@ impl NmdAppTreeProjectState {
         self.pins.retain(|pin| pin.id != pin_id);
     }
 
+    /// Give a conflicting record the first free ID and move it into the tree,
+    /// under its parent if there is one.
+    fn renumber(&mut self, record_index: usize) -> Option<(u16, UiState)> {
+        let new_id = (0..NmdFileBone::ROOT_BONE_ID).find(|id| !self.ids.contains(id))?;
+        let mut conflict = self.conflicts.remove(&record_index)?;
+
+        conflict.set_id(new_id);
+        conflict.data().node.borrow_mut().set_id(new_id);
+
+        Self::integrate(&conflict, &mut self.filter, &mut self.ids, &mut self.list);
+
+        if let Some(unclaimed) = self.tree.insert(conflict) {
+            self.tree.children.push(unclaimed);
+        }
+
+        self.on_modified();
+        self.sort();
+
+        Some((new_id, UiState::TreeConflictRenumber(record_index, new_id)))
+    }
+
+    /// Move a node under another, as a cut and paste would, without touching
+    /// what is being cut or copied.
+    fn reparent(&mut self, node_id: u16, new_parent_id: u16) -> Option<(u16, UiState)> {
+        if let (path_to_parent, Some(parent)) = self.tree.path_to_parent_mut(node_id) {
+            if let Some(child) = parent.take_child(node_id) {
+                match self.paste_cut_internal(&path_to_parent, child, new_parent_id, None) {
+                    Ok(_) => {
+                        self.on_paste_cut();
+
+                        return Some((node_id, UiState::TreeNodeCutPaste(node_id, new_parent_id)));
+                    }
+                    Err(unclaimed_child) => {
+                        // Put it back where it was (unexpected)
+                        if let Some(unclaimed_child) = self.tree.insert_at_path(&path_to_parent, unclaimed_child) {
+                            self.tree.children.push(unclaimed_child);
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
     fn root_mut(&mut self, distance_up: usize) -> Option<&mut dyn Tree<Data = NodeWrapper>> {
         if let Some(root_id) = self.roots.get(self.roots.len().wrapping_sub(distance_up + 1)) {
             // It can't infer the type here...
@@ -1961,7 +2169,7 @@ impl NmdAppTreeProjectState {
     }
 
     fn rooted(&self) -> bool {
-        self.roots.len() > 0
+        !self.roots.is_empty()
     }
 
     fn rooted_at(&self, node_id: u16) -> bool {
@@ -2014,7 +2222,7 @@ impl NmdAppTreeProjectState {
     }
 
     fn sort_by_id(&mut self) {
-        self.list.sort_by(|a, b| a.node.borrow().id.cmp(&b.node.borrow().id));
+        self.list.sort_by_key(|a| a.node.borrow().id);
     }
 
     fn sort_by_name(&mut self) {
@@ -2025,11 +2233,10 @@ impl NmdAppTreeProjectState {
         // Sort by type -> group by type -> sort groups by name -> flatten
         self.list = iter::sort_then_group_by(mem::take(&mut self.list), |a, b| a.node.borrow().normalized_flag.cmp(&b.node.borrow().normalized_flag))
             .into_iter()
-            .map(|mut group| {
+            .flat_map(|mut group| {
                 group.sort_by(|a, b| a.node.borrow().name.cmp(&b.node.borrow().name));
                 group
             })
-            .flatten()
             .collect();
     }
 
@@ -2069,13 +2276,13 @@ impl NmdAppTreeProjectState {
         }
     }
 
-    fn update_name(&mut self, node_id: u16, name: &String) {
+    fn update_name(&mut self, node_id: u16, name: &str) {
         let name_space;
         let name_str = if name.chars().all(|c| c.is_whitespace()) {
             name_space = format!("({})", " ".repeat(name.len()));
             name_space.as_str()
         } else {
-            name.as_str() 
+            name
         };
 
         self.update_name_for_node(node_id, name_str);
@@ -2135,9 +2342,9 @@ impl NmdAppTreeProjectState {
         }
     }
 
-    fn visitable(&mut self, node_id: u16, node_path: &Vec<u16>) -> bool {
+    fn visitable(&mut self, node_id: u16, node_path: &[u16]) -> bool {
         if let Some(root_id) = self.roots.last() {
-            if !node_path.contains(&root_id) {
+            if !node_path.contains(root_id) {
                 return false;
             }
         }
@@ -2167,8 +2374,15 @@ impl NmdAppTreeProjectState {
 impl From<&NmdFileData> for NmdAppTreeProjectState {
     fn from(data: &NmdFileData) -> Self {
         let tree = data.tree_with(|bone_data| NodeWrapper::from(bone_data));
+        let root_bone_ids: Vec<_> = tree.children.iter()
+            .filter(|subtree| !subtree.is_orphan())
+            .map(|subtree| subtree.id())
+            .collect();
         let mut state = Self {
-            natural_root: (tree.children.len() == 1).then(|| tree.children.first().unwrap().id()),
+            conflicts: data.conflicts.iter()
+                .map(|(record_index, bone_data)| (*record_index, NodeTree::new(bone_data, NodeWrapper::from(bone_data))))
+                .collect(),
+            natural_root: (root_bone_ids.len() == 1).then(|| root_bone_ids[0]),
             sort_mode: NodeSortMode::Type(ModifiedFirst(false)),
             ids: tree.iter().map(|subtree| subtree.id()).collect(),
             tree: tree,
@@ -2195,12 +2409,6 @@ impl NmdAppTreeTransientState {
     }
 }
 
-impl Default for NodeExpand {
-    fn default() -> Self {
-        NodeExpand::Nil
-    }
-}
-
 impl NodePasteMode {
     fn increment(&mut self) {
         *self = match self {
@@ -2223,12 +2431,6 @@ impl NodePasteMode {
     }
 }
 
-impl Default for NodePasteMode {
-    fn default() -> Self {
-        NodePasteMode::Nil
-    }
-}
-
 impl Display for NodePasteMode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", match self {
@@ -2260,14 +2462,8 @@ impl NodeSortMode {
     }
 }
 
-impl Default for NodeSortMode {
-    fn default() -> Self {
-        NodeSortMode::Nil
-    }
-}
-
 impl NodeState {
-    fn to_ancestor(&self, node_id: u16) -> Option<Self> {
+    fn to_ancestor(self, node_id: u16) -> Option<Self> {
         match self {
             Self::CopyPasted => Some(Self::CopyPastedAncestor(node_id)),
             Self::CutPasted => Some(Self::CutPastedAncestor(node_id)),
@@ -2278,18 +2474,6 @@ impl NodeState {
     }
 }
 
-impl Default for NodeState {
-    fn default() -> Self {
-        NodeState::Nil
-    }
-}
-
-impl Default for NodeView {
-    fn default() -> Self {
-        NodeView::Tree
-    }
-}
-
 impl Node {
     const COPY_SUFFIX: &'static str = "_copy";
 
@@ -2312,7 +2496,7 @@ impl Node {
     }
 
     fn normalize_flag(flag: NmdFileBoneFlag) -> String {
-        flag.to_string().to_lowercase()
+        UiAnnotations.flag_label(flag).to_lowercase()
     }
 
     fn normalize_id(id: u16) -> String {
@@ -2328,6 +2512,11 @@ impl Node {
         self.normalized_flag = Self::normalize_flag(flag);
     }
 
+    fn set_id(&mut self, id: u16) {
+        self.id = id;
+        self.normalized_id = Self::normalize_id(id);
+    }
+
     fn set_name(&mut self, name: &str) {
         self.name = name.to_owned();
         self.normalized_name = Self::normalize_name(name);
@@ -2373,7 +2562,7 @@ impl NodeFilter {
         self.collection.insert(node);
     }
 
-    fn iter<'a>(&'a self) -> impl Iterator<Item = &Rc<RefCell<Node>>> {
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a Rc<RefCell<Node>>> + 'a {
         self.collection.iter()
     }
 
@@ -2393,12 +2582,12 @@ impl Filter for NodeFilter {
         &self.collection
     }
 
-    fn search(&self, token: &String, nodes: &Self::FilterSet) -> Self::FilterSet {
+    fn search(&self, token: &str, nodes: &Self::FilterSet) -> Self::FilterSet {
         let mut result = Self::FilterSet::default();
 
         for node in nodes.iter() {
             if match token.chars().nth(0) {
-                Some('#') => node.borrow().normalized_id == &token[1..token.len()],
+                Some('#') => node.borrow().normalized_id == token[1..token.len()],
                 Some('$') => node.borrow().normalized_flag.contains(&token[1..token.len()]),
                 _         => node.borrow().normalized_name.contains(token)
             } {
@@ -2440,7 +2629,7 @@ impl NodeWrapper {
         }
     }
 
-    fn as_tuple(&self) -> (Ref<Node>, Ref<NodeMetadata>) {
+    fn as_tuple(&self) -> (Ref<'_, Node>, Ref<'_, NodeMetadata>) {
         (self.node.borrow(), self.metadata.borrow())
     }
 }
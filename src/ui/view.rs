@@ -6,8 +6,15 @@ use {
                 MessageSender,
             },
             nmd::{
-                data::NmdFileData,
+                anatomy::NmdFileVersion,
+                data::{
+                    ByteVec,
+                    NmdFileData,
+                },
                 NmdFile,
+                NmdFileBoneOrder,
+                NmdFileBoneTable,
+                NmdFileGltf,
             },
         },
         ui::{
@@ -17,7 +24,7 @@ use {
     },
     std::{
         cmp::Ordering,
-        fs::File,
+        fs::{self, File},
         io::{Error, ErrorKind, Result, Write},
         path::PathBuf,
     },
@@ -48,7 +55,15 @@ struct NmdAppProjectView {
 
 #[derive(Serialize, Deserialize)]
 struct NmdAppProjectSubRegions {
+    #[serde(default)]
+    bone_table: NmdAppBoneTableRegion,
     editor: NmdAppEditorRegion,
+    #[serde(default)]
+    gltf: NmdAppGltfRegion,
+    #[serde(default)]
+    inspector: NmdAppInspectorRegion,
+    #[serde(default)]
+    report: NmdAppReportRegion,
     tree: NmdAppTreeRegion,
 }
 
@@ -74,7 +89,9 @@ impl NmdAppView {
     fn conform_menu_to_project_view(menu: &mut NmdAppMenuRegion, project_view: &NmdAppProjectView) {
         if let Some(tab) = menu.most_recent_tab_mut() {
             tab.set_edited(project_view.regions.tree.modified());
+            tab.set_export_versions(project_view.state.file_data.export_versions());
             tab.set_hiding_ids(project_view.regions.tree.hiding_ids());
+            tab.set_version(project_view.state.file_data.version);
             tab.set_view(project_view.regions.tree.view());
         }
     }
@@ -123,20 +140,73 @@ impl NmdAppView {
         }
     }
 
-    pub fn try_export(&mut self, path: &PathBuf) -> Result<()> {
+    pub fn try_export(&mut self, path: &PathBuf, version: NmdFileVersion, backing_up: bool, bone_order: NmdFileBoneOrder) -> Result<Option<PathBuf>> {
         if let Some(project_view) = self.current_project_view_mut() {
             project_view.regions.editor
-                .try_export(path, &project_view.state.file_data)
+                .try_export(path, &project_view.state.file_data, version, backing_up, bone_order)
+                .map_err(Error::from)
         } else {
             Err(Error::from(ErrorKind::Other))
         }
     }
 
+    pub fn try_export_bone_table(&mut self, path: &PathBuf) -> Result<()> {
+        if let Some(project_view) = self.current_project_view_mut() {
+            project_view.regions.editor
+                .try_export_bone_table(path)
+                .map_err(Error::from)
+        } else {
+            Err(Error::from(ErrorKind::Other))
+        }
+    }
+
+    pub fn try_export_gltf(&mut self, path: &PathBuf) -> Result<()> {
+        if let Some(project_view) = self.current_project_view_mut() {
+            project_view.regions.editor
+                .try_export_gltf(path)
+                .map_err(Error::from)
+        } else {
+            Err(Error::from(ErrorKind::Other))
+        }
+    }
+
+    // Opens a preview, including when the table can't be read, to say why
+    pub fn import_bone_table(&mut self, path: &PathBuf) {
+        if let Some(project_view) = self.current_project_view_mut() {
+            let regions = &mut project_view.regions;
+
+            match NmdFileBoneTable::try_from(path) {
+                Ok(table) => {
+                    let (changes, unmatched_ids) = regions.editor.bone_table_changes(&table);
+
+                    regions.bone_table.preview(path, Ok(table), changes, unmatched_ids);
+                }
+                Err(error) => regions.bone_table.preview(path, Err(error.to_string()), vec![], vec![]),
+            }
+        }
+    }
+
+    // Opens a preview, including when the skeleton can't be read, to say why
+    pub fn import_gltf(&mut self, path: &PathBuf) {
+        if let Some(project_view) = self.current_project_view_mut() {
+            let regions = &mut project_view.regions;
+
+            match NmdFileGltf::try_from(path).and_then(|gltf| regions.editor.gltf_import(gltf)) {
+                Ok((import, changes, unmatched_bones)) => regions.gltf.preview(path, Ok(import), changes, unmatched_bones),
+                Err(error) => regions.gltf.preview(path, Err(error.to_string()), vec![], vec![]),
+            }
+        }
+    }
+
     pub fn try_import(&mut self, path: &PathBuf) -> Result<()> {
         let data = NmdFile::try_from(path)?.data;
+        let bytes = fs::read(path)?;
+
+        let project_view = NmdAppProjectView::new(&self.message_sender, data, bytes);
 
-        self.regions.data.push(NmdAppProjectView::new(&self.message_sender, data));
         self.regions.menu.push_tab(path);
+        Self::conform_menu_to_project_view(&mut self.regions.menu, &project_view);
+        self.regions.data.push(project_view);
 
         Ok(())
     }
@@ -153,7 +223,7 @@ impl NmdAppView {
 
                         Ok(())
                     }
-                    Err(serde_error) => Err(Error::new(ErrorKind::Other, serde_error)),
+                    Err(serde_error) => Err(Error::other(serde_error)),
                 }
             }
             Err(error) => Err(error)
@@ -165,14 +235,14 @@ impl NmdAppView {
             match File::create(project_path) {
                 Ok(project_file) => {
                     match serde_json::to_writer(&project_file, &project_view) {
-                        Err(serde_error) => Err(Error::new(ErrorKind::Other, serde_error)),
+                        Err(serde_error) => Err(Error::other(serde_error)),
                         _ => Ok(())
                     }
                 }
                 Err(error) => Err(error)
             }
         } else {
-            Err(Error::new(ErrorKind::Other, "Tried to save an empty view"))
+            Err(Error::other("Tried to save an empty view"))
         }
     }
 }
@@ -188,8 +258,16 @@ impl NmdAppRegion for NmdAppView {
 
     fn select(&mut self, ui_component: &UiComponent) {
         match ui_component {
-            UiComponent::MenuExport(path)
-                => { self.try_export(path); }
+            UiComponent::MenuBoneTableExport(path)
+                => { self.try_export_bone_table(path); }
+            UiComponent::MenuBoneTableImport(path)
+                => { self.import_bone_table(path); }
+            UiComponent::MenuExport(path, version, backing_up, bone_order)
+                => { self.try_export(path, *version, *backing_up, *bone_order); }
+            UiComponent::MenuGltfExport(path)
+                => { self.try_export_gltf(path); }
+            UiComponent::MenuGltfImport(path)
+                => { self.import_gltf(path); }
             UiComponent::MenuImport(path)
                 => { if self.try_import(path).is_ok()  { self.show_newest(); } }
             UiComponent::MenuProjectOpen(path)
@@ -222,20 +300,27 @@ impl NmdAppRegion for NmdAppView {
 }
 
 impl NmdAppProjectView {
-    fn new(message_sender: &MessageSender, data: NmdFileData) -> Self {
+    fn new(message_sender: &MessageSender, data: NmdFileData, bytes: ByteVec) -> Self {
         Self {
-            regions: NmdAppProjectSubRegions::new(message_sender, &data),
+            regions: NmdAppProjectSubRegions::new(message_sender, &data, bytes),
             state: NmdAppDataProjectState::from(data),
         }
     }
 
     fn emit_with(&mut self, message_sender: &MessageSender) {
+        self.regions.bone_table.emit_with(message_sender);
+        self.regions.gltf.emit_with(message_sender);
         self.regions.tree.emit_with(message_sender);
         self.regions.editor.emit_with(message_sender);
+        self.regions.inspector.emit_with(message_sender);
+        self.regions.report.emit_with(message_sender);
     }
 
     fn on_serialized(&mut self, project_path: &PathBuf) {
         self.regions.tree.on_serialized();
+        self.regions.editor.on_serialized(&self.state.file_data);
+        self.regions.inspector.remap();
+        self.regions.report.validate(&self.state.file_data);
 
         self.state.project_path_opt = Some(project_path.to_owned());
     }
@@ -250,18 +335,28 @@ impl NmdAppRegion for NmdAppProjectView {
     fn select(&mut self, ui_component: &UiComponent) {
         self.regions.tree.select(ui_component);
         self.regions.editor.select(ui_component);
+        self.regions.inspector.select(ui_component);
+        self.regions.report.select(ui_component);
     }
 
     fn ui(&mut self, ctx: &Context) {
         self.regions.tree.ui(ctx);
         self.regions.editor.ui(ctx);
+        self.regions.inspector.ui(ctx);
+        self.regions.report.ui(ctx);
+        self.regions.bone_table.ui(ctx);
+        self.regions.gltf.ui(ctx);
     }
 }
 
 impl NmdAppProjectSubRegions {
-    fn new(message_sender: &MessageSender, data: &NmdFileData) -> Self {
+    fn new(message_sender: &MessageSender, data: &NmdFileData, bytes: ByteVec) -> Self {
         Self {
+            bone_table: NmdAppBoneTableRegion::new(message_sender),
             editor: NmdAppEditorRegion::new(message_sender, data),
+            gltf: NmdAppGltfRegion::new(message_sender),
+            inspector: NmdAppInspectorRegion::new(message_sender, bytes),
+            report: NmdAppReportRegion::new(message_sender, data),
             tree: NmdAppTreeRegion::new(message_sender, data),
         }
     }
@@ -14,11 +14,11 @@ pub trait InputField<'a> {
 
     fn normalize(&self, value: Self::Input) -> Self::Input;
 
-    fn parse(&self, value_text: &String) -> Option<Self::Input>;
+    fn parse(&self, value_text: &str) -> Option<Self::Input>;
 
     fn proxy(&mut self) -> &mut InputFieldProxy<'a, Self::Input>;
 
-    fn set_value(&mut self, value_text: &String) -> bool {
+    fn set_value(&mut self, value_text: &str) -> bool {
         if let value_opt @ Some(_) = self.parse(value_text) {
             (self.proxy())(value_opt);
 
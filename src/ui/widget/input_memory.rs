@@ -42,7 +42,7 @@ impl InputFieldMemoryData {
     }
 
     pub fn deviates(&self) -> bool {
-        self.value_text != "" && self.value_text != self.original_value_text
+        !self.value_text.is_empty() && self.value_text != self.original_value_text
     }
 
     // TODO: Deprecate probably
@@ -70,7 +70,7 @@ pub trait InputFieldMemory<'a, I>: InputField<'a, Input = I> + Sized
     fn commit(&mut self, memory: &mut MutexGuard<InputFieldMemoryData>) {
         let prev_value = self.get_value();
 
-        if memory.value_text != "" {
+        if !memory.value_text.is_empty() {
             self.set_value(&memory.value_text);
             // Few possible cases:
             //
@@ -192,7 +192,7 @@ impl<'a, N> InputField<'a> for NumericInputField<'a, N>
         clamp(number, &self.clamp_range)
     }
 
-    fn parse(&self, value_text: &String) -> Option<N> {
+    fn parse(&self, value_text: &str) -> Option<N> {
         N::bounded_from(value_text).map(|number| self.normalize(number)).ok()
     }
 
@@ -238,9 +238,7 @@ fn as_num_string<N>(number: N, min_precision: usize, max_precision: usize) -> St
     if let Some(decimal) = output.find('.') {
         if min_precision > 0 {
             output += &"0".repeat(
-                min_precision
-                    .checked_sub(output.len() - (decimal + 1))
-                    .unwrap_or(0)
+                min_precision.saturating_sub(output.len() - (decimal + 1))
             );
         }
 
@@ -272,8 +270,8 @@ fn round_num_string(string: &mut String, precision: usize) {
             unsafe {
                 let mut bytes = string.as_bytes_mut(); 
 
-                if '5' as u8 <= bytes[roll_index] {
-                    *string = round_num_string_impl(&mut bytes, decimal, roll_index);
+                if b'5' <= bytes[roll_index] {
+                    *string = round_num_string_impl(bytes, decimal, roll_index);
                 } else {
                     *string = String::from(&string[0..roll_index]);
                 }
@@ -286,9 +284,9 @@ fn round_num_string_impl(bytes: &mut [u8], decimal: usize, roll_index: usize) ->
     let bytes_as_str;
 
     for byte in bytes.iter_mut().take(roll_index).rev() {
-        if *byte == '9' as u8 {
-            *byte = '0' as u8;
-        } else if '0' as u8 <= *byte {
+        if *byte == b'9' {
+            *byte = b'0';
+        } else if b'0' <= *byte {
             *byte += 1;
 
             break;
@@ -300,8 +298,8 @@ fn round_num_string_impl(bytes: &mut [u8], decimal: usize, roll_index: usize) ->
         .trim_end_matches('.');
 
     if decimal > 1 {
-        if let Some('0') = bytes_as_str.chars().nth(0) {
-            String::from("1") + &bytes_as_str
+        if let Some('0') = bytes_as_str.chars().next() {
+            String::from("1") + bytes_as_str
         } else {
             String::from(bytes_as_str)
         }
@@ -34,10 +34,10 @@ pub trait Filter: Sized {
     fn query(&self, expression: &Expression) -> Option<Self::FilterSet>
         where for<'s> &'s Self::FilterSet: BitAnd<Output = Self::FilterSet> + BitOr<Output = Self::FilterSet>,
     {
-        Some(query_internal(self, expression.clone(), self.collection())?)
+        query_internal(self, expression.clone(), self.collection())
     }
 
-    fn search(&self, needle: &String, superset: &Self::FilterSet) -> Self::FilterSet;
+    fn search(&self, needle: &str, superset: &Self::FilterSet) -> Self::FilterSet;
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
@@ -114,7 +114,7 @@ impl Expression {
             }
         }
 
-        (items > 1).then(|| ExpressionError::BUG)
+        (items > 1).then_some(ExpressionError::BUG)
     }
 
     fn is_unit(&self) -> bool {
@@ -226,23 +226,32 @@ impl TryFrom<&str> for Expression {
     /// E.g.:
     ///         A B             = A AND B
     ///         A B (C | D)     = A AND B AND (C OR D)
+    ///         $unknown(0x06)  = flag "unknown(0x06)"
     ///
+    /// A `$` item may end in a parenthesised suffix, which is part of it.
     /// Forbidden syntax includes "()", "(   )", etc.
     fn try_from(string: &str) -> Result<Expression, ExpressionError> {
         let mut expr = Expression::default();
         let mut operators = vec!();
         let mut item = String::new();
         let mut implicit_and = false;
+        let mut in_suffix = false;
 
         // `implicit_and` also implies the last non-space character visited was
         // not syntactic (i.e. an item).
 
         for character in string.chars() {
+            // Flag labels such as "Unknown(0x06)" would otherwise be split by
+            // their own parentheses
+            if in_suffix || (character == '(' && item.starts_with('$') && item.len() > 1) {
+                item.push(character);
+                in_suffix = character != ')';
+                continue;
+            }
+
             match character {
-                '|' | '(' | ')' | ' ' | '\t' => {
-                    if !item.is_empty() {
-                        item = expr.acquire(item);
-                    }
+                '|' | '(' | ')' | ' ' | '\t' if !item.is_empty() => {
+                    item = expr.acquire(item);
                 }
                 _ => {}
             }
@@ -316,7 +325,11 @@ impl TryFrom<&str> for Expression {
             }
         }
 
-        if item.len() > 0 {
+        if in_suffix {
+            return Err(ExpressionError::MISSING_CLOSING_PAREN);
+        }
+
+        if !item.is_empty() {
             expr.acquire(item);
         }
 
@@ -24,7 +24,7 @@ pub trait Bounded: Sized {
 }
 
 pub trait BoundedFrom: FromStr {
-    fn bounded_from(string: &String) -> Result<Self, <Self as FromStr>::Err>;
+    fn bounded_from(string: &str) -> Result<Self, <Self as FromStr>::Err>;
 }
 
 /// Temporary (?) protection from panic on signed MIN abs attempt
@@ -46,16 +46,16 @@ impl Bounded for i32 { fn min_bound() -> Self { Self::MIN } fn max_bound() -> Se
 impl Bounded for f32 { fn min_bound() -> Self { Self::MIN } fn max_bound() -> Self { Self::MAX } }
 impl Bounded for f64 { fn min_bound() -> Self { Self::MIN } fn max_bound() -> Self { Self::MAX } }
 
-impl BoundedFrom for i8  { fn bounded_from(string: &String) -> Result<Self, ParseIntError> { bounded_int_from(string) } }
-impl BoundedFrom for u8  { fn bounded_from(string: &String) -> Result<Self, ParseIntError> { bounded_int_from(string) } }
-impl BoundedFrom for i16 { fn bounded_from(string: &String) -> Result<Self, ParseIntError> { bounded_int_from(string) } }
-impl BoundedFrom for u16 { fn bounded_from(string: &String) -> Result<Self, ParseIntError> { bounded_int_from(string) } }
-impl BoundedFrom for i32 { fn bounded_from(string: &String) -> Result<Self, ParseIntError> { bounded_int_from(string) } }
-impl BoundedFrom for u32 { fn bounded_from(string: &String) -> Result<Self, ParseIntError> { bounded_int_from(string) } }
-impl BoundedFrom for f32 { fn bounded_from(string: &String) -> Result<Self, ParseFloatError> { string.parse() } }
-impl BoundedFrom for f64 { fn bounded_from(string: &String) -> Result<Self, ParseFloatError> { string.parse() } }
+impl BoundedFrom for i8  { fn bounded_from(string: &str) -> Result<Self, ParseIntError> { bounded_int_from(string) } }
+impl BoundedFrom for u8  { fn bounded_from(string: &str) -> Result<Self, ParseIntError> { bounded_int_from(string) } }
+impl BoundedFrom for i16 { fn bounded_from(string: &str) -> Result<Self, ParseIntError> { bounded_int_from(string) } }
+impl BoundedFrom for u16 { fn bounded_from(string: &str) -> Result<Self, ParseIntError> { bounded_int_from(string) } }
+impl BoundedFrom for i32 { fn bounded_from(string: &str) -> Result<Self, ParseIntError> { bounded_int_from(string) } }
+impl BoundedFrom for u32 { fn bounded_from(string: &str) -> Result<Self, ParseIntError> { bounded_int_from(string) } }
+impl BoundedFrom for f32 { fn bounded_from(string: &str) -> Result<Self, ParseFloatError> { string.parse() } }
+impl BoundedFrom for f64 { fn bounded_from(string: &str) -> Result<Self, ParseFloatError> { string.parse() } }
 
-fn bounded_int_from<N>(string: &String) -> Result<N, ParseIntError>
+fn bounded_int_from<N>(string: &str) -> Result<N, ParseIntError>
     where N: Bounded + FromStr<Err = ParseIntError>,
 {
     use IntErrorKind::*;
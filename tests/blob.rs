@@ -0,0 +1,69 @@
+//! The blob's bone ID table follows the records written, and the rest of the
+//! blob is written back as read.
+
+mod common;
+
+use {
+    common::{
+        read,
+        seed,
+    },
+    nmde::nmd::{
+        anatomy::NmdFileBlobSegment,
+        data::NmdFileData,
+        NmdFileWriter,
+    },
+    std::io::Cursor,
+};
+
+fn bone_id_table(data: &NmdFileData) -> Option<&Vec<u16>> {
+    data.blob.segments.iter().find_map(|segment| match segment {
+        NmdFileBlobSegment::BoneIdTable(ids) => Some(ids),
+        _ => None,
+    })
+}
+
+fn raw_segments(data: &NmdFileData) -> Vec<&Vec<u8>> {
+    data.blob.segments.iter()
+        .filter_map(|segment| match segment {
+            NmdFileBlobSegment::Raw(bytes) => Some(bytes),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn id_tables_are_found() {
+    let data = seed();
+    let ids: Vec<_> = data.records().iter().map(|record| record.id).collect();
+
+    assert_eq!(bone_id_table(&data), Some(&ids));
+    assert!(data.blob.annotations().iter().any(|annotation| annotation.label.starts_with("bone ID table")));
+}
+
+#[test]
+fn id_tables_follow_added_and_removed_bones() {
+    let data = seed();
+    let mut bones = data.bones.to_owned();
+    let removed_id = *data.bones.keys()
+        .find(|id| !data.bones[id].is_phys() && !data.bones.values().any(|bone_data| bone_data.parent_id == **id))
+        .expect("a leaf bone");
+    let mut copy = data.bones.values().find(|bone_data| !bone_data.is_phys()).expect("a bone").to_owned();
+
+    copy.id = 0x10;
+    copy.name = format!("{}_COPY", copy.name);
+    bones.insert(copy.id, copy);
+    bones.remove(&removed_id);
+
+    let written = NmdFileWriter::new(Cursor::new(Vec::new()))
+        .write_new(&data, &bones, &data.conflicts, &data.physics, &data.conflict_physics)
+        .expect("write")
+        .into_inner();
+    let read_back = read(written).expect("read back");
+    let ids: Vec<_> = read_back.records().iter().map(|record| record.id).collect();
+
+    assert_eq!(read_back.bones.len(), data.bones.len());
+    assert!(ids.contains(&0x10) && !ids.contains(&removed_id), "{:?}", ids);
+    assert_eq!(bone_id_table(&read_back), Some(&ids));
+    assert_eq!(raw_segments(&read_back), raw_segments(&data));
+}
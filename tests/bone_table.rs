@@ -0,0 +1,65 @@
+//! The bone table goes out as CSV and comes back as the cells that changed,
+//! matched to bones by ID.
+
+mod common;
+
+use {
+    common::seed,
+    nmde::nmd::NmdFileBoneTable,
+    std::io::ErrorKind,
+};
+
+#[test]
+fn unedited_tables_change_nothing() {
+    let data = seed();
+    let text = data.bones.values().collect::<NmdFileBoneTable>().to_csv();
+    let table = NmdFileBoneTable::from_csv(&text).expect("from CSV");
+
+    assert!(table.changes(&data.bones).is_empty());
+    assert_eq!(table.bone_ids().count(), data.bones.len());
+    assert_eq!(table.to_csv(), text);
+}
+
+#[test]
+fn edited_cells_are_the_changes() {
+    let data = seed();
+    let bone_data = data.bones.values().nth(1).expect("a second bone");
+    // Reordered, trimmed down to a few columns and with a quoted name; the
+    // parent is read-only, so isn't a change
+    let text = format!(
+        "name,gravity_x,id,parent_id\r\n\"{},\"\"2\"\"\",{},{},{}\r\n",
+        bone_data.name, bone_data.gravity_x + 1, bone_data.id, bone_data.parent_id.wrapping_add(1),
+    );
+    let table = NmdFileBoneTable::from_csv(&text).expect("from CSV");
+    let changes = table.changes(&data.bones);
+    let applied = table.apply(bone_data);
+
+    assert_eq!(changes.len(), 2);
+    assert!(changes.iter().all(|change| change.bone_id == bone_data.id));
+    assert_eq!(changes[0].column, "name");
+    assert_eq!(changes[1].column, "gravity_x");
+    assert_eq!(applied.name, format!("{},\"2\"", bone_data.name));
+    assert_eq!(applied.gravity_x, bone_data.gravity_x + 1);
+    assert_eq!(applied.parent_id, bone_data.parent_id);
+    assert_eq!(applied.translation_x.to_bits(), bone_data.translation_x.to_bits());
+}
+
+#[test]
+fn malformed_tables_are_rejected() {
+    let cases = [
+        ("", "empty"),
+        ("name\r\nKOSHI\r\n", "`id`"),
+        ("id,height\r\n0,1\r\n", "`height`"),
+        ("id,gravity_x\r\n0,up\r\n", "line 2"),
+        ("id,name\r\n0,KOSHI\r\n0,MOMO\r\n", "line 3"),
+        ("id,name\r\n0,KUBI€\r\n", "`€`"),
+        ("id,name\r\n0,\"KOSHI\r\n", "isn't closed"),
+    ];
+
+    for (text, expected) in cases {
+        let error = NmdFileBoneTable::from_csv(text).expect_err(text);
+
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains(expected), "{:?}: {}", text, error);
+    }
+}
@@ -0,0 +1,124 @@
+//! Files are exported for each game in its byte order, physics records and blob
+//! tables included, as far as bytes that aren't decoded allow, and only while
+//! their header fields can hold them.
+
+mod common;
+
+use {
+    common::{
+        load,
+        read,
+        write_for,
+        CONFLICTS_PATH,
+        SCV_PATH,
+        SEED_PATH,
+    },
+    nmde::nmd::{
+        anatomy::{
+            token::NmdFileToken,
+            NmdFileBone,
+            NmdFileVersion,
+        },
+        data::NmdFileData,
+        NmdError,
+        NmdFileWriter,
+    },
+    std::{
+        collections::BTreeMap,
+        fs,
+        io::Cursor,
+    },
+};
+
+#[test]
+fn every_version_is_written_in_its_byte_order() {
+    let data = load(SCV_PATH);
+
+    assert_eq!(data.export_versions(), NmdFileVersion::VERSIONS);
+
+    for version in NmdFileVersion::VERSIONS {
+        let bytes = write_for(&data, *version).expect("export");
+        let read_back = read(bytes.to_vec()).expect("read back");
+
+        assert_eq!(read_back.version.byte_order(), version.byte_order(), "{}", version);
+        assert_eq!(read_back.bones.len(), data.bones.len(), "{}", version);
+
+        for (bone_data, read_bone_data) in data.bones.values().zip(read_back.bones.values()) {
+            assert_eq!(read_bone_data.name, bone_data.name, "{}", version);
+            assert_eq!(read_bone_data.parent_id, bone_data.parent_id, "{}", version);
+            assert_eq!(read_bone_data.translation_x.to_bits(), bone_data.translation_x.to_bits(), "{}", version);
+        }
+
+        if version.byte_order() == data.version.byte_order() {
+            assert_eq!(bytes, fs::read(SCV_PATH).expect("seed"), "{}", version);
+        }
+    }
+}
+
+fn labels(data: &NmdFileData) -> Vec<String> {
+    data.blob.annotations().into_iter().map(|annotation| annotation.label).collect()
+}
+
+#[test]
+fn physics_and_blob_data_change_byte_order() {
+    let data = load(CONFLICTS_PATH);
+
+    assert_eq!(data.export_versions(), NmdFileVersion::VERSIONS);
+
+    let bytes = write_for(&data, NmdFileVersion::Sc5).expect("export");
+    let read_back = read(bytes.to_vec()).expect("read back");
+
+    assert_eq!(read_back.version.byte_order(), NmdFileVersion::Sc5.byte_order());
+    assert_eq!(read_back.records().len(), data.records().len());
+    assert_eq!(labels(&read_back), labels(&data));
+    assert_eq!(read_back.physics.len() + read_back.conflict_physics.len(), data.physics.len() + data.conflict_physics.len());
+
+    for (physics, read_physics) in data.physics.values().chain(data.conflict_physics.values())
+        .zip(read_back.physics.values().chain(read_back.conflict_physics.values()))
+    {
+        assert!(read_physics.same_as(physics), "{:?} became {:?}", physics, read_physics);
+    }
+
+    // And back again, byte for byte
+    assert_eq!(write_for(&read_back, NmdFileVersion::Sc6).expect("export back"), fs::read(CONFLICTS_PATH).expect("seed"));
+}
+
+#[test]
+fn undecoded_bytes_keep_their_byte_order() {
+    // The seed's physics block starts with bytes no record covers
+    let data = load(SEED_PATH);
+
+    assert_eq!(data.export_versions(), [NmdFileVersion::Sc6]);
+    assert_eq!(write_for(&data, NmdFileVersion::Sc6).expect("export"), fs::read(SEED_PATH).expect("seed"));
+
+    for version in [NmdFileVersion::Sc4, NmdFileVersion::Sc5] {
+        match write_for(&data, version) {
+            Err(NmdError::ByteOrderConversion(from, to)) => assert_eq!((from, to), (NmdFileVersion::Sc6, version)),
+            result => panic!("expected a byte order error for {}, got {:?}", version, result.map(|bytes| bytes.len())),
+        }
+    }
+}
+
+#[test]
+fn bone_counts_past_the_header_field_are_refused() {
+    let data = load(SCV_PATH);
+    let bone_data = data.bones.values().next().expect("a bone");
+    let bones: BTreeMap<u16, NmdFileBone> = (0..=u16::MAX)
+        .map(|id| {
+            let mut bone_data = bone_data.to_owned();
+
+            bone_data.id = id;
+            (id, bone_data)
+        })
+        .collect();
+    let result = NmdFileWriter::new(Cursor::new(Vec::new()))
+        .write_new(&data, &bones, &data.conflicts, &data.physics, &data.conflict_physics);
+
+    match result {
+        Err(NmdError::AddressOutOfBounds { token, address, .. }) => {
+            assert_eq!(token, NmdFileToken::HeaderBoneCount);
+            assert_eq!(address, 0x10000);
+        }
+        result => panic!("expected an out of bounds error, got {:?}", result.map(|cursor| cursor.into_inner().len())),
+    }
+}
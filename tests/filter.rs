@@ -0,0 +1,62 @@
+//! Filter expressions group with parentheses, except for the suffix of a `$`
+//! flag item, so every flag label can be searched for as shown.
+
+use {
+    nmde::utils::filter::{
+        Expression,
+        Filter,
+    },
+    std::collections::BTreeSet,
+};
+
+// Each label is "<name> <flag>", both lowercase; as in the bone tree, `$`
+// items search flags and any other item names
+struct LabelFilter {
+    labels: BTreeSet<String>,
+}
+
+impl Filter for LabelFilter {
+    type FilterSet = BTreeSet<String>;
+
+    fn collection(&self) -> &Self::FilterSet {
+        &self.labels
+    }
+
+    fn search(&self, needle: &str, superset: &Self::FilterSet) -> Self::FilterSet {
+        superset.iter()
+            .filter(|label| {
+                let (name, flag) = label.split_once(' ').expect("name and flag");
+
+                match needle.strip_prefix('$') {
+                    Some(needle) => flag.contains(needle),
+                    None => name.contains(needle),
+                }
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+fn query(text: &str) -> Vec<String> {
+    let filter = LabelFilter {
+        labels: ["koshi standard", "kami_swing swing", "kami_odd unknown(0x06)", "momo_odd unknown(0x07)"]
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+    };
+    let expression = Expression::try_from(text).expect("expression");
+
+    filter.query(&expression).expect("query").into_iter().collect()
+}
+
+#[test]
+fn unknown_flags_are_found_by_label() {
+    assert_eq!(query("$unknown(0x06)"), ["kami_odd unknown(0x06)"]);
+    assert_eq!(query("kami ($swing | $unknown(0x06))"), ["kami_odd unknown(0x06)", "kami_swing swing"]);
+    assert_eq!(query("$unknown(0x07)|koshi"), ["koshi standard", "momo_odd unknown(0x07)"]);
+}
+
+#[test]
+fn unclosed_flag_suffixes_are_refused() {
+    assert!(Expression::try_from("$unknown(0x06").is_err());
+}
@@ -0,0 +1,175 @@
+//! The bone hierarchy goes out as glTF nodes, one per bone, under their
+//! parents, and placements come back in by node name.
+
+mod common;
+
+use {
+    common::seed,
+    nmde::nmd::{
+        anatomy::NmdFileBone,
+        NmdFileGltf,
+        NmdFileGltfBoneExtras,
+        NmdFileGltfImport,
+        NmdFileGltfParent,
+    },
+    serde_json::Value,
+    std::{
+        collections::BTreeMap,
+        io::ErrorKind,
+    },
+};
+
+fn extras(gltf: &NmdFileGltf, index: usize) -> NmdFileGltfBoneExtras {
+    serde_json::from_value(gltf.nodes[index].extras.to_owned().expect("extras")).expect("bone extras")
+}
+
+#[test]
+fn every_bone_is_a_node_under_its_parent() {
+    let data = seed();
+    let gltf = NmdFileGltf::from(&data);
+    let document: Value = serde_json::from_str(&gltf.to_json().expect("to JSON")).expect("JSON");
+
+    assert_eq!(document["asset"]["version"], "2.0");
+    assert_eq!(gltf.nodes.len(), data.bones.len());
+    assert_eq!(document["skins"][0]["joints"].as_array().expect("joints").len(), data.bones.len());
+
+    for (index, node) in gltf.nodes.iter().enumerate() {
+        let bone_data = &data.bones[&extras(&gltf, index).id];
+
+        assert_eq!(node.name.as_deref(), Some(bone_data.name.as_str()));
+
+        for child_index in &node.children {
+            assert_eq!(extras(&gltf, *child_index).parent_id, bone_data.id);
+        }
+    }
+
+    for root_index in document["scenes"][0]["nodes"].as_array().expect("roots") {
+        let root_index = root_index.as_u64().expect("index") as usize;
+
+        assert_eq!(extras(&gltf, root_index).parent_id, NmdFileBone::ROOT_BONE_ID);
+    }
+}
+
+#[test]
+fn placements_are_converted() {
+    let mut data = seed();
+    let bone_data = data.bones.values_mut().next().expect("a bone");
+
+    bone_data.translation_x = 150.0;
+    bone_data.rotation_x = 0.0;
+    bone_data.rotation_y = 0.0;
+    bone_data.rotation_z = 90.0;
+
+    let id = bone_data.id;
+    let gltf = NmdFileGltf::from(&data);
+    let index = (0..gltf.nodes.len()).find(|index| extras(&gltf, *index).id == id).expect("the bone's node");
+    let [x, y, z, w] = gltf.nodes[index].rotation;
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+
+    assert_eq!(gltf.nodes[index].translation[0], 1.5);
+    assert!(x.abs() < 1e-6 && y.abs() < 1e-6, "{:?}", gltf.nodes[index].rotation);
+    assert!((z - half).abs() < 1e-6 && (w - half).abs() < 1e-6, "{:?}", gltf.nodes[index].rotation);
+}
+
+#[test]
+fn unedited_skeletons_change_nothing() {
+    let data = seed();
+    let text = NmdFileGltf::from(&data).to_json().expect("to JSON");
+    let import = NmdFileGltfImport::new(NmdFileGltf::from_json(&text).expect("from JSON"), &data.bones, &data.physics).expect("import");
+
+    assert!(import.changes(&data.bones).is_empty());
+    assert!(import.unmatched_bone_ids().is_empty());
+    assert!(import.unmatched_nodes().is_empty());
+    assert_eq!(import.bone_ids().count(), data.bones.len());
+}
+
+#[test]
+fn edited_nodes_are_matched_by_name() {
+    let data = seed();
+    let mut gltf = NmdFileGltf::from(&data);
+    let parent_index = (0..gltf.nodes.len()).find(|index| !gltf.nodes[*index].children.is_empty()).expect("a parent");
+    let child_index = gltf.nodes[parent_index].children[0];
+    let parent_id = extras(&gltf, parent_index).id;
+    let child_id = extras(&gltf, child_index).id;
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+
+    gltf.nodes[parent_index].translation[0] += 0.5;
+    gltf.nodes[parent_index].rotation = [0.0, 0.0, half, half];
+    gltf.nodes[child_index].name = Some(String::from("SHIPPO_NEW"));
+
+    let import = NmdFileGltfImport::new(gltf, &data.bones, &data.physics).expect("import");
+    let changes = import.changes(&data.bones);
+    let applied = import.apply(&data.bones[&parent_id]);
+
+    assert!(changes.iter().all(|change| change.bone_id == parent_id));
+    assert!(changes.iter().any(|change| change.column == "translation_x"));
+    assert!((applied.translation_x - (data.bones[&parent_id].translation_x + 50.0)).abs() < 1e-3);
+    assert!(applied.rotation_x.abs() < 1e-3 && applied.rotation_y.abs() < 1e-3, "{:?}", applied);
+    assert!((applied.rotation_z - 90.0).abs() < 1e-3, "{:?}", applied);
+
+    assert_eq!(import.unmatched_bone_ids(), [child_id]);
+    assert_eq!(import.unmatched_nodes(), [child_index]);
+    assert_eq!(import.parent(child_index), NmdFileGltfParent::Bone(parent_id));
+
+    let (bone_data, _) = import.new_bone(child_index, 0x7FFF, parent_id);
+
+    assert_eq!(bone_data.name, "SHIPPO_NEW");
+    assert_eq!(bone_data.parent_id, parent_id);
+    assert_eq!(bone_data.translation_y.to_bits(), data.bones[&child_id].translation_y.to_bits());
+}
+
+#[test]
+fn new_physics_bones_copy_a_record_of_their_flag() {
+    let data = seed();
+    let mut gltf = NmdFileGltf::from(&data);
+    let index = (0..gltf.nodes.len()).find(|index| data.bones[&extras(&gltf, *index).id].is_phys()).expect("a physics bone's node");
+    let id = extras(&gltf, index).id;
+
+    gltf.nodes[index].name = Some(String::from("KAMI_SWING_NEW"));
+
+    let import = NmdFileGltfImport::new(gltf.to_owned(), &data.bones, &data.physics).expect("import");
+    let (bone_data, physics_opt) = import.new_bone(index, 0x7FFF, data.bones[&id].parent_id);
+    let physics = physics_opt.expect("a physics record");
+
+    assert!(import.creatable(index));
+    assert_eq!(bone_data.flag, data.bones[&id].flag);
+    assert_eq!(physics.id, 0x7FFF);
+    assert_eq!(physics.parameter_a.to_bits(), data.physics[&id].parameter_a.to_bits());
+    assert_eq!(physics.unknown_data, data.physics[&id].unknown_data);
+
+    // Nothing to copy the rest of the record from
+    let import = NmdFileGltfImport::new(gltf, &data.bones, &BTreeMap::new()).expect("import");
+
+    assert!(!import.creatable(index));
+    assert!(import.new_bone(index, 0x7FFF, data.bones[&id].parent_id).1.is_none());
+}
+
+#[test]
+fn unknown_flags_are_rejected() {
+    let data = seed();
+    let mut gltf = NmdFileGltf::from(&data);
+
+    gltf.nodes[0].name = Some(String::from("KOSHI_NEW"));
+    gltf.nodes[0].extras.as_mut().expect("extras")["flag"] = Value::from("Wobbly");
+
+    let error = NmdFileGltfImport::new(gltf, &data.bones, &data.physics).expect_err("an unknown flag");
+
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert!(error.to_string().contains("KOSHI_NEW") && error.to_string().contains("Wobbly"), "{}", error);
+}
+
+#[test]
+fn malformed_hierarchies_are_rejected() {
+    let cases = [
+        (r#"{"asset":{"version":"2.0"},"nodes":[{"children":[1]}]}"#, "doesn't exist"),
+        (r#"{"asset":{"version":"2.0"},"nodes":[{"children":[2]},{"children":[2]},{}]}"#, "more than one parent"),
+        (r#"{"asset":{"version":"2.0"},"nodes":[{"children":[1]},{"children":[0]}]}"#, "cycle"),
+    ];
+
+    for (text, expected) in cases {
+        let error = NmdFileGltf::from_json(text).expect_err(text);
+
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains(expected), "{:?}: {}", text, error);
+    }
+}
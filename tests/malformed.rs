@@ -0,0 +1,102 @@
+//! Malformed files must either load or give an error, never panic. The corpus
+//! under `tests/corpus` holds two valid seeds (`seed_*.nmd`) and hand-mutated
+//! copies of them; the seeds are also mutated byte by byte here.
+
+mod common;
+
+use {
+    common::CORPUS_DIR,
+    nmde::nmd::{
+        anatomy::NmdFileHeader,
+        data::NmdFileData,
+        NmdFileMap,
+        NmdFileReader,
+        NmdFileValidator,
+        NmdFileVerifier,
+        NmdFileWriter,
+    },
+    std::{
+        fs,
+        io::Cursor,
+        path::PathBuf,
+    },
+};
+
+// Values written over each header byte in turn
+const MUTATIONS: &[u8] = &[0x00, 0x01, 0x20, 0x7F, 0x80, 0xFE, 0xFF];
+
+fn corpus() -> Vec<PathBuf> {
+    let mut paths: Vec<_> = fs::read_dir(CORPUS_DIR)
+        .expect("corpus directory")
+        .map(|entry| entry.expect("corpus entry").path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "nmd"))
+        .collect();
+
+    paths.sort();
+    paths
+}
+
+fn seeds() -> Vec<Vec<u8>> {
+    corpus().iter()
+        .filter(|path| path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("seed_")))
+        .map(|path| fs::read(path).expect("seed"))
+        .collect()
+}
+
+// Read, map, validate and write back out; any of these may fail, none may
+// panic
+fn exercise(bytes: &[u8]) {
+    let _ = NmdFileMap::try_from(bytes);
+
+    let data = match NmdFileReader::try_from(bytes.to_vec()).and_then(|mut reader| NmdFileData::try_from(&mut reader)) {
+        Ok(data) => data,
+        Err(error) => {
+            // Errors are displayed to the user, so must format too
+            let _ = error.to_string();
+            return;
+        }
+    };
+
+    for finding in NmdFileValidator::from(&data).validate() {
+        let _ = finding.to_string();
+    }
+
+    let _ = NmdFileWriter::new(Cursor::new(Vec::new()))
+        .write_new(&data, &data.bones, &data.conflicts, &data.physics, &data.conflict_physics);
+}
+
+#[test]
+fn corpus_loads_or_fails_cleanly() {
+    for path in corpus() {
+        exercise(&fs::read(&path).expect("corpus file"));
+
+        let verified = NmdFileVerifier::try_from(&path).and_then(|verifier| verifier.verify());
+
+        if path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("seed_")) {
+            assert!(matches!(verified, Ok(None)), "{} should survive an unedited export", path.display());
+        }
+    }
+}
+
+#[test]
+fn mutated_headers_load_or_fail_cleanly() {
+    for seed in seeds() {
+        for offset in 0..NmdFileHeader::CHUNK_SIZE as usize {
+            for value in MUTATIONS {
+                let mut bytes = seed.clone();
+
+                bytes[offset] = *value;
+                exercise(&bytes);
+            }
+        }
+    }
+}
+
+#[test]
+fn truncated_files_load_or_fail_cleanly() {
+    for seed in seeds() {
+        for length in 0..seed.len() {
+            exercise(&seed[..length]);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Bone names are written as shifted ASCII, with identical names sharing one
+//! string when the name block has to be rewritten.
+
+mod common;
+
+use {
+    common::{
+        read,
+        seed,
+        write,
+    },
+    nmde::nmd::{
+        data::NmdFileData,
+        NmdError,
+        NmdFileBlock,
+        NmdFileBoneOrder,
+        NmdFileMap,
+        NmdFileWriter,
+    },
+    std::io::Cursor,
+};
+
+#[test]
+fn duplicate_names_share_a_string() {
+    let mut data = seed();
+    let ids: Vec<u16> = data.bones.keys().copied().collect();
+    let name = data.bones[&ids[0]].name.to_owned();
+    let unedited = write(&data).expect("unedited");
+
+    data.bones.get_mut(&ids[1]).expect("second bone").name = name.to_owned();
+
+    let written = write(&data).expect("renamed");
+    let read = read(written.to_vec()).expect("read back");
+    let old_name_length = seed().bones[&ids[1]].name.len() + 1;
+
+    assert_eq!(read.bones[&ids[0]].name, name);
+    assert_eq!(read.bones[&ids[1]].name, name);
+    assert_eq!(written.len(), unedited.len() - old_name_length);
+}
+
+#[test]
+fn unencodable_names_are_rejected() {
+    let mut data = seed();
+    let id = *data.bones.keys().next().expect("a bone");
+
+    data.bones.get_mut(&id).expect("first bone").name = String::from("KUBI€");
+
+    match write(&data) {
+        Err(NmdError::UnencodableName { bone_id, character, .. }) => {
+            assert_eq!(bone_id, id);
+            assert_eq!(character, '€');
+        }
+        result => panic!("expected an unencodable name error, got {:?}", result.map(|bytes| bytes.len())),
+    }
+}
+
+// Written back with a bone renumbered, which moves its record to the end
+fn renumbered(data: &NmdFileData, from_id: u16, to_id: u16) -> NmdFileData {
+    let mut bones = data.bones.to_owned();
+    let mut bone_data = bones.remove(&from_id).expect("bone to renumber");
+
+    bone_data.id = to_id;
+    bones.insert(to_id, bone_data);
+
+    let written = NmdFileWriter::new(Cursor::new(Vec::new()))
+        .write_new(data, &bones, &data.conflicts, &data.physics, &data.conflict_physics)
+        .expect("renumbered")
+        .into_inner();
+
+    read(written).expect("read renumbered")
+}
+
+#[test]
+fn names_follow_bones_written_by_id() {
+    let data = renumbered(&renumbered(&seed(), 0x01, 0x10), 0x10, 0x01);
+
+    assert_eq!(data.records().iter().map(|record| record.id).collect::<Vec<_>>(), [0x00, 0x02, 0x03, 0x01]);
+
+    let written = NmdFileWriter::new(Cursor::new(Vec::new()))
+        .with_bone_order(NmdFileBoneOrder::ById)
+        .write_new(&data, &data.bones, &data.conflicts, &data.physics, &data.conflict_physics)
+        .expect("write by ID")
+        .into_inner();
+    let read = read(written).expect("read back");
+    let ids: Vec<u16> = read.records().iter().map(|record| record.id).collect();
+    let name_addresses: Vec<u64> = read.records().iter().map(|record| record.name_address as u64).collect();
+
+    assert_eq!(ids, [0x00, 0x01, 0x02, 0x03]);
+    assert!(name_addresses.windows(2).all(|pair| pair[0] < pair[1]), "{:X?}", name_addresses);
+    assert_eq!(read.bones[&0x01].name, "MOMO_L");
+}
+
+#[test]
+fn name_spans_cover_one_byte_a_character() {
+    let mut data = seed();
+
+    for bone_data in data.bones.values_mut() {
+        bone_data.name.push('¥');
+    }
+
+    // Padding past the last name, so its span isn't clipped to the file
+    let mut written = write(&data).expect("renamed");
+    written.extend([0; 4]);
+
+    let map = NmdFileMap::try_from(&written[..]).expect("map");
+    let read = read(written.to_vec()).expect("read back");
+    let records = read.records();
+
+    for span in map.spans() {
+        if let NmdFileBlock::BoneName(indices) = &span.block {
+            let name = &data.bones[&records[indices[0]].id].name;
+
+            assert_eq!(span.range.len(), name.chars().count() + 1, "{}", name);
+        }
+    }
+}
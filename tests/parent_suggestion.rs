@@ -0,0 +1,78 @@
+//! Orphans are offered parents by mirror image first, then by numbered
+//! sequence, then by how many leading name parts they share.
+
+use nmde::nmd::{
+    anatomy::NmdFileBone,
+    data::{
+        NmdFileParentReason,
+        NmdFileParentSuggestion,
+    },
+};
+
+const MISSING_ID: u16 = 0x77;
+
+fn bone(bone_id: u16, name: &str, parent: u16) -> NmdFileBone {
+    NmdFileBone {
+        id: bone_id,
+        name: String::from(name),
+        parent_id: parent,
+        ..Default::default()
+    }
+}
+
+// KATA_L, KAMI_03 and TE_L_YUBI have lost their parents; KAMI_04 hangs off
+// KAMI_03
+fn skeleton() -> Vec<NmdFileBone> {
+    vec![
+        bone(0x00, "KOSHI", NmdFileBone::ROOT_BONE_ID),
+        bone(0x01, "SAKOTSU_L", 0x00),
+        bone(0x02, "SAKOTSU_R", 0x00),
+        bone(0x03, "KATA_R", 0x02),
+        bone(0x04, "KATA_L", MISSING_ID),
+        bone(0x05, "KAMI_01", 0x00),
+        bone(0x06, "KAMI_02", 0x05),
+        bone(0x07, "KAMI_03", MISSING_ID),
+        bone(0x08, "KAMI_04", 0x07),
+        bone(0x09, "TE_L", 0x00),
+        bone(0x0A, "TE_R", 0x00),
+        bone(0x0B, "TE_L_YUBI", MISSING_ID),
+    ]
+}
+
+fn suggestions(orphan_id: u16) -> Vec<(u16, NmdFileParentReason)> {
+    NmdFileParentSuggestion::for_orphan(&skeleton(), orphan_id)
+        .into_iter()
+        .map(|suggestion| (suggestion.parent_id, suggestion.reason))
+        .collect()
+}
+
+#[test]
+fn mirror_parents_come_first() {
+    assert_eq!(suggestions(0x04), [
+        (0x01, NmdFileParentReason::Mirror(String::from("KATA_R"), String::from("SAKOTSU_R"))),
+        (0x03, NmdFileParentReason::NamePrefix(1)),
+    ]);
+}
+
+#[test]
+fn sequences_come_before_name_prefixes_and_descendants_are_left_out() {
+    // KAMI_02 is suggested once, for its best reason; KAMI_04 is beneath the
+    // orphan
+    assert_eq!(suggestions(0x07), [
+        (0x06, NmdFileParentReason::Sequence),
+        (0x05, NmdFileParentReason::NamePrefix(1)),
+    ]);
+}
+
+#[test]
+fn longer_name_prefixes_come_first() {
+    assert_eq!(suggestions(0x0B), [
+        (0x09, NmdFileParentReason::NamePrefix(2)),
+        (0x0A, NmdFileParentReason::NamePrefix(1)),
+    ]);
+}
+
+#[test]
+fn unknown_bones_get_no_suggestions() {
+    assert!(suggestions(MISSING_ID).is_empty());
+}
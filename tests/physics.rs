@@ -0,0 +1,120 @@
+//! Each physics bone keeps its own physics record, including bones whose ID an
+//! earlier record already has and bones edited to point into another's.
+
+mod common;
+
+use {
+    common::{
+        load,
+        read,
+        seed,
+        write,
+        CONFLICTS_PATH,
+    },
+    nmde::nmd::{
+        NmdError,
+        NmdFileWriter,
+    },
+    std::{
+        fs,
+        io::Cursor,
+    },
+};
+
+#[test]
+fn conflicting_records_keep_their_physics() {
+    let data = load(CONFLICTS_PATH);
+    let (record_index, conflict) = data.conflicts.iter().next().expect("a conflicting record");
+    let primary = &data.bones[&conflict.id];
+
+    assert!(primary.is_phys() && conflict.is_phys());
+    assert_ne!(primary.physics_data_address, conflict.physics_data_address);
+    assert_eq!(data.physics[&primary.id].len(), 0x30);
+    assert_eq!(data.conflict_physics[record_index].len(), 0x10);
+    assert_eq!(write(&data).expect("write"), fs::read(CONFLICTS_PATH).expect("seed"));
+}
+
+#[test]
+fn pasted_physics_bones_get_their_own_records() {
+    let data = seed();
+    let mut bones = data.bones.to_owned();
+    let mut physics_map = data.physics.to_owned();
+    let source = data.bones.values().find(|bone_data| bone_data.is_phys()).expect("a physics bone");
+    let mut copy = source.to_owned();
+    let mut physics = data.physics[&source.id].to_owned();
+
+    // As pasting does, address and all
+    copy.id = 0x10;
+    copy.name = format!("{}_COPY", source.name);
+    physics.id = copy.id;
+    physics.parameter_a += 1.0;
+    bones.insert(copy.id, copy.to_owned());
+    physics_map.insert(copy.id, physics.to_owned());
+
+    let written = NmdFileWriter::new(Cursor::new(Vec::new()))
+        .write_new(&data, &bones, &data.conflicts, &physics_map, &data.conflict_physics)
+        .expect("write")
+        .into_inner();
+    let read = read(written.to_vec()).expect("read");
+    let read_source = &read.bones[&source.id];
+    let read_copy = &read.bones[&copy.id];
+
+    assert_eq!(read.raw_physics_data().len(), seed().raw_physics_data().len() + physics.len());
+    assert_ne!(read_source.physics_data_address, read_copy.physics_data_address);
+    assert_eq!(read.physics[&source.id].parameter_a, data.physics[&source.id].parameter_a);
+    assert_eq!(read.physics[&copy.id].parameter_a, physics.parameter_a);
+    assert_eq!(write(&read).expect("write back"), written);
+}
+
+#[test]
+fn records_edited_to_overlap_are_moved_apart() {
+    let data = seed();
+    let mut bones = data.bones.to_owned();
+    let mut physics_map = data.physics.to_owned();
+    let source = data.bones.values().find(|bone_data| bone_data.is_phys()).expect("a physics bone");
+    let mut copy = source.to_owned();
+    let mut physics = data.physics[&source.id].to_owned();
+
+    // Part way into the block, so the copy's record would run into its source's
+    copy.id = 0x10;
+    copy.physics_data_address = source.physics_data_address - 0x08;
+    physics.id = copy.id;
+    physics.parameter_a += 1.0;
+    bones.insert(copy.id, copy.to_owned());
+    physics_map.insert(copy.id, physics.to_owned());
+
+    let written = NmdFileWriter::new(Cursor::new(Vec::new()))
+        .write_new(&data, &bones, &data.conflicts, &physics_map, &data.conflict_physics)
+        .expect("write")
+        .into_inner();
+    let read = read(written).expect("read");
+
+    assert_eq!(read.raw_physics_data().len(), data.raw_physics_data().len() + physics.len());
+    assert_eq!(read.bones[&source.id].physics_data_address - read.header.physics_data_address(),
+               source.physics_data_address - data.header.physics_data_address());
+    assert!(read.physics[&source.id].same_as(&data.physics[&source.id]));
+    assert_eq!(read.physics[&copy.id].parameter_a, physics.parameter_a);
+}
+
+#[test]
+fn shared_records_edited_apart_are_rejected() {
+    let mut bytes = fs::read(CONFLICTS_PATH).expect("seed");
+
+    // Point the conflicting record (the fourth) at the record the bone before
+    // it has
+    bytes[0x20 + 3 * 0x70 + 0x38..][..4].copy_from_slice(&0x1F0u32.to_le_bytes());
+
+    let mut data = read(bytes.to_vec()).expect("read");
+
+    assert_eq!(write(&data).expect("unedited"), bytes);
+
+    data.conflict_physics.values_mut().next().expect("a conflicting record's physics").parameter_a += 1.0;
+
+    match write(&data) {
+        Err(NmdError::SharedPhysicsRecord { address, bone_ids }) => {
+            assert_eq!(address, 0x1F0);
+            assert_eq!(bone_ids, (0x03, 0x03));
+        }
+        result => panic!("expected a shared record error, got {:?}", result.map(|bytes| bytes.len())),
+    }
+}
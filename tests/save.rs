@@ -0,0 +1,103 @@
+//! Saving goes through a temporary file beside the target, which only replaces
+//! it once written in full and read back.
+
+mod common;
+
+use {
+    common::{
+        seed,
+        SEED_PATH,
+    },
+    nmde::nmd::{
+        anatomy::NmdFileVersion,
+        NmdFileSave,
+        NmdFileWriter,
+    },
+    std::{
+        env,
+        fs,
+        path::PathBuf,
+        process,
+    },
+};
+
+// A folder of its own, so leftover files can be told apart
+fn temp_dir(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("nmde-test-{}-{}", name, process::id()));
+
+    fs::remove_dir_all(&path).ok();
+    fs::create_dir_all(&path).expect("temp dir");
+    path
+}
+
+#[test]
+fn save_replaces_target_and_backs_it_up() {
+    let dir = temp_dir("save");
+    let path = dir.join("out.nmd");
+    let data = seed();
+
+    fs::write(&path, b"previous").expect("previous file");
+
+    let backup_path = NmdFileWriter::new(NmdFileSave::try_from(&path).expect("save").with_backup(true))
+        .write_new(&data, &data.bones, &data.conflicts, &data.physics, &data.conflict_physics)
+        .and_then(NmdFileSave::commit)
+        .expect("commit")
+        .expect("backup path");
+
+    assert_eq!(fs::read(&path).expect("saved file"), fs::read(SEED_PATH).expect("seed"));
+    assert_eq!(fs::read(&backup_path).expect("backup"), b"previous");
+    assert_eq!(fs::read_dir(&dir).expect("temp dir").count(), 2, "only the target and its backup should be left");
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn backups_never_replace_each_other() {
+    let dir = temp_dir("backups");
+    let path = dir.join("out.nmd");
+    let data = seed();
+    let mut backup_paths = vec![];
+
+    fs::write(&path, b"previous").expect("previous file");
+
+    // Close enough together to fall in the same second
+    for _ in 0..3 {
+        let backup_path = NmdFileWriter::new(NmdFileSave::try_from(&path).expect("save").with_backup(true))
+            .write_new(&data, &data.bones, &data.conflicts, &data.physics, &data.conflict_physics)
+            .and_then(NmdFileSave::commit)
+            .expect("commit")
+            .expect("backup path");
+
+        backup_paths.push(backup_path);
+    }
+
+    backup_paths.sort();
+    backup_paths.dedup();
+
+    assert_eq!(backup_paths.len(), 3);
+    assert_eq!(fs::read_dir(&dir).expect("temp dir").count(), 4, "the target and three backups should be left");
+    assert!(backup_paths.iter().any(|backup_path| fs::read(backup_path).expect("backup") == b"previous"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn failed_save_leaves_target_alone() {
+    let dir = temp_dir("failed-save");
+    let path = dir.join("out.nmd");
+    let data = seed();
+
+    fs::write(&path, b"previous").expect("previous file");
+
+    // Raw blocks can't be written in another byte order
+    let result = NmdFileWriter::new(NmdFileSave::try_from(&path).expect("save").with_backup(true))
+        .for_version(NmdFileVersion::Sc5)
+        .write_new(&data, &data.bones, &data.conflicts, &data.physics, &data.conflict_physics)
+        .and_then(NmdFileSave::commit);
+
+    assert!(result.is_err());
+    assert_eq!(fs::read(&path).expect("previous file"), b"previous");
+    assert_eq!(fs::read_dir(&dir).expect("temp dir").count(), 1, "the temporary file should be removed");
+
+    fs::remove_dir_all(&dir).ok();
+}
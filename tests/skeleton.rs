@@ -0,0 +1,117 @@
+//! An unedited skeleton, as TOML or JSON, writes back the file it was made
+//! from byte for byte.
+
+mod common;
+
+use {
+    common::{
+        load,
+        seed,
+        write,
+        CONFLICTS_PATH,
+        SCV_PATH,
+        SEED_PATH,
+    },
+    nmde::nmd::{
+        data::NmdFileData,
+        NmdFile,
+        NmdFileSkeleton,
+    },
+    std::{
+        fs,
+        io::ErrorKind,
+        path::PathBuf,
+    },
+};
+
+const SEED_PATHS: &[&str] = &[SCV_PATH, SEED_PATH, CONFLICTS_PATH];
+
+#[test]
+fn skeletons_round_trip() {
+    for seed_path in SEED_PATHS {
+        let path = PathBuf::from(seed_path);
+        let bytes = fs::read(&path).expect("seed");
+        let data = NmdFile::try_from(&path).expect("seed").data;
+        let skeleton = NmdFileSkeleton::from(&data);
+
+        let from_toml = NmdFileSkeleton::from_toml(&skeleton.to_toml().expect("to TOML")).expect("from TOML");
+        let from_json = NmdFileSkeleton::from_json(&skeleton.to_json().expect("to JSON")).expect("from JSON");
+
+        // Physics are copied as read, so would come out the same without them
+        assert_eq!(NmdFileData::from(&from_toml).conflict_physics.keys().collect::<Vec<_>>(), data.conflict_physics.keys().collect::<Vec<_>>(), "{}", seed_path);
+
+        assert!(write(&NmdFileData::from(&from_toml)).expect("write") == bytes, "{} changed through TOML", seed_path);
+        assert!(write(&NmdFileData::from(&from_json)).expect("write") == bytes, "{} changed through JSON", seed_path);
+    }
+}
+
+#[test]
+fn floats_keep_their_bits() {
+    // A NaN with a payload, a negative one, negative zero and infinity
+    let bits = [0x7FC00001, 0xFFC00002, 0x80000000, 0x7F800000];
+    let mut data = seed();
+    let bone_data = data.bones.values_mut().find(|bone_data| bone_data.is_phys()).expect("a physics bone");
+    let id = bone_data.id;
+
+    bone_data.translation_x = f32::from_bits(bits[0]);
+    bone_data.translation_y = f32::from_bits(bits[1]);
+    bone_data.translation_z = f32::from_bits(bits[2]);
+    data.physics.get_mut(&id).expect("its physics").parameter_a = f32::from_bits(bits[3]);
+
+    let skeleton = NmdFileSkeleton::from(&data);
+    let toml = skeleton.to_toml().expect("to TOML");
+    let json = skeleton.to_json().expect("to JSON");
+
+    assert!(toml.contains("\"0x7FC00001\"") && json.contains("\"0x7FC00001\""), "NaN should be written as its bits");
+
+    for (text, from_text) in [
+        ("TOML", NmdFileSkeleton::from_toml(&toml).expect("from TOML")),
+        ("JSON", NmdFileSkeleton::from_json(&json).expect("from JSON")),
+    ] {
+        let read = NmdFileData::from(&from_text);
+        let bone_data = &read.bones[&id];
+        let read_bits = [
+            bone_data.translation_x.to_bits(),
+            bone_data.translation_y.to_bits(),
+            bone_data.translation_z.to_bits(),
+            read.physics[&id].parameter_a.to_bits(),
+        ];
+
+        assert_eq!(read_bits, bits, "changed through {}", text);
+    }
+}
+
+#[test]
+fn newer_skeletons_are_rejected() {
+    let path = PathBuf::from(SEED_PATHS[0]);
+    let text = NmdFileSkeleton::from(&NmdFile::try_from(&path).expect("seed").data)
+        .to_toml()
+        .expect("to TOML")
+        .replacen("nmde_skeleton = 1", "nmde_skeleton = 2", 1);
+
+    let error = NmdFileSkeleton::from_toml(&text).expect_err("a newer version");
+
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert!(error.to_string().contains("version 2"), "{}", error);
+}
+
+#[test]
+fn conflicts_keep_their_records_past_inserted_bones() {
+    let data = load(CONFLICTS_PATH);
+    let mut skeleton = NmdFileSkeleton::from(&data);
+    let (record_index, conflict) = data.conflicts.iter().next().expect("a conflict");
+    let mut new_bone = skeleton.bones[0].to_owned();
+
+    new_bone.id = 0x20;
+    new_bone.name = String::from("KOSHI_NEW");
+    new_bone.name_address = None;
+    skeleton.bones.insert(*record_index, new_bone);
+
+    let read = NmdFileData::from(&skeleton);
+
+    assert_eq!(read.conflicts.keys().collect::<Vec<_>>(), data.conflicts.keys().collect::<Vec<_>>());
+    assert_eq!(read.conflict_physics.keys().collect::<Vec<_>>(), data.conflict_physics.keys().collect::<Vec<_>>());
+    assert_eq!(read.conflicts[record_index].name, conflict.name);
+    assert_eq!(read.records()[*record_index].id, conflict.id);
+    assert!(read.bones.contains_key(&0x20));
+}
@@ -0,0 +1,168 @@
+//! Each check of the validator, on seeds edited (or corpus files broken) to
+//! trip it, and how serious each finding is.
+
+mod common;
+
+use {
+    common::{
+        load,
+        read,
+        seed,
+        CONFLICTS_PATH,
+        CORPUS_DIR,
+    },
+    nmde::nmd::{
+        anatomy::NmdFileBoneFlag,
+        data::NmdFileData,
+        NmdFileFinding,
+        NmdFileFindingKind,
+        NmdFileSeverity,
+        NmdFileValidator,
+    },
+    std::fs,
+};
+
+fn findings(data: &NmdFileData) -> Vec<NmdFileFinding> {
+    NmdFileValidator::from(data).validate()
+}
+
+fn corpus(name: &str) -> NmdFileData {
+    read(fs::read(format!("{}/{}", CORPUS_DIR, name)).expect("corpus file")).expect("read")
+}
+
+#[test]
+fn parent_cycles_are_errors() {
+    let mut data = seed();
+
+    // MOMO_L and MOMO_R each other's parent
+    data.bones.get_mut(&0x01).expect("MOMO_L").parent_id = 0x02;
+    data.bones.get_mut(&0x02).expect("MOMO_R").parent_id = 0x01;
+
+    let findings = findings(&data);
+
+    assert!(matches!(
+        findings.as_slice(),
+        [finding] if matches!(&finding.kind, NmdFileFindingKind::ParentCycle(ids) if ids == &[0x01, 0x02])),
+        "{:?}", findings);
+    assert_eq!(findings[0].severity(), NmdFileSeverity::Error);
+    assert_eq!(findings[0].bone_id(), Some(0x01));
+}
+
+#[test]
+fn duplicate_names_are_warnings() {
+    let mut data = seed();
+
+    data.bones.get_mut(&0x02).expect("MOMO_R").name = String::from("MOMO_L");
+
+    let findings = findings(&data);
+
+    assert!(matches!(
+        findings.as_slice(),
+        [finding] if matches!(&finding.kind, NmdFileFindingKind::DuplicateName(0x02, name, 0x01) if name == "MOMO_L")),
+        "{:?}", findings);
+    assert_eq!(findings[0].severity(), NmdFileSeverity::Warning);
+}
+
+#[test]
+fn unencodable_names_are_errors() {
+    let mut data = seed();
+
+    data.bones.get_mut(&0x02).expect("MOMO_R").name = String::from("MOMO_€");
+
+    let findings = findings(&data);
+
+    assert!(matches!(
+        findings.as_slice(),
+        [finding] if matches!(finding.kind, NmdFileFindingKind::UnencodableName(0x02, _, '€'))),
+        "{:?}", findings);
+    assert_eq!(findings[0].severity(), NmdFileSeverity::Error);
+}
+
+#[test]
+fn unknown_flags_are_warnings() {
+    let mut data = seed();
+
+    data.bones.get_mut(&0x01).expect("MOMO_L").flag = NmdFileBoneFlag::Unknown(0x06);
+
+    let findings = findings(&data);
+
+    assert!(matches!(
+        findings.as_slice(),
+        [finding] if matches!(finding.kind, NmdFileFindingKind::UnknownFlag(0x01, 0x06))),
+        "{:?}", findings);
+    assert_eq!(findings[0].severity(), NmdFileSeverity::Warning);
+}
+
+#[test]
+fn name_addresses_outside_the_name_block_are_errors() {
+    let data = corpus("bone_name_address_past_end.nmd");
+    let findings = findings(&data);
+
+    assert!(matches!(
+        findings.as_slice(),
+        [finding] if matches!(finding.kind, NmdFileFindingKind::NameAddressOutOfBounds(0x00, 0xFFFF_FFFF, _))),
+        "{:?}", findings);
+    assert_eq!(findings[0].severity(), NmdFileSeverity::Error);
+}
+
+#[test]
+fn physics_addresses_outside_the_physics_block_are_errors() {
+    for name in ["physics_address_past_end.nmd", "physics_address_in_header.nmd"] {
+        let data = corpus(name);
+        let findings = findings(&data);
+
+        assert!(matches!(
+            findings.as_slice(),
+            [finding] if matches!(finding.kind, NmdFileFindingKind::PhysicsAddressOutOfBounds(0x03, ..))),
+            "{}: {:?}", name, findings);
+        assert_eq!(findings[0].severity(), NmdFileSeverity::Error);
+    }
+
+    // Only physics bones are held to the block
+    let mut data = seed();
+
+    data.bones.get_mut(&0x01).expect("MOMO_L").physics_data_address = 0xFFFF_FFFF;
+
+    assert!(findings(&data).is_empty());
+}
+
+#[test]
+fn orphans_are_warnings() {
+    let mut data = seed();
+
+    data.bones.get_mut(&0x02).expect("MOMO_R").parent_id = 0x77;
+
+    let findings = findings(&data);
+
+    assert!(matches!(
+        findings.as_slice(),
+        [finding] if matches!(finding.kind, NmdFileFindingKind::Orphan(0x02, 0x77))),
+        "{:?}", findings);
+    assert_eq!(findings[0].severity(), NmdFileSeverity::Warning);
+}
+
+#[test]
+fn duplicate_ids_are_warnings() {
+    let data = load(CONFLICTS_PATH);
+    let findings = findings(&data);
+
+    assert!(matches!(
+        findings.as_slice(),
+        [finding] if matches!(&finding.kind, NmdFileFindingKind::DuplicateId(0x03, name) if name == "KAMI_SWING")),
+        "{:?}", findings);
+    assert_eq!(findings[0].severity(), NmdFileSeverity::Warning);
+}
+
+#[test]
+fn findings_are_in_offset_order() {
+    let mut data = seed();
+
+    // Found in the opposite order to their records
+    data.bones.get_mut(&0x01).expect("MOMO_L").parent_id = 0x77;
+    data.bones.get_mut(&0x03).expect("KAMI_SWING").name = String::from("MOMO_R");
+
+    let offsets: Vec<Option<u64>> = findings(&data).iter().map(|finding| finding.offset).collect();
+
+    assert_eq!(offsets.len(), 2, "{:?}", offsets);
+    assert!(offsets[0] < offsets[1], "{:?}", offsets);
+}
@@ -0,0 +1,37 @@
+//! Unedited files write back byte for byte, including ones whose bone records
+//! share IDs and physics records.
+
+mod common;
+
+use {
+    common::CONFLICTS_PATH,
+    nmde::nmd::NmdFileVerifier,
+    std::fs,
+};
+
+fn verify(bytes: Vec<u8>) {
+    match NmdFileVerifier::try_from(bytes).and_then(|verifier| verifier.verify()) {
+        Ok(None) => {}
+        Ok(Some(divergence)) => panic!("{}", divergence),
+        Err(error) => panic!("{}", error),
+    }
+}
+
+#[test]
+fn conflicting_records_with_physics_survive() {
+    verify(fs::read(CONFLICTS_PATH).expect("seed"));
+}
+
+#[test]
+fn shared_physics_records_survive() {
+    let bytes = fs::read(CONFLICTS_PATH).expect("seed");
+
+    // Either bone with the shared ID pointing at the other's record, so both
+    // records are shared by a primary and a conflicting record in turn
+    for (record, address) in [(2, 0x1E0u32), (3, 0x1F0)] {
+        let mut bytes = bytes.to_vec();
+
+        bytes[0x20 + record * 0x70 + 0x38..][..4].copy_from_slice(&address.to_le_bytes());
+        verify(bytes);
+    }
+}
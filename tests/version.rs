@@ -0,0 +1,59 @@
+//! Files are read as the game whose header layout fits them, with SCIV and SCV
+//! reported as indistinguishable and headers no game fits refused.
+
+mod common;
+
+use {
+    common::CORPUS_DIR,
+    nmde::nmd::{
+        anatomy::NmdFileVersion,
+        data::NmdFileData,
+        NmdError,
+        NmdFileFindingKind,
+        NmdFileValidator,
+    },
+    std::fs,
+};
+
+fn read(name: &str) -> Result<NmdFileData, NmdError> {
+    common::read(fs::read(format!("{}/{}", CORPUS_DIR, name)).expect("corpus file"))
+}
+
+#[test]
+fn little_endian_files_are_scvi() {
+    let data = read("seed_scvi.nmd").expect("read");
+
+    assert_eq!(data.version, NmdFileVersion::Sc6);
+    assert!(data.other_versions.is_empty());
+    assert!(NmdFileValidator::from(&data).validate().is_empty());
+}
+
+#[test]
+fn big_endian_files_are_ambiguous() {
+    let data = read("seed_scv.nmd").expect("read");
+    let findings = NmdFileValidator::from(&data).validate();
+
+    assert_eq!(data.version, NmdFileVersion::Sc5);
+    assert_eq!(data.other_versions, [NmdFileVersion::Sc4]);
+    assert!(matches!(
+        findings.as_slice(),
+        [finding] if matches!(&finding.kind, NmdFileFindingKind::AmbiguousVersion(NmdFileVersion::Sc5, other_versions) if other_versions == &[NmdFileVersion::Sc4])));
+}
+
+#[test]
+fn mismatched_echoes_are_read_by_the_bone_data_address() {
+    let data = read("bone_count_echo_mismatch.nmd").expect("read");
+
+    assert_eq!(data.version, NmdFileVersion::Sc6);
+    assert!(NmdFileValidator::from(&data).validate().iter()
+        .any(|finding| matches!(finding.kind, NmdFileFindingKind::BoneCountMismatch(..))));
+}
+
+#[test]
+fn unfitting_headers_are_refused() {
+    for name in ["header_zeroed.nmd", "header_ff.nmd", "bone_data_address_zero.nmd"] {
+        assert!(matches!(read(name), Err(NmdError::UnknownVersion)), "{}", name);
+    }
+
+    assert!(matches!(read("short_header.nmd"), Err(NmdError::Truncated { .. })));
+}